@@ -0,0 +1,92 @@
+use criterion::{criterion_group, BenchmarkId, Criterion};
+use ratatui::backend::TestBackend;
+use ratatui::widgets::ListItem;
+use ratatui::Terminal;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use todo_list::app::AppState;
+use todo_list::item::{Priority, TodoItem};
+use todo_list::ui;
+
+const SIZES: [usize; 3] = [1_000, 5_000, 10_000];
+
+// Drawing 1 000 items takes longer than this on the CI machine, the budget fails the run
+const BUDGET: Duration = Duration::from_millis(5);
+
+// Every fourth item done, every third one tagged, some with a priority and a due date, so
+// each kind of span gets built
+fn items(count: usize) -> Vec<TodoItem> {
+    (0..count)
+        .map(|i| TodoItem {
+            done: i % 4 == 0,
+            priority: [Priority::None, Priority::Low, Priority::Medium, Priority::High][i % 4],
+            tags: if i % 3 == 0 { vec!["work".to_owned()] } else { vec![] },
+            due_date: (i % 5 == 0).then(|| chrono::Local::now().date_naive()),
+            ..TodoItem::from(format!("Item {} of the benchmark list", i))
+        })
+        .collect()
+}
+
+fn app_with(count: usize) -> AppState {
+    let mut app_state = AppState::default();
+    app_state.extend_items(items(count));
+    app_state.todo_list_state.select(Some(count / 2));
+    app_state
+}
+
+fn bench_render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("list_items");
+    for size in SIZES {
+        let app_state = app_with(size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &app_state, |b, app_state| {
+            b.iter(|| {
+                let items: Vec<ListItem> =
+                    app_state.items().iter().map(|item| item.to_list_item(&app_state.config, &app_state.config.theme)).collect();
+                black_box(items)
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("draw");
+    for size in SIZES {
+        let mut app_state = app_with(size);
+        let mut terminal = Terminal::new(TestBackend::new(80, 40)).unwrap();
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter(|| terminal.try_draw(|frame| ui::draw(frame, &mut app_state)).unwrap().area)
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("serialize");
+    for size in SIZES {
+        let items = items(size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &items, |b, items| {
+            b.iter(|| serde_json::to_string(black_box(items)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+// The fastest of a few draws, so one slow frame on a busy machine doesn't fail it
+fn check_budget() {
+    let mut app_state = app_with(1_000);
+    let mut terminal = Terminal::new(TestBackend::new(80, 40)).unwrap();
+    let fastest = (0..10)
+        .map(|_| {
+            let started = Instant::now();
+            terminal.try_draw(|frame| ui::draw(frame, &mut app_state)).unwrap();
+            started.elapsed()
+        })
+        .min()
+        .unwrap();
+    assert!(fastest < BUDGET, "drawing 1 000 items took {:?}, the budget is {:?}", fastest, BUDGET);
+}
+
+criterion_group!(benches, bench_render);
+
+fn main() {
+    check_budget();
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+}
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use todo_list::app::AppState;
+use todo_list::error::AppError;
+
+fuzz_target!(|text: &str| {
+    let _: Result<_, AppError> = AppState::import_google_tasks(text);
+});
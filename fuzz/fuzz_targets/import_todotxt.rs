@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use todo_list::app::AppState;
+
+// Lines that don't parse are skipped, there is no error to return
+fuzz_target!(|text: &str| {
+    let _ = AppState::import_todotxt(text);
+});
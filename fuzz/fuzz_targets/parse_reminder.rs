@@ -0,0 +1,11 @@
+#![no_main]
+
+use chrono::Local;
+use libfuzzer_sys::fuzz_target;
+use todo_list::config::Config;
+use todo_list::date_utils::parse_reminder;
+use todo_list::error::AppError;
+
+fuzz_target!(|text: &str| {
+    let _: Result<_, AppError> = parse_reminder(text, Local::now(), &Config::default());
+});
@@ -0,0 +1,2404 @@
+use crate::cli::Mutation;
+use crate::config::{Config, DoneItemsPosition, ListConfig, ListSettings, NewItemPosition, Session, StartupScreen, TodoTemplate};
+use crate::crypto;
+use crate::date_utils::{parse_due, parse_reminder};
+use crate::error::{AppError, IntegrityError};
+use crate::item::{Priority, TodoItem};
+use crate::parser::parse_inline_syntax;
+use crate::pomodoro::Pomodoro;
+use crate::qr::QrCode;
+use crate::search::Matcher;
+use crate::spell::{self, SpellChecker};
+use crate::stats;
+use crate::storage::{FileBackend, SaveFile, StorageBackend};
+use crate::strings::{fill, Strings};
+use crate::undo::{Snapshot, UndoStack};
+use crate::update;
+use crate::webhook::{self, WebhookEvent};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use notify_rust::Notification;
+use ratatui::crossterm::event::KeyEvent;
+use ratatui::layout::Rect;
+use ratatui::widgets::ListState;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::time::Instant;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use tui_input::Input;
+use tui_textarea::TextArea;
+use uuid::Uuid;
+
+pub const DEFAULT_LIST: &str = "TODO";
+pub const INBOX_LIST: &str = "Inbox";
+const SEARCH_HISTORY_LEN: usize = 20;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CurrentScreen {
+    Main,
+    Add,
+    Edit,
+    Inbox,
+    TemplatePicker,
+    CommandPalette,
+    ConfirmDedup,
+    SearchReplace,
+    ColorPicker,
+    ConfirmClearColor,
+    Focus,
+    NoteEditor,
+    AddComment,
+    PasswordPrompt,
+    Stats,
+    ConfirmClearDone,
+    ConfirmBulk,
+    Search,
+    ConfirmDiscardNote,
+    ConfirmDiscard,
+    DuplicateWarning,
+    SpellCheck,
+    GlobalSearch,
+    MergeListPicker,
+    ConfirmMerge,
+    SplitListName,
+    MoveToPosition,
+    ConfirmSplit,
+    PasteMultiple,
+    SaveFilterName,
+    LoadFilter,
+    ConfirmDelete,
+    RecentlyDone,
+    DailyPrompt,
+    WeeklyReview,
+    StaleItemsReview,
+    Timeline,
+    ItemDetail,
+    LinkSearch,
+    ParentPicker,
+    GithubImport,
+    ConfirmGithubImport,
+    Import,
+    BatchTag,
+    ThemePreview,
+    UndoHistory,
+    ContextMenu,
+    PriorityMenu,
+    SortMenu,
+    TagFilter,
+    Help,
+    Archive,
+    Exit,
+}
+
+impl CurrentScreen {
+    // Screens where printable keys go into a text input rather than triggering commands
+    pub fn is_text_input(&self) -> bool {
+        matches!(
+            self,
+            CurrentScreen::Add
+                | CurrentScreen::Edit
+                | CurrentScreen::Inbox
+                | CurrentScreen::CommandPalette
+                | CurrentScreen::SearchReplace
+                | CurrentScreen::NoteEditor
+                | CurrentScreen::AddComment
+                | CurrentScreen::PasswordPrompt
+                | CurrentScreen::Search
+                | CurrentScreen::GlobalSearch
+                | CurrentScreen::SplitListName
+                | CurrentScreen::MoveToPosition
+                | CurrentScreen::SaveFilterName
+                | CurrentScreen::DailyPrompt
+                | CurrentScreen::LinkSearch
+                | CurrentScreen::ParentPicker
+                | CurrentScreen::GithubImport
+                | CurrentScreen::Import
+                | CurrentScreen::BatchTag
+        )
+    }
+}
+
+// Fields of the Add screen in Tab order
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum AddField {
+    #[default]
+    Description,
+    Due,
+    Priority,
+    Tags,
+    Reminder,
+}
+
+impl AddField {
+    const ALL: [AddField; 5] = [
+        AddField::Description,
+        AddField::Due,
+        AddField::Priority,
+        AddField::Tags,
+        AddField::Reminder,
+    ];
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        Self::ALL[(self as usize + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Manual,
+    ByDueDate,
+    ByPriority,
+    ByDone,
+    Alphabetical,
+    // Overdue, due today, later, undated, then done, see smart_key
+    Smart,
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 6] = [
+        SortOrder::Manual,
+        SortOrder::ByDueDate,
+        SortOrder::ByPriority,
+        SortOrder::ByDone,
+        SortOrder::Alphabetical,
+        SortOrder::Smart,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortOrder::Manual => "Manual",
+            SortOrder::ByDueDate => "Due date",
+            SortOrder::ByPriority => "Priority",
+            SortOrder::ByDone => "Done last",
+            SortOrder::Alphabetical => "Alphabetical",
+            SortOrder::Smart => "Smart",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TodoList {
+    pub name: String,
+    items: Vec<TodoItem>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub list_config: Option<ListConfig>,
+}
+
+impl TodoList {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            items: vec![],
+            list_config: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&TodoItem> {
+        self.items.get(index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut TodoItem> {
+        self.items.get_mut(index)
+    }
+
+    pub fn remove(&mut self, index: usize) -> TodoItem {
+        self.items.remove(index)
+    }
+}
+
+// What narrows the main list, also kept by name in the config's saved_filters
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterState {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub query: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub fuzzy: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub incomplete_only: bool,
+    // A single tag, as written before several could be combined
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    // Items need every one of them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub due_today: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub overdue_only: bool,
+}
+
+impl FilterState {
+    pub fn is_empty(&self) -> bool {
+        *self == FilterState { fuzzy: self.fuzzy, ..FilterState::default() }
+    }
+
+    pub fn matches(&self, item: &TodoItem) -> bool {
+        self.matches_besides_query(item, Local::now().date_naive())
+            && (self.query.is_empty()
+                || Matcher::new(self.fuzzy).find(&self.query, &item.description).is_some())
+    }
+
+    fn matches_besides_query(&self, item: &TodoItem, today: NaiveDate) -> bool {
+        (!self.incomplete_only || !item.done)
+            && self.tag.iter().chain(&self.tags).all(|tag| item.tags.contains(tag))
+            && self.priority.is_none_or(|priority| item.priority == priority)
+            && (!self.due_today || item.due_date == Some(today))
+            && (!self.overdue_only || (!item.done && item.due_date.is_some_and(|due| due < today)))
+    }
+}
+
+pub struct AppState {
+    pub current_screen: CurrentScreen,
+    pub config: Config,
+    pub input: Input,
+    pub inbox_input: Input,
+    pub inbox_return_screen: CurrentScreen,
+    // The screen ? opened the help over, and how far the help is scrolled
+    pub help_return_screen: CurrentScreen,
+    pub help_scroll: u16,
+    pub input_error: Option<String>,
+    pub currently_editing: Option<TodoItem>,
+    pub edit_index: usize,
+    pub todo_list_state: ListState,
+    pub template_state: ListState,
+    // Due date, priority and tags of the Add screen, the description is `input` and the
+    // reminder `reminder_input` as on the Edit screen
+    pub add_fields: [Input; 3],
+    pub add_active_field: AddField,
+    pub lists: Vec<TodoList>,
+    pub active_list: usize,
+    pub render_window: (usize, usize),
+    pub undo_stack: UndoStack,
+    // Set by Ctrl+Z, an h right after it opens the undo history
+    pub undo_pending: bool,
+    // Problems integrity_check found after the last change, debug builds warn when it grows
+    pub integrity_errors: usize,
+    // Rows are the undo stack newest first
+    pub undo_history_state: ListState,
+    pub sort_menu_state: ListState,
+    pub tag_filter_state: ListState,
+    // Ids of the items marked with v, see bulk.rs
+    pub marked: BTreeSet<Uuid>,
+    pub status_message: Option<String>,
+    pub palette_input: Input,
+    pub palette_state: ListState,
+    pub find_input: Input,
+    pub replace_input: Input,
+    pub replace_field_active: bool,
+    pub search_case_sensitive: bool,
+    pub color_picker_index: usize,
+    pub note_editor: TextArea<'static>,
+    pub note_index: usize,
+    pub note_return_screen: CurrentScreen,
+    // Add or Edit, where n goes back to from ConfirmDiscard
+    pub discard_return_screen: CurrentScreen,
+    pub comment_input: Input,
+    pub comment_state: ListState,
+    // Drawn over the Focus screen until the next key
+    pub qr_code: Option<QrCode>,
+    // The filled in startup_message and when it goes away
+    pub startup_message: Option<(String, Instant)>,
+    pub pomodoro: Option<Pomodoro>,
+    pub password_input: Input,
+    pub password_attempts_left: u32,
+    pub password_error: Option<String>,
+    pub password: Option<String>,
+    pub locked_file: Option<Vec<u8>>,
+    // The save file couldn't be read, it is never saved over
+    pub load_failed: bool,
+    pub bulk_done: bool,
+    pub reminder_input: Input,
+    pub reminder_field_active: bool,
+    // Due date on the Edit screen, the Add screen has its own in add_fields
+    pub edit_due_input: Input,
+    pub search_input: Input,
+    pub search_fuzzy: bool,
+    // Queries that found something, oldest first
+    pub search_history: VecDeque<String>,
+    // The entry shown in the search input while going through the history
+    pub search_history_idx: Option<usize>,
+    pub export_pending: bool,
+    // Digits typed before a motion, "5" in 5j
+    pub numeric_prefix: String,
+    // Set by g, a second g goes to the first item as in vim
+    pub g_pending: bool,
+    // Where the left mouse button went down, for swipe gestures
+    pub drag_start: Option<(u16, u16)>,
+    // Row and time of the last left click, a second one on the row soon after edits the item
+    pub last_click: Option<(usize, Instant)>,
+    // Rows of the item being dragged to a new place and of the one under the mouse, which
+    // is at drag_position
+    pub drag_source: Option<usize>,
+    pub drag_target: usize,
+    pub drag_position: (u16, u16),
+    // Up/Down presses while held, for key_repeat_debounce_ms and scroll_acceleration
+    pub last_scroll_time: Instant,
+    pub last_scroll_key: Option<(bool, Instant)>,
+    pub scroll_held_since: Instant,
+    pub scroll_repeats: usize,
+    // Work running in the background, shown with a spinner in the status bar
+    pub background_op: Option<String>,
+    pub spinner_frame: u8,
+    // When spinner_frame last moved on, it follows the clock rather than the ticks
+    pub spinner_advanced: Instant,
+    // The list alone, without borders, title, progress or status bar
+    pub distraction_free: bool,
+    // One row per item, switched off with x to put a blank row after each item
+    pub compact_mode: bool,
+    // Long descriptions go on as many rows as they need instead of being cut off
+    pub wrap: bool,
+    // 1., 1.1, 1.2, 2. in front of the items
+    pub outline_mode: bool,
+    // Only items due today, Esc shows everything again
+    pub filter_due_today: bool,
+    // Picked with T or set by loading a saved filter, Esc clears them along with the rest
+    pub filter_tags: Vec<String>,
+    pub filter_priority: Option<Priority>,
+    pub filter_overdue: bool,
+    pub filter_hide_done: bool,
+    // Name of the saved filter loaded last, for the status bar
+    pub active_filter: Option<String>,
+    pub filter_name_input: Input,
+    pub position_input: Input,
+    pub saved_filter_state: ListState,
+    // Keys go to the list sidebar instead of the items, switched with Tab
+    pub sidebar_focused: bool,
+    pub sidebar_state: ListState,
+    pub overdue_first: bool,
+    // Keys recorded with Q and replayed with @q
+    pub macro_recording: bool,
+    pub macro_buffer: Vec<KeyEvent>,
+    pub macro_playing: bool,
+    pub macro_position: usize,
+    pub replay_pending: bool,
+    pub duplicate_of: Option<String>,
+    pub spell_checker: SpellChecker,
+    // Misspelled words per item, found when the item was last saved
+    pub misspelled: HashMap<Uuid, Vec<String>>,
+    // Words left to review on the spell check screen, by storage index
+    pub spell_queue: Vec<(usize, String)>,
+    pub spell_suggestions: Vec<String>,
+    pub spell_state: ListState,
+    pub global_search_input: Input,
+    pub global_search_state: ListState,
+    pub recent_state: ListState,
+    pub archived: Vec<TodoItem>,
+    pub archive_state: ListState,
+    pub daily_input: Input,
+    pub daily_count: usize,
+    // Index into ReviewStep::ALL
+    pub review_step: usize,
+    pub review_done: [bool; 4],
+    pub review_state: ListState,
+    pub review_editing_due: bool,
+    pub review_due_input: Input,
+    // Stale items kept as they are during this review
+    pub stale_dismissed: HashSet<Uuid>,
+    pub stale_state: ListState,
+    // First day drawn on the timeline
+    pub timeline_start: NaiveDate,
+    pub timeline_state: ListState,
+    pub link_input: Input,
+    pub link_state: ListState,
+    pub parent_input: Input,
+    pub parent_state: ListState,
+    // owner/repo typed on the GitHub import screen and the issues fetched from it
+    pub github_input: Input,
+    pub import_input: Input,
+    pub github_issues: Vec<TodoItem>,
+    pub tag_input: Input,
+    // Non-empty lines of a multi-line paste on the Add screen, waiting for y/n
+    pub pasted_lines: Vec<String>,
+    // The one item the tag goes to when opened from the context menu, else every visible one
+    pub tag_item: Option<usize>,
+    // Storage index of the right-clicked item and the cell clicked
+    pub context_menu_item: usize,
+    pub context_menu_position: (u16, u16),
+    pub context_menu_state: ListState,
+    // Where the menu and the list rows were last drawn, for telling what a click hit
+    pub context_menu_area: Rect,
+    pub item_area: Rect,
+    // Top row of each item drawn there, relative to item_area
+    pub item_rows: Vec<u16>,
+    // Set by the palette, run_app hands the terminal to $EDITOR and clears it
+    pub edit_config_pending: bool,
+    // The file --config named, or the default config path
+    pub config_file: Option<PathBuf>,
+    // Where Ctrl+E writes its files, --export-dir
+    pub export_dir: Option<PathBuf>,
+    // Text for run_app to put on the clipboard through the terminal
+    pub clipboard_pending: Option<String>,
+    // Index into theme::THEMES shown by the theme preview
+    pub theme_preview: usize,
+    // Lists other than the active one, the active list is merged into the selected one
+    pub merge_state: ListState,
+    pub split_name_input: Input,
+    pub strings: Strings,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self {
+            input: Input::default(),
+            config: Config::default(),
+            inbox_input: Input::default(),
+            inbox_return_screen: CurrentScreen::Main,
+            help_return_screen: CurrentScreen::Main,
+            help_scroll: 0,
+            current_screen: CurrentScreen::Main,
+            input_error: None,
+            currently_editing: None,
+            edit_index: 0,
+            todo_list_state: ListState::default(),
+            template_state: ListState::default(),
+            add_fields: Default::default(),
+            add_active_field: AddField::Description,
+            lists: vec![TodoList::new(DEFAULT_LIST), TodoList::new(INBOX_LIST)],
+            active_list: 0,
+            render_window: (0, 0),
+            undo_stack: UndoStack::default(),
+            undo_pending: false,
+            integrity_errors: 0,
+            undo_history_state: ListState::default(),
+            sort_menu_state: ListState::default(),
+            tag_filter_state: ListState::default(),
+            marked: BTreeSet::new(),
+            status_message: None,
+            palette_input: Input::default(),
+            palette_state: ListState::default(),
+            find_input: Input::default(),
+            replace_input: Input::default(),
+            replace_field_active: false,
+            search_case_sensitive: false,
+            color_picker_index: 0,
+            note_editor: TextArea::default(),
+            note_index: 0,
+            note_return_screen: CurrentScreen::Main,
+            discard_return_screen: CurrentScreen::Main,
+            comment_input: Input::default(),
+            comment_state: ListState::default(),
+            password_input: Input::default(),
+            password_attempts_left: 3,
+            password_error: None,
+            password: None,
+            locked_file: None,
+            load_failed: false,
+            bulk_done: true,
+            reminder_input: Input::default(),
+            reminder_field_active: false,
+            edit_due_input: Input::default(),
+            search_input: Input::default(),
+            search_fuzzy: true,
+            search_history: VecDeque::new(),
+            search_history_idx: None,
+            export_pending: false,
+            numeric_prefix: String::new(),
+            g_pending: false,
+            drag_start: None,
+            last_click: None,
+            drag_source: None,
+            drag_target: 0,
+            drag_position: (0, 0),
+            last_scroll_time: Instant::now(),
+            last_scroll_key: None,
+            scroll_held_since: Instant::now(),
+            scroll_repeats: 0,
+            background_op: None,
+            spinner_frame: 0,
+            spinner_advanced: Instant::now(),
+            distraction_free: false,
+            compact_mode: true,
+            wrap: false,
+            outline_mode: false,
+            filter_due_today: false,
+            filter_tags: vec![],
+            filter_priority: None,
+            filter_overdue: false,
+            filter_hide_done: false,
+            active_filter: None,
+            filter_name_input: Input::default(),
+            position_input: Input::default(),
+            saved_filter_state: ListState::default(),
+            sidebar_focused: false,
+            sidebar_state: ListState::default(),
+            overdue_first: false,
+            macro_recording: false,
+            macro_buffer: vec![],
+            macro_playing: false,
+            macro_position: 0,
+            replay_pending: false,
+            duplicate_of: None,
+            spell_checker: SpellChecker::default(),
+            misspelled: HashMap::new(),
+            spell_queue: vec![],
+            spell_suggestions: vec![],
+            spell_state: ListState::default(),
+            global_search_input: Input::default(),
+            global_search_state: ListState::default(),
+            recent_state: ListState::default(),
+            archived: vec![],
+            archive_state: ListState::default(),
+            daily_input: Input::default(),
+            daily_count: 0,
+            review_step: 0,
+            review_done: [false; 4],
+            review_state: ListState::default(),
+            review_editing_due: false,
+            review_due_input: Input::default(),
+            stale_dismissed: HashSet::new(),
+            stale_state: ListState::default(),
+            timeline_start: Local::now().date_naive(),
+            timeline_state: ListState::default(),
+            qr_code: None,
+            startup_message: None,
+            pomodoro: None,
+            link_input: Input::default(),
+            link_state: ListState::default(),
+            parent_input: Input::default(),
+            parent_state: ListState::default(),
+            github_input: Input::default(),
+            import_input: Input::default(),
+            github_issues: vec![],
+            tag_input: Input::default(),
+            pasted_lines: vec![],
+            tag_item: None,
+            context_menu_item: 0,
+            context_menu_position: (0, 0),
+            context_menu_state: ListState::default(),
+            context_menu_area: Rect::default(),
+            item_area: Rect::default(),
+            item_rows: vec![],
+            edit_config_pending: false,
+            config_file: None,
+            export_dir: None,
+            clipboard_pending: None,
+            theme_preview: 0,
+            merge_state: ListState::default(),
+            split_name_input: Input::default(),
+            strings: Strings::english(),
+        }
+    }
+}
+
+impl AppState {
+    // The lists saved at `path`, a missing file starts them empty. A file that can't be read
+    // leaves them empty too, with the error in the status bar, and is never saved over. Without
+    // the password an encrypted file waits for the password prompt. The caller holds
+    // storage::lock on `path`
+    pub fn new(config: Config, path: Option<&Path>, password: Option<String>) -> Self {
+        let mut app = Self::with_config(config);
+        let Some(path) = path else {
+            return app;
+        };
+        if app.config.encrypt && password.is_none() {
+            app.current_screen = CurrentScreen::PasswordPrompt;
+            match fs::read(path) {
+                Ok(data) => app.locked_file = Some(data),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => app.report_load_error(path, &e),
+            }
+            return app;
+        }
+        match FileBackend::new(path.to_owned(), password).load() {
+            Ok(save_file) => app.restore(save_file),
+            Err(e) => app.report_load_error(path, &e),
+        }
+        app
+    }
+
+    fn report_load_error(&mut self, path: &Path, error: &dyn fmt::Display) {
+        self.load_failed = true;
+        self.flash(fill(&self.strings.load_failed, &[("path", &path.display()), ("error", error)]));
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        Self {
+            strings: Strings::load(&config.language),
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn new_with_backend(backend: &impl StorageBackend) -> Result<Self, AppError> {
+        let mut app = Self::default();
+        app.restore(backend.load()?);
+        Ok(app)
+    }
+
+    pub fn items(&self) -> &Vec<TodoItem> {
+        &self.lists[self.active_list].items
+    }
+
+    fn items_mut(&mut self) -> &mut Vec<TodoItem> {
+        &mut self.lists[self.active_list].items
+    }
+
+    // Global config with the overrides of the active list applied
+    pub fn effective_config(&self) -> ListSettings<'_> {
+        match &self.lists[self.active_list].list_config {
+            Some(list_config) => list_config.apply(&self.config),
+            None => ListConfig::default().apply(&self.config),
+        }
+    }
+
+    // Indices into the active list of the items shown, in display order
+    pub fn visible_indices(&self) -> Vec<usize> {
+        let config = self.effective_config();
+        let items = self.items();
+        let today = Local::now().date_naive();
+        let filter = self.filter_state();
+        let shown = |&i: &usize| filter.matches_besides_query(&items[i], today);
+        let query = self.search_input.value();
+        if query.is_empty() {
+            let mut indices = self.items_matching_filter();
+            indices.sort_by(|&a, &b| compare_items(&items[a], &items[b], &config, today));
+            if self.overdue_first {
+                let overdue = |item: &TodoItem| !item.done && item.due_date.is_some_and(|due| due < today);
+                indices.sort_by_key(|&i| !overdue(&items[i]));
+            }
+            return self.nest_subtasks(indices);
+        }
+        // Searching narrows the list, fuzzy results come best match first
+        let mut scored = if self.search_fuzzy {
+            self.find_by_description_fuzzy(query)
+        } else {
+            self.find_by_description(query).into_iter().map(|i| (i, 0)).collect()
+        };
+        scored.retain(|(i, _)| shown(i));
+        scored.sort_by(|&(a, score_a), &(b, score_b)| {
+            score_b
+                .cmp(&score_a)
+                .then_with(|| compare_items(&items[a], &items[b], &config, today))
+        });
+        scored.into_iter().map(|(i, _)| i).collect()
+    }
+
+    // The indices with whether each starts a new group: done items moved to the top or
+    // bottom, each priority when sorted by it, overdue items shown first. Subtasks stay in
+    // their parent's group and search results aren't grouped
+    pub fn items_grouped<'a>(&'a self, indices: &'a [usize]) -> impl Iterator<Item = (bool, usize)> + 'a {
+        let config = self.effective_config();
+        let by_done = config.done_items_position != DoneItemsPosition::Mixed || config.sort_order == SortOrder::ByDone;
+        let by_priority = config.sort_order == SortOrder::ByPriority;
+        let grouped = self.search_input.value().is_empty() && (by_done || by_priority || self.overdue_first);
+        let today = Local::now().date_naive();
+        let items = self.items();
+        let mut previous = None;
+        indices.iter().map(move |&i| {
+            let item = &items[i];
+            if !grouped || item.parent_id.is_some() {
+                return (false, i);
+            }
+            let overdue = !item.done && item.due_date.is_some_and(|due| due < today);
+            let key = (self.overdue_first && overdue, by_done && item.done, by_priority.then_some(item.priority));
+            let starts = previous.is_some_and(|previous| previous != key);
+            previous = Some(key);
+            (starts, i)
+        })
+    }
+
+    // Indices of the items in the active list meeting every condition of the filter at once,
+    // in list order
+    pub fn items_matching_filter(&self) -> Vec<usize> {
+        let filter = self.filter_state();
+        let items = self.items();
+        (0..items.len()).filter(|&i| filter.matches(&items[i])).collect()
+    }
+
+    // Indices of the items in the active list containing the query, ignoring case and filters
+    pub fn find_by_description(&self, query: &str) -> Vec<usize> {
+        let query = query.to_lowercase();
+        let items = self.items();
+        (0..items.len())
+            .filter(|&i| items[i].description.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    // (index, score) of the items fuzzy matching the query, best match first
+    pub fn find_by_description_fuzzy(&self, query: &str) -> Vec<(usize, i64)> {
+        let matcher = Matcher::new(true);
+        let mut scored: Vec<(usize, i64)> = self
+            .items()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| Some((i, matcher.find(query, &item.description)?.0)))
+            .collect();
+        scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+        scored
+    }
+
+    // Char positions in the description that match the current search
+    pub fn search_matches(&self, item: &TodoItem) -> Vec<usize> {
+        let query = self.search_input.value();
+        if query.is_empty() {
+            return vec![];
+        }
+        Matcher::new(self.search_fuzzy)
+            .find(query, &item.description)
+            .map(|(_, positions)| positions)
+            .unwrap_or_default()
+    }
+
+    // (list, storage index) of every item in any list whose description or notes match
+    pub fn global_search_results(&self) -> Vec<(usize, usize)> {
+        let query = self.global_search_input.value();
+        if query.is_empty() {
+            return vec![];
+        }
+        let matcher = Matcher::new(self.search_fuzzy);
+        let matches = |text: &str| matcher.find(query, text).is_some();
+        self.lists
+            .iter()
+            .enumerate()
+            .flat_map(|(l, list)| list.items.iter().enumerate().map(move |(i, item)| (l, i, item)))
+            .filter(|(_, _, item)| {
+                matches(&item.description) || item.notes.as_deref().is_some_and(matches)
+            })
+            .map(|(l, i, _)| (l, i))
+            .collect()
+    }
+
+    // Re-read the config file after it was edited, the old settings stay on errors
+    pub fn reload_config(&mut self) -> Result<(), AppError> {
+        let Some(path) = &self.config_file else {
+            return Ok(());
+        };
+        self.config = Config::read(path)?;
+        self.strings = Strings::load(&self.config.language);
+        Ok(())
+    }
+
+    // Ask for the day's top tasks once a day, when enabled
+    pub fn daily_review_due(&self) -> bool {
+        let today = Local::now().date_naive();
+        self.config.enable_daily_review && self.config.last_daily_review != Some(today)
+    }
+
+    pub fn finish_daily_review(&mut self) {
+        self.daily_input.reset();
+        self.current_screen = CurrentScreen::Main;
+        self.config.last_daily_review = Some(Local::now().date_naive());
+        if let Err(e) = self.save_config() {
+            self.flash(fill(&self.strings.config_save_failed, &[("error", &e)]));
+        }
+        if !self.stale_items().is_empty() {
+            self.start_stale_review();
+        }
+    }
+
+    // Look for a newer release once a day, the answer shows up in the status bar
+    pub fn start_update_check(&mut self) {
+        let today = Local::now().date_naive();
+        if !self.config.check_for_updates || self.config.last_update_check == Some(today) {
+            return;
+        }
+        update::check_in_background();
+        self.config.last_update_check = Some(today);
+        if let Err(e) = self.save_config() {
+            self.flash(fill(&self.strings.config_save_failed, &[("error", &e)]));
+        }
+    }
+
+    // Items of the active list with a due date, soonest first
+    pub fn timeline_items(&self) -> Vec<usize> {
+        let items = self.items();
+        let mut indices: Vec<usize> = (0..items.len()).filter(|&i| items[i].due_date.is_some()).collect();
+        indices.sort_by_key(|&i| items[i].due_date);
+        indices
+    }
+
+    pub fn open_timeline(&mut self) {
+        // The start of last week, so the week labels line up
+        let week_ago = Local::now().date_naive() - Duration::days(7);
+        let weekday = week_ago.weekday().days_since(self.config.first_day_of_week);
+        self.timeline_start = week_ago - Duration::days(weekday as i64);
+        let any = !self.timeline_items().is_empty();
+        self.timeline_state.select(any.then_some(0));
+        self.current_screen = CurrentScreen::Timeline;
+    }
+
+    // Items of the active list completed in the last 24 hours, latest first
+    pub fn recently_done(&self) -> Vec<usize> {
+        let since = Local::now() - Duration::hours(24);
+        let items = self.items();
+        let mut indices: Vec<usize> = (0..items.len())
+            .filter(|&i| items[i].done && items[i].completed_at.is_some_and(|at| at >= since))
+            .collect();
+        indices.sort_by_key(|&i| std::cmp::Reverse(items[i].completed_at));
+        indices
+    }
+
+    // Move one item to the end of another list
+    pub fn move_to_list(&mut self, list: usize, index: usize, target: usize) {
+        if list == target || index >= self.lists[list].len() {
+            return;
+        }
+        let item = self.lists[list].items.remove(index);
+        self.lists[target].items.push(item);
+    }
+
+    // Candidate merge targets, every list except the active one
+    pub fn merge_targets(&self) -> Vec<usize> {
+        (0..self.lists.len()).filter(|&l| l != self.active_list).collect()
+    }
+
+    pub fn merge_target(&self) -> Option<usize> {
+        self.merge_state.selected().and_then(|i| self.merge_targets().get(i).copied())
+    }
+
+    // Move every item of `source` into `target` and delete `source`. Items with the same id
+    // keep the copy completed last
+    pub fn merge_lists(&mut self, source: usize, target: usize) {
+        if source == target || source >= self.lists.len() || target >= self.lists.len() {
+            return;
+        }
+        for item in std::mem::take(&mut self.lists[source].items) {
+            let items = &mut self.lists[target].items;
+            match items.iter_mut().find(|existing| existing.id == item.id) {
+                Some(existing) if item.completed_at > existing.completed_at => *existing = item,
+                Some(_) => {}
+                None => items.push(item),
+            }
+        }
+        self.lists.remove(source);
+        self.active_list = if target > source { target - 1 } else { target };
+        self.todo_list_state = ListState::default();
+    }
+
+    pub fn filter_state(&self) -> FilterState {
+        FilterState {
+            query: self.search_input.value().to_owned(),
+            fuzzy: self.search_fuzzy,
+            incomplete_only: self.effective_config().hide_done() || self.filter_hide_done,
+            tag: None,
+            tags: self.filter_tags.clone(),
+            priority: self.filter_priority,
+            due_today: self.filter_due_today,
+            overdue_only: self.filter_overdue,
+        }
+    }
+
+    // Keep the query for Up in the search input, once and as the newest
+    pub fn record_search(&mut self) {
+        self.search_history_idx = None;
+        let query = self.search_input.value().trim().to_owned();
+        if query.is_empty() || self.visible_indices().is_empty() {
+            return;
+        }
+        self.search_history.retain(|q| *q != query);
+        self.search_history.push_back(query);
+        while self.search_history.len() > SEARCH_HISTORY_LEN {
+            self.search_history.pop_front();
+        }
+    }
+
+    // Put an older or newer history entry into the search input, past the newest it empties
+    pub fn recall_search(&mut self, older: bool) {
+        let len = self.search_history.len();
+        self.search_history_idx = match (self.search_history_idx, older) {
+            (None, true) => len.checked_sub(1),
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i + 1 < len => Some(i + 1),
+            (_, false) => None,
+        };
+        let query = self.search_history_idx.and_then(|i| self.search_history.get(i));
+        self.search_input = Input::new(query.cloned().unwrap_or_default());
+        self.todo_list_state.select(Some(0));
+    }
+
+    // Replace whatever narrows the list with `filter`. Done items hidden by the config stay hidden
+    pub fn apply_filter(&mut self, filter: &FilterState) {
+        self.search_input = Input::new(filter.query.clone());
+        self.search_fuzzy = filter.fuzzy;
+        self.filter_hide_done = filter.incomplete_only;
+        self.filter_tags = filter.tag.iter().chain(&filter.tags).cloned().collect();
+        self.filter_priority = filter.priority;
+        self.filter_due_today = filter.due_today;
+        self.filter_overdue = filter.overdue_only;
+        self.active_filter = None;
+        self.todo_list_state.select(Some(0));
+    }
+
+    // Whether anything besides the config narrows the list, which Esc would clear
+    pub fn has_filter(&self) -> bool {
+        !self.search_input.value().is_empty()
+            || self.filter_due_today
+            || !self.filter_tags.is_empty()
+            || self.filter_priority.is_some()
+            || self.filter_overdue
+            || self.filter_hide_done
+    }
+
+    pub fn clear_filters(&mut self) {
+        self.search_input.reset();
+        self.filter_due_today = false;
+        self.filter_tags.clear();
+        self.filter_priority = None;
+        self.filter_overdue = false;
+        self.filter_hide_done = false;
+        self.active_filter = None;
+    }
+
+    // Save the current filter under `name`, replacing one saved with that name
+    pub fn save_filter(&mut self, name: &str) -> Result<(), AppError> {
+        if name.trim().is_empty() {
+            return Err(AppError::InvalidInput("filter name can't be empty".to_owned()));
+        }
+        let mut filter = self.filter_state();
+        // Done items hidden by the config aren't part of the filter
+        filter.incomplete_only = self.filter_hide_done;
+        self.config.saved_filters.insert(name.trim().to_owned(), filter);
+        self.save_config()
+    }
+
+    pub fn validate_list_name(&self, name: &str) -> Result<(), AppError> {
+        if name.trim().is_empty() {
+            return Err(AppError::InvalidInput("list name can't be empty".to_owned()));
+        }
+        if self.lists.iter().any(|list| list.name == name.trim()) {
+            return Err(AppError::InvalidInput(format!("a list named '{}' exists", name.trim())));
+        }
+        Ok(())
+    }
+
+    // Move the items of the active list matching `filter` into a new list, which becomes active
+    pub fn split_list_by_filter(
+        &mut self,
+        filter: &FilterState,
+        new_name: &str,
+    ) -> Result<usize, AppError> {
+        self.validate_list_name(new_name)?;
+        let (matching, rest) = std::mem::take(self.items_mut())
+            .into_iter()
+            .partition(|item| filter.matches(item));
+        *self.items_mut() = rest;
+        let mut list = TodoList::new(new_name.trim());
+        list.items = matching;
+        self.lists.push(list);
+        self.active_list = self.lists.len() - 1;
+        self.search_input.reset();
+        self.todo_list_state = ListState::default();
+        Ok(self.active_list)
+    }
+
+    // Add the tag to the items at the storage indices that don't have it yet, undone in one step
+    pub fn add_tag_to_items(&mut self, tag: &str, indices: &[usize]) {
+        self.checkpoint("Tag items");
+        for &i in indices {
+            if let Some(item) = self.get_mut(i) {
+                if !item.tags.iter().any(|t| t == tag) {
+                    item.tags.push(tag.to_owned());
+                }
+            }
+        }
+    }
+
+    // Switch to the list holding a global search result and select it
+    pub fn open_search_result(&mut self, list: usize, index: usize) {
+        self.active_list = list;
+        self.clear_filters();
+        self.select_storage_index(index);
+    }
+
+    // Visible items in display order
+    pub fn iter(&self) -> impl Iterator<Item = &TodoItem> {
+        let items = self.items();
+        self.visible_indices().into_iter().map(move |i| &items[i])
+    }
+
+    // The items iter yields, in the same order
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut TodoItem> {
+        let indices = self.visible_indices();
+        let mut items: Vec<Option<&mut TodoItem>> = self.items_mut().iter_mut().map(Some).collect();
+        indices.into_iter().filter_map(move |i| items[i].take())
+    }
+
+    // Every item of the active list in storage order
+    pub fn iter_unfiltered(&self) -> std::slice::Iter<'_, TodoItem> {
+        self.items().iter()
+    }
+
+    // Number of visible items
+    pub fn items_len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn item_at(&self, logical_index: usize) -> Option<&TodoItem> {
+        self.iter().nth(logical_index)
+    }
+
+    // Storage index of the highlighted item
+    pub fn selected_item_index(&self) -> Option<usize> {
+        let selected = self.todo_list_state.selected()?;
+        self.visible_indices().get(selected).copied()
+    }
+
+    // Repeat count of the next motion, 1 without a prefix
+    pub fn consume_prefix(&mut self) -> usize {
+        let count = self.numeric_prefix.parse().unwrap_or(1);
+        self.numeric_prefix.clear();
+        count.max(1)
+    }
+
+    // Rows a press of Up/Down moves, None drops a key repeat that came too fast
+    pub fn scroll_step(&mut self, down: bool) -> Option<usize> {
+        let now = Instant::now();
+        // Terminals repeat a held key well within this, the first repeat after a delay
+        let held = self
+            .last_scroll_key
+            .is_some_and(|(last_down, at)| last_down == down && now.duration_since(at).as_millis() < 600);
+        self.last_scroll_key = Some((down, now));
+        if !held {
+            self.scroll_held_since = now;
+            self.scroll_repeats = 0;
+        } else if now.duration_since(self.last_scroll_time).as_millis() < self.config.key_repeat_debounce_ms.into() {
+            return None;
+        }
+        self.last_scroll_time = now;
+        self.scroll_repeats += 1;
+        let accelerate = self.config.scroll_acceleration
+            && self.scroll_repeats > 5
+            && now.duration_since(self.scroll_held_since).as_millis() > 300;
+        Some(if accelerate { 2 } else { 1 })
+    }
+
+    // Move the selection down (or up) by count rows, stopping at either end
+    pub fn select_by(&mut self, count: usize, down: bool) {
+        let len = self.visible_indices().len();
+        if len == 0 {
+            return;
+        }
+        let selected = self.todo_list_state.selected().unwrap_or(0);
+        let selected = if down { selected.saturating_add(count) } else { selected.saturating_sub(count) };
+        self.todo_list_state.select(Some(selected.min(len - 1)));
+    }
+
+    pub fn select_next_wrap(&mut self) {
+        let len = self.visible_indices().len();
+        match self.todo_list_state.selected() {
+            Some(i) if self.config.wrap_navigation && len > 0 && i + 1 >= len => {
+                self.todo_list_state.select(Some(0))
+            }
+            _ => self.todo_list_state.select_next(),
+        }
+    }
+
+    pub fn select_previous_wrap(&mut self) {
+        let len = self.visible_indices().len();
+        match self.todo_list_state.selected() {
+            Some(0) if self.config.wrap_navigation && len > 0 => {
+                self.todo_list_state.select(Some(len - 1))
+            }
+            _ => self.todo_list_state.select_previous(),
+        }
+    }
+
+    // Clamp the selection to the visible items and scroll the window of rendered rows so
+    // the selection stays in view
+    pub fn update_render_window(&mut self, height: usize, len: usize) {
+        self.clamp_selection(len);
+        let selected = self.todo_list_state.selected();
+        let mut start = self.render_window.0;
+        if let Some(selected) = selected {
+            if selected < start {
+                start = selected;
+            } else if selected >= start + height {
+                start = selected + 1 - height;
+            }
+        }
+        start = start.min(len.saturating_sub(height));
+        self.render_window = (start, (start + height).min(len));
+    }
+
+    pub fn get(&self, index: usize) -> Option<&TodoItem> {
+        self.items().get(index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut TodoItem> {
+        self.items_mut().get_mut(index)
+    }
+
+    // Insert where the config asks and select the new item
+    pub fn add_item(&mut self, todo_item: TodoItem) {
+        let index = match self.config.new_item_position {
+            NewItemPosition::Top => 0,
+            NewItemPosition::Bottom => self.items().len(),
+            NewItemPosition::AfterSelected => self
+                .selected_item_index()
+                .map_or(self.items().len(), |i| i + 1),
+        };
+        self.notify(WebhookEvent::ItemAdded, &todo_item);
+        self.items_mut().insert(index, todo_item);
+        self.select_storage_index(index);
+    }
+
+    pub fn notify(&self, event: WebhookEvent, item: &TodoItem) {
+        webhook::notify(&self.config, event, item);
+    }
+
+    // Description of the first item in the list close enough to count as a duplicate
+    pub fn similar_item(&self, description: &str) -> Option<String> {
+        let description = description.trim().to_lowercase();
+        self.items()
+            .iter()
+            .find(|item| {
+                strsim::jaro_winkler(&description, &item.description.to_lowercase())
+                    > self.config.duplicate_threshold
+            })
+            .map(|item| item.description.clone())
+    }
+
+    pub fn extend_items(&mut self, todo_items: impl IntoIterator<Item = TodoItem>) {
+        self.items_mut().extend(todo_items);
+    }
+
+    // Insert an undone copy with a new id right after the item and select it. Links are
+    // not copied, the linked items would only point back at the original
+    pub fn duplicate_item(&mut self, index: usize) {
+        let Some(item) = self.get(index) else {
+            return;
+        };
+        let copy = TodoItem {
+            id: Uuid::new_v4(),
+            done: false,
+            created_at: Local::now(),
+            completed_at: None,
+            links: vec![],
+            ..item.clone()
+        };
+        self.checkpoint("Duplicate");
+        self.notify(WebhookEvent::ItemAdded, &copy);
+        self.items_mut().insert(index + 1, copy);
+        self.select_storage_index(index + 1);
+    }
+
+    pub fn set_priority(&mut self, index: usize, priority: Priority) {
+        if self.get(index).is_some_and(|item| item.priority != priority) {
+            self.checkpoint("Set priority");
+            if let Some(item) = self.get_mut(index) {
+                item.priority = priority;
+            }
+        }
+    }
+
+    // + and - on the main list, None when nothing changed. Without a due date + makes the
+    // item due today; - into the past clears the date unless allow_past_due_dates is set
+    pub fn shift_due(&mut self, index: usize, days: i64) -> Option<Option<NaiveDate>> {
+        let item = self.get(index).filter(|item| !item.done)?;
+        let today = Local::now().date_naive();
+        let due = match item.due_date {
+            None if days > 0 => Some(today),
+            None => return None,
+            Some(due) => Some(due + Duration::days(days)).filter(|&due| due >= today || self.config.allow_past_due_dates),
+        };
+        self.checkpoint("Reschedule");
+        self.get_mut(index)?.due_date = due;
+        Some(due)
+    }
+
+    pub fn remove_at(&mut self, index: usize) {
+        let item = self.items_mut().remove(index);
+        self.notify(WebhookEvent::ItemDeleted, &item);
+        self.normalize_selection();
+    }
+
+    pub fn move_item_to_top(&mut self, index: usize) {
+        if self.items().len() > 1 && index < self.items().len() {
+            self.checkpoint("Move to top");
+            let item = self.items_mut().remove(index);
+            self.items_mut().insert(0, item);
+            self.select_storage_index(0);
+        }
+    }
+
+    pub fn move_item_to_bottom(&mut self, index: usize) {
+        if self.items().len() > 1 && index < self.items().len() {
+            self.checkpoint("Move to bottom");
+            let item = self.items_mut().remove(index);
+            self.items_mut().push(item);
+            self.select_storage_index(self.items().len() - 1);
+        }
+    }
+
+    // Take the item at `index` out and put it back at `position`, counted from 0
+    pub fn move_item_to_position(&mut self, index: usize, position: usize) {
+        if index < self.items().len() && position < self.items().len() && index != position {
+            self.checkpoint("Move to position");
+            let item = self.items_mut().remove(index);
+            self.items_mut().insert(position, item);
+            self.select_storage_index(position);
+        }
+    }
+
+    // Swap the selected item with the one shown above (or below) it
+    pub fn move_selected(&mut self, up: bool) {
+        let indices = self.visible_indices();
+        let Some(row) = self.todo_list_state.selected().filter(|&row| row < indices.len()) else {
+            return;
+        };
+        let Some(other) = (if up { row.checked_sub(1) } else { Some(row + 1) }).and_then(|r| indices.get(r)) else {
+            return;
+        };
+        self.swap_items(indices[row], *other);
+    }
+
+    // Swap two items of the active list by storage index, keeping the selection on the
+    // item that was at `a`
+    pub fn swap_items(&mut self, a: usize, b: usize) {
+        if a == b || a.max(b) >= self.items().len() {
+            return;
+        }
+        self.checkpoint(if b < a { "Move up" } else { "Move down" });
+        self.items_mut().swap(a, b);
+        self.select_storage_index(b);
+    }
+
+    // Move the item shown at row `from` to where row `to` is, shifting the ones in between
+    pub fn reorder(&mut self, from: usize, to: usize) {
+        let indices = self.visible_indices();
+        let (Some(&source), Some(&target)) = (indices.get(from), indices.get(to)) else {
+            return;
+        };
+        if source == target {
+            return;
+        }
+        self.checkpoint("Move");
+        let item = self.items_mut().remove(source);
+        self.items_mut().insert(target, item);
+        self.select_storage_index(target);
+    }
+
+    // Row of the first incomplete item shown after `row`, wrapping around
+    pub fn next_incomplete_after(&self, row: usize) -> Option<usize> {
+        let indices = self.visible_indices();
+        let items = self.items();
+        (1..=indices.len()).map(|step| (row + step) % indices.len()).find(|&r| !items[indices[r]].done)
+    }
+
+    // Move a selection left past the end of the shown items, by deleting or filtering them
+    // away, to the last one, or drop it when nothing is shown. Marks on items no longer in the
+    // active list go as well
+    pub fn normalize_selection(&mut self) {
+        let len = self.visible_indices().len();
+        self.clamp_selection(len);
+        if !self.marked.is_empty() {
+            let items = &self.lists[self.active_list].items;
+            self.marked.retain(|id| items.iter().any(|item| item.id == *id));
+        }
+    }
+
+    fn clamp_selection(&mut self, len: usize) {
+        let selected = match self.todo_list_state.selected() {
+            Some(_) if len == 0 => None,
+            Some(selected) => Some(selected.min(len - 1)),
+            None => None,
+        };
+        if selected != self.todo_list_state.selected() {
+            self.todo_list_state.select(selected);
+        }
+    }
+
+    // Select the row showing the item at `index`
+    pub fn select_storage_index(&mut self, index: usize) {
+        let selected = self.visible_indices().iter().position(|&i| i == index);
+        self.todo_list_state.select(selected);
+    }
+
+    pub fn replace(&mut self, todo_item: TodoItem, index: usize) {
+        self.notify(WebhookEvent::ItemEdited, &todo_item);
+        self.items_mut()[index] = todo_item;
+    }
+
+    // Load the notes of the item at `index` into the note editor
+    pub fn open_notes(&mut self, index: usize) {
+        let notes = self.get(index).and_then(|item| item.notes.clone()).unwrap_or_default();
+        self.note_editor = TextArea::from(notes.lines());
+        self.note_index = index;
+    }
+
+    pub fn note_text(&self) -> String {
+        self.note_editor.lines().join("\n")
+    }
+
+    pub fn notes_changed(&self) -> bool {
+        let saved = self.get(self.note_index).and_then(|item| item.notes.as_deref());
+        saved.unwrap_or_default() != self.note_text()
+    }
+
+    pub fn save_notes(&mut self) {
+        if !self.notes_changed() {
+            return;
+        }
+        let text = self.note_text();
+        self.checkpoint("Edit notes");
+        if let Some(item) = self.get_mut(self.note_index) {
+            item.notes = if text.trim().is_empty() { None } else { Some(text) };
+        }
+    }
+
+    pub fn note_word_count(&self) -> usize {
+        word_count(&self.note_text())
+    }
+
+    // The Inbox is always present, recreate it if it went missing
+    pub fn inbox_index(&mut self) -> usize {
+        match self.lists.iter().position(|l| l.name == INBOX_LIST) {
+            Some(index) => index,
+            None => {
+                self.lists.push(TodoList::new(INBOX_LIST));
+                self.lists.len() - 1
+            }
+        }
+    }
+
+    pub fn inbox_len(&self) -> usize {
+        self.lists
+            .iter()
+            .find(|l| l.name == INBOX_LIST)
+            .map_or(0, |l| l.len())
+    }
+
+    pub fn add_to_inbox(&mut self, todo_item: TodoItem) {
+        let inbox = self.inbox_index();
+        self.notify(WebhookEvent::ItemAdded, &todo_item);
+        self.lists[inbox].items.push(todo_item);
+    }
+
+    // Briefly shown in the status bar until the next key press
+    pub fn flash(&mut self, message: impl Into<String>) {
+        self.status_message = Some(message.into());
+    }
+
+    // Remember the current state so the next change can be undone
+    pub fn checkpoint(&mut self, label: &str) {
+        // What the change before this one left behind
+        #[cfg(debug_assertions)]
+        self.warn_integrity();
+        let snapshot = self.snapshot(label);
+        self.undo_stack.push(snapshot, self.config.undo_max_depth);
+    }
+
+    fn snapshot(&self, label: &str) -> Snapshot {
+        Snapshot {
+            label: label.to_owned(),
+            at: Local::now(),
+            lists: self.lists.clone(),
+            archived: self.archived.clone(),
+            active_list: self.active_list,
+        }
+    }
+
+    pub fn undo(&mut self) {
+        match self.undo_stack.undo(self.snapshot("")) {
+            Some(snapshot) => {
+                self.flash(fill(&self.strings.undid, &[("label", &snapshot.label)]));
+                self.restore_snapshot(snapshot);
+            }
+            None => self.flash(self.strings.nothing_to_undo.clone()),
+        }
+    }
+
+    pub fn redo(&mut self) {
+        match self.undo_stack.redo(self.snapshot("")) {
+            Some(snapshot) => {
+                self.flash(fill(&self.strings.redid, &[("label", &snapshot.label)]));
+                self.restore_snapshot(snapshot);
+            }
+            None => self.flash(self.strings.nothing_to_redo.clone()),
+        }
+    }
+
+    // Starts on the order the list is sorted by now
+    pub fn open_sort_menu(&mut self) {
+        let current = self.effective_config().sort_order;
+        self.sort_menu_state.select(SortOrder::ALL.iter().position(|&order| order == current));
+        self.current_screen = CurrentScreen::SortMenu;
+    }
+
+    // Kept with the active list, only the displayed order changes so Manual gets the
+    // list order back
+    pub fn set_sort_order(&mut self, sort_order: SortOrder) {
+        let list = &mut self.lists[self.active_list];
+        list.list_config.get_or_insert_with(ListConfig::default).sort_order = Some(sort_order);
+        self.normalize_selection();
+    }
+
+    // Tags of the items shown not filtered by yet, sorted
+    pub fn unfiltered_tags(&self) -> Vec<String> {
+        let items = self.items();
+        let tags: BTreeSet<&String> = self.visible_indices().iter().flat_map(|&i| &items[i].tags).collect();
+        tags.into_iter().filter(|tag| !self.filter_tags.contains(tag)).cloned().collect()
+    }
+
+    pub fn open_tag_filter(&mut self) {
+        if self.unfiltered_tags().is_empty() {
+            self.flash(self.strings.no_tags.clone());
+        } else {
+            self.tag_filter_state.select(Some(0));
+            self.current_screen = CurrentScreen::TagFilter;
+        }
+    }
+
+    // Narrow the list further to the items that also carry `tag`
+    pub fn add_tag_filter(&mut self, tag: String) {
+        self.filter_tags.push(tag);
+        self.active_filter = None;
+        self.todo_list_state.select(Some(0));
+    }
+
+    pub fn open_undo_history(&mut self) {
+        if self.undo_stack.can_undo() {
+            self.undo_history_state.select(Some(0));
+            self.current_screen = CurrentScreen::UndoHistory;
+        } else {
+            self.flash(self.strings.nothing_to_undo.clone());
+        }
+    }
+
+    // Undo everything back to and including the undo stack entry at `index`
+    pub fn undo_to(&mut self, index: usize) {
+        if let Some(snapshot) = self.undo_stack.pop_to(index, self.snapshot("")) {
+            self.flash(fill(&self.strings.undid, &[("label", &snapshot.label)]));
+            self.restore_snapshot(snapshot);
+        }
+    }
+
+    fn restore_snapshot(&mut self, snapshot: Snapshot) {
+        self.lists = snapshot.lists;
+        self.archived = snapshot.archived;
+        self.active_list = snapshot.active_list.min(self.lists.len() - 1);
+    }
+
+    // Indices of items repeating an earlier description (trimmed, case-folded), all but
+    // the oldest of each group
+    fn duplicate_indices(&self) -> Vec<usize> {
+        let items = self.items();
+        let mut oldest: HashMap<String, usize> = HashMap::new();
+        let mut duplicates = vec![];
+        for (index, item) in items.iter().enumerate() {
+            let key = item.description.trim().to_lowercase();
+            match oldest.get(&key) {
+                Some(&kept) if items[kept].created_at <= item.created_at => duplicates.push(index),
+                Some(&kept) => {
+                    duplicates.push(kept);
+                    oldest.insert(key, index);
+                }
+                None => {
+                    oldest.insert(key, index);
+                }
+            }
+        }
+        duplicates
+    }
+
+    pub fn duplicate_count(&self) -> usize {
+        self.duplicate_indices().len()
+    }
+
+    pub fn deduplicate(&mut self) -> usize {
+        let mut duplicates = self.duplicate_indices();
+        if duplicates.is_empty() {
+            return 0;
+        }
+        self.checkpoint("Remove duplicates");
+        duplicates.sort_unstable();
+        for &index in duplicates.iter().rev() {
+            self.items_mut().remove(index);
+        }
+        duplicates.len()
+    }
+
+    // Replace in the descriptions and notes of every list, returns the number of occurrences
+    // replaced
+    pub fn search_replace(&mut self, find: &str, replace: &str, case_sensitive: bool) -> usize {
+        if find.is_empty() {
+            return 0;
+        }
+        let count: usize = self
+            .lists
+            .iter()
+            .flat_map(|list| list.items.iter())
+            .flat_map(|item| std::iter::once(&item.description).chain(&item.notes))
+            .map(|text| replace_all(text, find, replace, case_sensitive).1)
+            .sum();
+        if count == 0 {
+            return 0;
+        }
+        self.checkpoint("Search and replace");
+        for item in self.lists.iter_mut().flat_map(|list| list.items.iter_mut()) {
+            for text in std::iter::once(&mut item.description).chain(&mut item.notes) {
+                *text = replace_all(text, find, replace, case_sensitive).0;
+            }
+        }
+        count
+    }
+
+    pub fn save_file(&self) -> SaveFile {
+        SaveFile {
+            lists: self.lists.clone(),
+            archived: self.archived.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, save_file: SaveFile) {
+        if !save_file.lists.is_empty() {
+            self.lists = save_file.lists;
+        }
+        self.archived = save_file.archived;
+        self.inbox_index();
+        self.active_list = 0;
+        #[cfg(debug_assertions)]
+        self.warn_integrity();
+        let config = self.config.clone();
+        self.apply_startup_screen(&config);
+    }
+
+    pub fn apply_startup_screen(&mut self, config: &Config) {
+        if config.persist_session {
+            let session = &config.session;
+            self.apply_filter(&FilterState {
+                query: session.search.clone(),
+                fuzzy: self.search_fuzzy,
+                incomplete_only: session.incomplete_only,
+                tag: session.tag.clone(),
+                tags: session.tags.clone(),
+                priority: session.priority,
+                due_today: session.due_today,
+                overdue_only: session.overdue_only,
+            });
+            self.todo_list_state.select(None);
+            self.search_history = session.search_history.iter().cloned().collect();
+        }
+        match config.startup_screen {
+            StartupScreen::AllItems | StartupScreen::LastSelected => {}
+            StartupScreen::TodayView => self.filter_due_today = true,
+            StartupScreen::OverdueFirst => self.overdue_first = true,
+        }
+        if config.persist_session && (config.restore_selection || config.startup_screen == StartupScreen::LastSelected) {
+            self.restore_session();
+        }
+    }
+
+    // Select the saved item, or the next visible one after it when it is gone or filtered out
+    fn restore_session(&mut self) {
+        let session = self.config.session.clone();
+        if session.list >= self.lists.len() {
+            return;
+        }
+        self.active_list = session.list;
+        let Some(saved) = session.selected else {
+            return;
+        };
+        let indices = self.visible_indices();
+        let saved = saved.min(self.items().len().saturating_sub(1));
+        let row = indices
+            .iter()
+            .position(|&i| i == saved)
+            .or_else(|| {
+                let next = indices.iter().filter(|&&i| i > saved).min()?;
+                indices.iter().position(|i| i == next)
+            })
+            .or_else(|| indices.len().checked_sub(1));
+        self.todo_list_state.select(row);
+    }
+
+    // Written right after each change rather than on exit, see storage::save_atomic
+    pub fn save_config(&self) -> Result<(), AppError> {
+        self.config.save()
+    }
+
+    pub fn save_session(&mut self) -> Result<(), AppError> {
+        let session = Session {
+            list: self.active_list,
+            selected: self.selected_item_index(),
+            search: self.search_input.value().to_owned(),
+            due_today: self.filter_due_today,
+            tag: None,
+            tags: self.filter_tags.clone(),
+            priority: self.filter_priority,
+            overdue_only: self.filter_overdue,
+            incomplete_only: self.filter_hide_done,
+            search_history: self.search_history.iter().cloned().collect(),
+        };
+        if !self.config.persist_session || session == self.config.session {
+            return Ok(());
+        }
+        self.config.session = session;
+        self.save_config()
+    }
+
+    // Decrypt the save file read at startup, a missing file takes any password
+    pub fn unlock(&mut self, password: String) -> Result<(), AppError> {
+        if let Some(data) = &self.locked_file {
+            let save_file = serde_json::from_slice(&crypto::decrypt(data, &password)?)?;
+            self.restore(save_file);
+            self.locked_file = None;
+        }
+        self.password = Some(password);
+        Ok(())
+    }
+
+    pub fn done_count(&self) -> usize {
+        self.items().iter().filter(|item| item.done).count()
+    }
+
+    // Drop every completed item of the active list
+    pub fn remove_all_done(&mut self) -> usize {
+        let count = self.done_count();
+        if count > 0 {
+            self.checkpoint("Clear completed");
+            let (removed, kept): (Vec<_>, Vec<_>) =
+                std::mem::take(self.items_mut()).into_iter().partition(|item| item.done);
+            *self.items_mut() = kept;
+            for item in &removed {
+                self.notify(WebhookEvent::ItemDeleted, item);
+            }
+            self.normalize_selection();
+        }
+        count
+    }
+
+    // Items of the active list that marking everything done (or undone) would change
+    pub fn bulk_affected(&self, done: bool) -> usize {
+        self.items().iter().filter(|item| item.done != done).count()
+    }
+
+    pub fn mark_all_done(&mut self) -> usize {
+        self.mark_all(true)
+    }
+
+    pub fn mark_all_undone(&mut self) -> usize {
+        self.mark_all(false)
+    }
+
+    fn mark_all(&mut self, done: bool) -> usize {
+        let count = self.bulk_affected(done);
+        if count > 0 {
+            self.checkpoint(if done { "Mark all done" } else { "Mark all undone" });
+            for i in 0..self.items().len() {
+                let item = &mut self.items_mut()[i];
+                if item.done != done {
+                    item.set_done(done);
+                    if done {
+                        self.notify(WebhookEvent::ItemCompleted, &self.items()[i]);
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    pub fn all_items(&self) -> impl Iterator<Item = &TodoItem> {
+        self.lists.iter().flat_map(|list| list.items.iter())
+    }
+
+    // Subtasks follow their parent in the given order, as deep as they are nested
+    fn nest_subtasks(&self, indices: Vec<usize>) -> Vec<usize> {
+        let items = self.items();
+        if indices.iter().all(|&i| items[i].parent_id.is_none()) {
+            return indices;
+        }
+        let shown: HashSet<Uuid> = indices.iter().map(|&i| items[i].id).collect();
+        let mut children: HashMap<Uuid, Vec<usize>> = HashMap::new();
+        let mut roots = vec![];
+        for &i in &indices {
+            match items[i].parent_id {
+                Some(parent) if shown.contains(&parent) => children.entry(parent).or_default().push(i),
+                _ => roots.push(i),
+            }
+        }
+        let mut nested = Vec::with_capacity(indices.len());
+        let mut seen = HashSet::new();
+        // (index, shown), everything under a collapsed item is skipped
+        let mut stack: Vec<(usize, bool)> = roots.into_iter().rev().map(|i| (i, true)).collect();
+        while let Some((i, shown)) = stack.pop() {
+            if !seen.insert(i) {
+                continue;
+            }
+            if shown {
+                nested.push(i);
+            }
+            if let Some(kids) = children.get(&items[i].id) {
+                let shown = shown && !items[i].collapsed;
+                stack.extend(kids.iter().rev().map(|&kid| (kid, shown)));
+            }
+        }
+        // Items whose parents form a loop have no root, keep them at the end
+        nested.extend(indices.into_iter().filter(|i| !seen.contains(i)));
+        nested
+    }
+
+    // Position of each shown item among its siblings in display order, the parent's number
+    // first: 1., 1.1, 1.1.1
+    pub fn compute_outline_numbers(&self) -> HashMap<Uuid, String> {
+        let items = self.items();
+        let mut numbers: HashMap<Uuid, String> = HashMap::new();
+        let mut counts: HashMap<Option<Uuid>, usize> = HashMap::new();
+        for i in self.visible_indices() {
+            // Parents come before their subtasks, one that isn't shown makes this a top-level item
+            let parent = items[i].parent_id.filter(|parent| numbers.contains_key(parent));
+            let count = counts.entry(parent).or_default();
+            *count += 1;
+            let number = match parent.and_then(|parent| numbers.get(&parent)) {
+                Some(prefix) => format!("{}.{}", prefix.trim_end_matches('.'), count),
+                None => format!("{}.", count),
+            };
+            numbers.insert(items[i].id, number);
+        }
+        numbers
+    }
+
+    // Number of ancestors of the item in the active list, for indenting
+    pub fn subtask_depth(&self, index: usize) -> usize {
+        let items = self.items();
+        let mut depth = 0;
+        let mut parent = items[index].parent_id;
+        while let Some(id) = parent {
+            let Some(p) = items.iter().position(|item| item.id == id) else { break };
+            depth += 1;
+            if depth >= items.len() {
+                break;
+            }
+            parent = items[p].parent_id;
+        }
+        depth
+    }
+
+    // Turn the subtask at the storage index into an item of its own, placed after its
+    // former siblings
+    pub fn promote_subtask(&mut self, index: usize) {
+        let Some(parent) = self.get(index).and_then(|item| item.parent_id) else {
+            return;
+        };
+        self.checkpoint("Promote subtask");
+        let items = self.items_mut();
+        let mut item = items.remove(index);
+        item.parent_id = None;
+        let anchor = items
+            .iter()
+            .rposition(|other| other.parent_id == Some(parent))
+            .or_else(|| items.iter().position(|other| other.id == parent));
+        let to = anchor.map_or(items.len(), |anchor| anchor + 1);
+        items.insert(to, item);
+        self.select_storage_index(to);
+    }
+
+    // Whether `ancestor` is the parent of the item at the storage index, or its parent's...
+    fn descends_from(&self, index: usize, ancestor: Uuid) -> bool {
+        let items = self.items();
+        let mut parent = items[index].parent_id;
+        for _ in 0..items.len() {
+            let Some(id) = parent else { break };
+            if id == ancestor {
+                return true;
+            }
+            parent = items.iter().find(|item| item.id == id).and_then(|item| item.parent_id);
+        }
+        false
+    }
+
+    pub fn toggle_collapse(&mut self, index: usize) {
+        let Some(id) = self.get(index).map(|item| item.id) else {
+            return;
+        };
+        if !self.items().iter().any(|item| item.parent_id == Some(id)) {
+            return;
+        }
+        let collapsed = &mut self.items_mut()[index].collapsed;
+        *collapsed = !*collapsed;
+        self.select_storage_index(index);
+    }
+
+    // Subtasks of the item at the storage index at any depth, what collapsing it hides
+    pub fn descendant_count(&self, index: usize) -> usize {
+        let id = self.items()[index].id;
+        (0..self.items().len()).filter(|&i| self.descends_from(i, id)).count()
+    }
+
+    // Possible parents of the selected item matching the picker query: every item of the
+    // list except the item itself and its subtasks
+    pub fn parent_candidates(&self) -> Vec<usize> {
+        let Some(child) = self.selected_item_index() else {
+            return vec![];
+        };
+        let id = self.items()[child].id;
+        let query = self.parent_input.value();
+        let matcher = Matcher::new(true);
+        (0..self.items().len())
+            .filter(|&i| i != child && !self.descends_from(i, id))
+            .filter(|&i| query.is_empty() || matcher.find(query, &self.items()[i].description).is_some())
+            .collect()
+    }
+
+    // Make the item a subtask of another one, None makes it a top-level item
+    pub fn reparent(&mut self, child_index: usize, new_parent_id: Option<Uuid>) {
+        let Some(child) = self.get(child_index) else {
+            return;
+        };
+        let id = child.id;
+        if let Some(parent) = new_parent_id {
+            let parent_index = self.items().iter().position(|item| item.id == parent);
+            if parent == id || parent_index.is_some_and(|p| self.descends_from(p, id)) {
+                self.flash(self.strings.parent_cycle.clone());
+                return;
+            }
+        }
+        self.checkpoint("Move subtask");
+        self.items_mut()[child_index].parent_id = new_parent_id;
+        self.select_storage_index(child_index);
+    }
+
+    // (done, total) of the item's subtasks
+    pub fn subtask_progress(&self, parent_id: Uuid) -> (usize, usize) {
+        self.subtask_counts().get(&parent_id).copied().unwrap_or_default()
+    }
+
+    // subtask_progress of every parent in one pass, for drawing a whole list
+    pub fn subtask_counts(&self) -> HashMap<Uuid, (usize, usize)> {
+        let mut counts: HashMap<Uuid, (usize, usize)> = HashMap::new();
+        for item in self.all_items() {
+            if let Some(parent) = item.parent_id {
+                let (done, total) = counts.entry(parent).or_default();
+                *done += usize::from(item.done);
+                *total += 1;
+            }
+        }
+        counts
+    }
+
+    pub fn current_streak(&self) -> u32 {
+        stats::current_streak(self.all_items(), Local::now().date_naive())
+    }
+
+    // Remember a new best streak in the config
+    pub fn update_streak(&mut self) {
+        let current = self.current_streak();
+        if current > self.config.longest_streak {
+            self.config.longest_streak = current;
+            if let Err(e) = self.save_config() {
+                self.flash(fill(&self.strings.config_save_failed, &[("error", &e)]));
+            }
+        }
+    }
+
+    pub fn next_list(&mut self) {
+        self.switch_list((self.active_list + 1) % self.lists.len());
+    }
+
+    pub fn switch_list(&mut self, list: usize) {
+        self.active_list = list;
+        self.todo_list_state = ListState::default();
+    }
+
+    // Pre-fill the Add screen from a template, the description stays editable
+    pub fn apply_template(&mut self, template: &TodoTemplate) {
+        self.input = template.description.clone().into();
+        let due = template.due_offset_days.map(|days| {
+            let due = Local::now().date_naive() + Duration::days(days);
+            due.format(&self.config.date_format).to_string()
+        });
+        let priority = (template.priority != Priority::None).then(|| template.priority.label().to_owned());
+        self.add_fields = [
+            due.unwrap_or_default().into(),
+            priority.unwrap_or_default().into(),
+            template.tags.join(" ").into(),
+        ];
+    }
+
+    pub fn set_add_field(&mut self, field: AddField) {
+        self.add_active_field = field;
+        self.reminder_field_active = field == AddField::Reminder;
+    }
+
+    // Due date, priority and tags typed into the Add screen
+    fn add_field_values(&self) -> Result<(Option<NaiveDate>, Priority, Vec<String>), AppError> {
+        let [due, priority, tags] = &self.add_fields;
+        let due = parse_due(due.value(), Local::now().date_naive(), &self.config)?;
+        let priority = match priority.value().trim() {
+            "" => Priority::None,
+            text => Priority::parse(text).ok_or_else(|| {
+                AppError::InvalidInput(format!("invalid priority \"{}\", use low, medium or high", text))
+            })?,
+        };
+        let tags = tags
+            .value()
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .map(|tag| tag.trim_start_matches('#'))
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_owned)
+            .collect();
+        Ok((due, priority, tags))
+    }
+
+    pub fn reset_add_fields(&mut self) {
+        self.input.reset();
+        self.reminder_input.reset();
+        for field in &mut self.add_fields {
+            field.reset();
+        }
+        self.set_add_field(AddField::Description);
+    }
+
+    // An empty field means no reminder
+    pub fn reminder_from_input(&self) -> Result<Option<DateTime<Local>>, AppError> {
+        match self.reminder_input.value().trim() {
+            "" => Ok(None),
+            text => parse_reminder(text, Local::now(), &self.config).map(Some),
+        }
+    }
+
+    // The next recorded key while a macro plays
+    pub fn next_macro_key(&mut self) -> Option<KeyEvent> {
+        if !self.macro_playing {
+            return None;
+        }
+        let key = self.macro_buffer.get(self.macro_position).copied();
+        self.macro_position += 1;
+        if key.is_none() {
+            self.macro_playing = false;
+        }
+        key
+    }
+
+    // Debug builds point out the first new problem a change introduced in the status bar, checked
+    // on load, before each undo step and after each command line change
+    #[cfg(debug_assertions)]
+    fn warn_integrity(&mut self) {
+        let errors = self.integrity_check();
+        if errors.len() > self.integrity_errors {
+            if let Some(error) = errors.last() {
+                self.flash(format!("Integrity: {}", error));
+            }
+        }
+        self.integrity_errors = errors.len();
+    }
+
+    // Every problem found in the lists, for --doctor and the checks of debug builds
+    pub fn integrity_check(&self) -> Vec<IntegrityError> {
+        let mut errors = vec![];
+        let mut seen = HashSet::new();
+        for item in self.all_items() {
+            if !seen.insert(item.id) {
+                errors.push(IntegrityError::DuplicateUuid(item.id));
+            }
+            if item.completed_at.is_some_and(|at| at < item.created_at) {
+                errors.push(IntegrityError::CompletedBeforeCreated(item.id));
+            }
+        }
+        for list in &self.lists {
+            let parents: HashMap<Uuid, Option<Uuid>> =
+                list.items.iter().map(|item| (item.id, item.parent_id)).collect();
+            for item in &list.items {
+                if let Some(parent) = item.parent_id.filter(|parent| !parents.contains_key(parent)) {
+                    errors.push(IntegrityError::OrphanedSubtask { child_id: item.id, missing_parent: parent });
+                }
+            }
+            // Walk up from each item, a walk that comes back to an id on its own path found a
+            // cycle, reported once from its smallest id
+            let mut cycles = HashSet::new();
+            for item in &list.items {
+                let mut path = vec![item.id];
+                let mut current = item.parent_id;
+                while let Some(id) = current.filter(|id| parents.contains_key(id)) {
+                    if let Some(start) = path.iter().position(|&seen| seen == id) {
+                        let mut cycle = path.split_off(start);
+                        let smallest = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap_or(0);
+                        cycle.rotate_left(smallest);
+                        if cycles.insert(cycle.clone()) {
+                            errors.push(IntegrityError::BlockingCycle(cycle));
+                        }
+                        break;
+                    }
+                    path.push(id);
+                    current = parents[&id];
+                }
+            }
+        }
+        errors
+    }
+
+    // A frame for every spinner_interval since the last one, however often tick runs
+    fn advance_spinner(&mut self) {
+        let interval = self.config.spinner_interval();
+        let frames = self.spinner_advanced.elapsed().as_millis() / interval.as_millis();
+        if frames > 0 {
+            self.spinner_frame = self.spinner_frame.wrapping_add(frames as u8);
+            self.spinner_advanced += interval * frames as u32;
+        }
+    }
+
+    pub fn show_startup_message(&mut self) {
+        if let Some(message) = &self.config.startup_message {
+            let pending = self.all_items().filter(|item| !item.done).count();
+            let text = fill(message, &[("pending", &pending)]);
+            self.startup_message = Some((text, Instant::now() + std::time::Duration::from_secs(3)));
+        }
+    }
+
+    pub fn tick(&mut self) {
+        self.advance_spinner();
+        self.update_pomodoro();
+        if self.startup_message.as_ref().is_some_and(|(_, until)| *until <= Instant::now()) {
+            self.startup_message = None;
+        }
+        self.background_op = match webhook::pending() {
+            0 => None,
+            count => Some(fill(&self.strings.sending_webhooks, &[("count", &count)])),
+        };
+        if let Some(version) = update::take_newer_version() {
+            self.flash(fill(&self.strings.update_available, &[("version", &version)]));
+        }
+        // Notify about reminders that are due, each one fires once
+        let now = Local::now();
+        let mut fired = vec![];
+        for item in self.lists.iter_mut().flat_map(|list| list.items.iter_mut()) {
+            if item.reminder_at.is_some_and(|at| at <= now) {
+                item.reminder_at = None;
+                fired.push(item.description.clone());
+            }
+        }
+        for description in fired {
+            show_notification("todo-tui reminder", description.clone());
+            self.flash(fill(&self.strings.reminder, &[("description", &description)]));
+        }
+    }
+
+    // One item per line, each with the priority, tags and due date on the Add screen
+    pub fn add_pasted_items(&mut self, lines: &[String]) -> Result<(), AppError> {
+        let (due, priority, tags) = self.add_field_values()?;
+        let mut todo_items = vec![];
+        for line in lines {
+            let mut builder = TodoItem::builder().description(line).priority(priority).tags(tags.clone());
+            if let Some(due) = due {
+                builder = builder.due(due);
+            }
+            todo_items.push(builder.build()?);
+        }
+        self.checkpoint("Paste items");
+        for todo_item in todo_items {
+            self.add_item(todo_item);
+        }
+        self.reset_add_fields();
+        Ok(())
+    }
+
+    // Build the new item from the Add screen and reset it for the next one, the input is
+    // kept when validation fails
+    pub fn take_add_draft(&mut self) -> Result<TodoItem, AppError> {
+        let (due, priority, tags) = self.add_field_values()?;
+        // The fields fill in whatever the description didn't set inline
+        let mut todo_item = parse_inline_syntax(self.input.value())?;
+        if todo_item.priority == Priority::None {
+            todo_item.priority = priority;
+        }
+        todo_item.due_date = todo_item.due_date.or(due);
+        for tag in tags {
+            if !todo_item.tags.contains(&tag) {
+                todo_item.tags.push(tag);
+            }
+        }
+        todo_item.reminder_at = self.reminder_from_input()?;
+        self.reset_add_fields();
+        Ok(todo_item)
+    }
+}
+
+// Stable comparison for display, ties keep insertion order
+// A desktop notification, sent from a thread of its own so a slow notification daemon can't
+// hold up the UI
+pub fn show_notification(summary: &'static str, body: String) {
+    thread::spawn(move || {
+        let _ = Notification::new().summary(summary).body(&body).show();
+    });
+}
+
+fn compare_items(a: &TodoItem, b: &TodoItem, config: &ListSettings, today: NaiveDate) -> Ordering {
+    let done_order = match config.done_items_position {
+        DoneItemsPosition::Mixed | DoneItemsPosition::Hidden => Ordering::Equal,
+        DoneItemsPosition::Top => b.done.cmp(&a.done),
+        DoneItemsPosition::Bottom => a.done.cmp(&b.done),
+    };
+    done_order.then_with(|| compare_by(a, b, config.sort_order, today))
+}
+
+fn compare_by(a: &TodoItem, b: &TodoItem, sort_order: SortOrder, today: NaiveDate) -> Ordering {
+    match sort_order {
+        SortOrder::Manual => Ordering::Equal,
+        SortOrder::ByDueDate => match (a.due_date, b.due_date) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        },
+        SortOrder::ByPriority => b.priority.cmp(&a.priority),
+        SortOrder::ByDone => a.done.cmp(&b.done),
+        SortOrder::Alphabetical => a
+            .description
+            .to_lowercase()
+            .cmp(&b.description.to_lowercase()),
+        SortOrder::Smart => smart_key(a, today).cmp(&smart_key(b, today)),
+    }
+}
+
+// Overdue items by due date, due today by priority, later ones by due date then priority,
+// undated ones by priority and done items last
+fn smart_key(item: &TodoItem, today: NaiveDate) -> (u8, Option<NaiveDate>, Reverse<Priority>) {
+    let priority = Reverse(item.priority);
+    match item.due_date {
+        _ if item.done => (4, None, Reverse(Priority::None)),
+        Some(due) if due < today => (0, Some(due), priority),
+        Some(due) if due == today => (1, None, priority),
+        Some(due) => (2, Some(due), priority),
+        None => (3, None, priority),
+    }
+}
+
+// Replacement that can ignore case without assuming lowercasing keeps byte offsets
+fn replace_all(haystack: &str, find: &str, replace: &str, case_sensitive: bool) -> (String, usize) {
+    if case_sensitive {
+        return (haystack.replace(find, replace), haystack.matches(find).count());
+    }
+    let needle: Vec<char> = find.chars().flat_map(char::to_lowercase).collect();
+    let mut result = String::with_capacity(haystack.len());
+    let mut count = 0;
+    let mut rest = haystack;
+    while !rest.is_empty() {
+        match match_len_ignore_case(rest, &needle) {
+            Some(len) => {
+                result.push_str(replace);
+                rest = &rest[len..];
+                count += 1;
+            }
+            None => {
+                let c = rest.chars().next().unwrap_or_default();
+                result.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    (result, count)
+}
+
+// Byte length of the prefix of `s` matching the lowercased needle
+fn match_len_ignore_case(s: &str, needle: &[char]) -> Option<usize> {
+    let mut matched = 0;
+    for (offset, c) in s.char_indices() {
+        if matched == needle.len() {
+            return Some(offset);
+        }
+        for lower in c.to_lowercase() {
+            if needle.get(matched) != Some(&lower) {
+                return None;
+            }
+            matched += 1;
+        }
+    }
+    (matched == needle.len()).then_some(s.len())
+}
+
+pub fn word_count(text: &str) -> usize {
+    text.split(char::is_whitespace).filter(|word| !word.is_empty()).count()
+}
+
+impl AppState {
+    // Apply a command line change, returning what happened for the user
+    pub fn apply_mutation(&mut self, mutation: &Mutation) -> Result<String, AppError> {
+        let result = self.mutate(mutation);
+        #[cfg(debug_assertions)]
+        self.warn_integrity();
+        result
+    }
+
+    fn mutate(&mut self, mutation: &Mutation) -> Result<String, AppError> {
+        match mutation {
+            Mutation::Add(description) => {
+                let item = TodoItem::builder().description(description).build()?;
+                let id = item.id;
+                self.notify(WebhookEvent::ItemAdded, &item);
+                self.lists[0].items.push(item);
+                Ok(format!("Added {}", id))
+            }
+            Mutation::MarkDone(id) | Mutation::Unmark(id) | Mutation::Toggle(id) => {
+                let (list, index) = self.find_by_id(id)?;
+                let item = &mut self.lists[list].items[index];
+                let done = match mutation {
+                    Mutation::MarkDone(_) => true,
+                    Mutation::Unmark(_) => false,
+                    _ => !item.done,
+                };
+                let completed = done && !item.done;
+                item.set_done(done);
+                if completed {
+                    self.notify(WebhookEvent::ItemCompleted, &self.lists[list].items[index]);
+                }
+                Ok(self.lists[list].items[index].description.clone())
+            }
+            Mutation::Delete(id) => {
+                let (list, index) = self.find_by_id(id)?;
+                let item = self.lists[list].items.remove(index);
+                self.notify(WebhookEvent::ItemDeleted, &item);
+                Ok(format!("Deleted {}", id))
+            }
+        }
+    }
+
+    pub fn item_by_id(&self, id: &str) -> Result<&TodoItem, AppError> {
+        let (list, index) = self.find_by_id(id)?;
+        Ok(&self.lists[list].items[index])
+    }
+
+    // Overwrite the fields present in `patch`, a JSON object in the save file format
+    pub fn update_item(
+        &mut self,
+        id: &str,
+        patch: serde_json::Value,
+    ) -> Result<&TodoItem, AppError> {
+        let (list, index) = self.find_by_id(id)?;
+        let item = &mut self.lists[list].items[index];
+        let serde_json::Value::Object(patch) = patch else {
+            return Err(AppError::InvalidInput("expected a JSON object".to_owned()));
+        };
+        let mut value = serde_json::to_value(&*item)?;
+        if let serde_json::Value::Object(fields) = &mut value {
+            fields.extend(patch);
+        }
+        let updated = TodoItem {
+            id: item.id,
+            ..serde_json::from_value(value)?
+        };
+        TodoItem::validate_description(&updated.description)?;
+        webhook::notify(&self.config, WebhookEvent::ItemEdited, &updated);
+        let item = &mut self.lists[list].items[index];
+        *item = updated;
+        Ok(item)
+    }
+
+    // List and item index of the item with this id, in any list. A prefix of at least 4 hex
+    // digits is enough when only one item's id starts with it
+    pub fn find_by_id(&self, id: &str) -> Result<(usize, usize), AppError> {
+        let prefix = match Uuid::parse_str(id) {
+            Ok(uuid) => uuid.to_string(),
+            Err(_) => id.to_ascii_lowercase(),
+        };
+        if prefix.len() < 4 || !prefix.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+            return Err(AppError::InvalidInput(format!("invalid id '{}'", id)));
+        }
+        let mut matches = self.lists.iter().enumerate().flat_map(|(l, list)| {
+            list.items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| item.id.to_string().starts_with(&prefix))
+                .map(move |(index, _)| (l, index))
+        });
+        let found = matches
+            .next()
+            .ok_or_else(|| AppError::NotFound(format!("no item with id {}", id)))?;
+        if matches.next().is_some() {
+            return Err(AppError::InvalidInput(format!("more than one item's id starts with {}", id)));
+        }
+        Ok(found)
+    }
+}
+
+impl AppState {
+    pub fn check_spelling(&mut self, item: &TodoItem) {
+        let misspelled = self.spell_checker.misspelled(&item.description);
+        if misspelled.is_empty() {
+            self.misspelled.remove(&item.id);
+        } else {
+            self.misspelled.insert(item.id, misspelled);
+        }
+    }
+
+    // Char positions of the misspelled words in the item, for underlining
+    pub fn misspelled_chars(&self, item: &TodoItem) -> Vec<usize> {
+        let Some(misspelled) = self.misspelled.get(&item.id) else {
+            return vec![];
+        };
+        spell::words(&item.description)
+            .filter(|(_, word)| misspelled.iter().any(|w| w == word))
+            .flat_map(|(start, word)| start..start + word.chars().count())
+            .collect()
+    }
+
+    // Queue every misspelled word in the list, false when there is nothing to review
+    pub fn start_spell_check(&mut self) -> bool {
+        self.spell_queue = self
+            .items()
+            .iter()
+            .enumerate()
+            .flat_map(|(i, item)| {
+                self.spell_checker
+                    .misspelled(&item.description)
+                    .into_iter()
+                    .map(move |word| (i, word))
+            })
+            .collect();
+        self.load_suggestions();
+        !self.spell_queue.is_empty()
+    }
+
+    fn load_suggestions(&mut self) {
+        self.spell_suggestions = match self.spell_queue.first() {
+            Some((_, word)) => self.spell_checker.suggest(word),
+            None => vec![],
+        };
+        self.spell_state.select((!self.spell_suggestions.is_empty()).then_some(0));
+    }
+
+    // Move on to the next misspelled word, false once all were reviewed
+    pub fn skip_misspelled(&mut self) -> bool {
+        if !self.spell_queue.is_empty() {
+            self.spell_queue.remove(0);
+        }
+        self.load_suggestions();
+        !self.spell_queue.is_empty()
+    }
+
+    // Replace the current word with the selected suggestion
+    pub fn accept_suggestion(&mut self) -> bool {
+        let suggestion = self
+            .spell_state
+            .selected()
+            .and_then(|i| self.spell_suggestions.get(i))
+            .cloned();
+        let current = self.spell_queue.first().cloned();
+        if let (Some((index, word)), Some(suggestion)) = (current, suggestion) {
+            if let Some(item) = self.items().get(index).cloned() {
+                let start = spell::words(&item.description)
+                    .find(|(_, w)| *w == word)
+                    .and_then(|(start, _)| item.description.char_indices().nth(start));
+                if let Some((offset, _)) = start {
+                    self.checkpoint("Spell check");
+                    let mut description = item.description.clone();
+                    description.replace_range(offset..offset + word.len(), &suggestion);
+                    let item = TodoItem { description, ..item };
+                    self.check_spelling(&item);
+                    self.replace(item, index);
+                }
+            }
+        }
+        self.skip_misspelled()
+    }
+}
@@ -0,0 +1,61 @@
+use crate::app::AppState;
+use crate::item::TodoItem;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+// Items taken out of the lists without deleting them, saved with the lists and restored
+// from the archive screen into whichever list is open
+impl AppState {
+    // Done items of the active list into the archive
+    pub fn archive_completed(&mut self) -> usize {
+        let count = self.done_count();
+        if count > 0 {
+            self.checkpoint("Archive completed");
+            let done: Vec<usize> = (0..self.items().len()).filter(|&i| self.items()[i].done).collect();
+            self.archive(&done);
+        }
+        count
+    }
+
+    pub fn archive_selected(&mut self) {
+        let Some(index) = self.selected_item_index().filter(|&i| self.get(i).is_some()) else {
+            return;
+        };
+        self.checkpoint("Archive item");
+        self.archive(&[index]);
+    }
+
+    // Back at the end of the active list, at the top level when its parent isn't there
+    pub fn restore_archived(&mut self, index: usize) -> Option<String> {
+        if index >= self.archived.len() {
+            return None;
+        }
+        self.checkpoint("Restore item");
+        let mut item = self.archived.remove(index);
+        if item.parent_id.is_some_and(|parent| !self.items().iter().any(|other| other.id == parent)) {
+            item.parent_id = None;
+        }
+        let description = item.description.clone();
+        self.extend_items([item]);
+        Some(description)
+    }
+
+    // Storage indices in ascending order. Subtasks left behind move up to their nearest
+    // ancestor still in the list
+    fn archive(&mut self, indices: &[usize]) {
+        let list = &mut self.lists[self.active_list];
+        let mut items: Vec<TodoItem> = indices.iter().rev().map(|&i| list.remove(i)).collect();
+        items.reverse();
+        let parents: HashMap<Uuid, Option<Uuid>> = items.iter().map(|item| (item.id, item.parent_id)).collect();
+        for i in 0..list.len() {
+            let Some(item) = list.get_mut(i) else {
+                continue;
+            };
+            while let Some(parent) = item.parent_id.filter(|parent| parents.contains_key(parent)) {
+                item.parent_id = parents[&parent];
+            }
+        }
+        self.archived.extend(items);
+        self.normalize_selection();
+    }
+}
@@ -0,0 +1,65 @@
+use crate::app::AppState;
+use crate::item::Priority;
+use crate::webhook::WebhookEvent;
+
+// Items marked with v on the main list, which d, Space and p then act on all at once. Kept
+// by id so adding, moving or sorting items doesn't change which ones are marked
+impl AppState {
+    pub fn toggle_mark(&mut self) {
+        let Some(id) = self.selected_item_index().and_then(|i| self.get(i)).map(|item| item.id) else {
+            return;
+        };
+        if !self.marked.remove(&id) {
+            self.marked.insert(id);
+        }
+    }
+
+    pub fn is_marked(&self, index: usize) -> bool {
+        self.get(index).is_some_and(|item| self.marked.contains(&item.id))
+    }
+
+    // Storage indices of the marked items in the active list, in list order
+    pub fn marked_indices(&self) -> Vec<usize> {
+        let items = self.items();
+        (0..items.len()).filter(|&i| self.marked.contains(&items[i].id)).collect()
+    }
+
+    pub fn delete_marked(&mut self) -> usize {
+        let indices = self.marked_indices();
+        if !indices.is_empty() {
+            self.checkpoint("Delete marked");
+        }
+        for &i in indices.iter().rev() {
+            self.remove_at(i);
+        }
+        self.marked.clear();
+        indices.len()
+    }
+
+    // All of them done, or undone when they already are
+    pub fn toggle_marked_done(&mut self) {
+        let indices = self.marked_indices();
+        let done = !indices.iter().all(|&i| self.items()[i].done);
+        self.checkpoint(if done { "Mark done" } else { "Mark undone" });
+        for i in indices {
+            if let Some(item) = self.get_mut(i).filter(|item| item.done != done) {
+                item.set_done(done);
+                if done {
+                    self.notify(WebhookEvent::ItemCompleted, &self.items()[i]);
+                }
+            }
+        }
+        self.update_streak();
+        self.marked.clear();
+    }
+
+    pub fn set_marked_priority(&mut self, priority: Priority) {
+        self.checkpoint("Set priority");
+        for i in self.marked_indices() {
+            if let Some(item) = self.get_mut(i) {
+                item.priority = priority;
+            }
+        }
+        self.marked.clear();
+    }
+}
@@ -0,0 +1,130 @@
+use crate::import::ImportFormat;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "Usage: todo-list [OPTIONS]
+
+Options:
+      --stdin                  Add one item per line read from stdin
+      --stdin-format <FORMAT>  Format of stdin: plain, json, todotxt, csv, google-tasks or org [default: plain]
+      --import <PATH>          Add one item per line of this file, [x] lines as done and
+                               lines starting with # skipped
+      --no-tui                 Exit after importing instead of starting the TUI
+      --add <DESCRIPTION>      Add an item to the first list and exit
+      --mark-done <ID>         Mark the item with this id done and exit, the first 4 or more
+                               characters of the id are enough
+      --unmark <ID>            Mark the item with this id not done and exit
+      --toggle <ID>            Flip whether the item with this id is done and exit
+      --delete <ID>            Delete the item with this id and exit
+      --dry-run                Print the changes --add, --mark-done, --delete etc. would make
+                               as a diff instead of saving them
+      --serve                  Serve the items over HTTP instead of starting the TUI
+      --port <PORT>            Port for --serve [default: 7878]
+      --print                  Print the items of the first list and exit
+      --plain                  Print them as [x] done and [ ] pending lines, the default
+      --ids                    Start each printed line with the item's id
+      --doctor                 Check the saved lists for broken subtasks, repeated ids and
+                               impossible dates and exit
+      --config <PATH>          Read the settings from this file instead of
+                               ~/.config/todo-tui/config.toml
+      --file <PATH>            Keep the lists in this file instead of file_path from the
+                               config, or ~/.local/share/todo-tui/todos.json without either
+      --export-dir <DIR>       Write the files Ctrl+E exports here instead of the current
+                               directory
+  -h, --help                   Print this help";
+
+#[derive(Default, Debug)]
+pub struct Args {
+    pub stdin: bool,
+    pub stdin_format: ImportFormat,
+    pub import: Option<PathBuf>,
+    pub no_tui: bool,
+    pub mutations: Vec<Mutation>,
+    pub dry_run: bool,
+    pub serve: bool,
+    pub port: Option<u16>,
+    pub doctor: bool,
+    pub print: bool,
+    pub plain: bool,
+    pub ids: bool,
+    pub config: Option<PathBuf>,
+    pub file: Option<PathBuf>,
+    pub export_dir: Option<PathBuf>,
+    pub help: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mutation {
+    Add(String),
+    MarkDone(String),
+    Unmark(String),
+    Toggle(String),
+    Delete(String),
+}
+
+impl Args {
+    pub fn parse() -> Result<Args, String> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    pub fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--stdin" => parsed.stdin = true,
+                "--stdin-format" => {
+                    let value = args.next().ok_or("--stdin-format needs a value")?;
+                    parsed.stdin_format = value.parse()?;
+                }
+                "--import" => parsed.import = Some(args.next().ok_or("--import needs a path")?.into()),
+                "--no-tui" => parsed.no_tui = true,
+                "--add" => {
+                    let value = args.next().ok_or("--add needs a description")?;
+                    parsed.mutations.push(Mutation::Add(value));
+                }
+                "--mark-done" => {
+                    let value = args.next().ok_or("--mark-done needs an id")?;
+                    parsed.mutations.push(Mutation::MarkDone(value));
+                }
+                "--unmark" => {
+                    let value = args.next().ok_or("--unmark needs an id")?;
+                    parsed.mutations.push(Mutation::Unmark(value));
+                }
+                "--toggle" => {
+                    let value = args.next().ok_or("--toggle needs an id")?;
+                    parsed.mutations.push(Mutation::Toggle(value));
+                }
+                "--delete" => {
+                    let value = args.next().ok_or("--delete needs an id")?;
+                    parsed.mutations.push(Mutation::Delete(value));
+                }
+                "--dry-run" => parsed.dry_run = true,
+                "--serve" => parsed.serve = true,
+                "--port" => {
+                    let value = args.next().ok_or("--port needs a value")?;
+                    let port = value.parse().map_err(|_| format!("invalid port '{}'", value))?;
+                    parsed.port = Some(port);
+                }
+                "--doctor" => parsed.doctor = true,
+                "--print" => parsed.print = true,
+                "--plain" => parsed.plain = true,
+                "--ids" => parsed.ids = true,
+                "--config" => parsed.config = Some(args.next().ok_or("--config needs a path")?.into()),
+                "--file" => parsed.file = Some(args.next().ok_or("--file needs a path")?.into()),
+                "--export-dir" => parsed.export_dir = Some(args.next().ok_or("--export-dir needs a path")?.into()),
+                "-h" | "--help" => parsed.help = true,
+                _ => return Err(format!("unexpected argument '{}'", arg)),
+            }
+        }
+        if parsed.serve && (parsed.stdin || parsed.import.is_some() || !parsed.mutations.is_empty()) {
+            return Err("--serve can't be combined with --stdin, --import or item changes".to_owned());
+        }
+        if (parsed.plain || parsed.ids) && !parsed.print {
+            return Err("--plain and --ids need --print".to_owned());
+        }
+        if parsed.port.is_some() && !parsed.serve {
+            return Err("--port needs --serve".to_owned());
+        }
+        Ok(parsed)
+    }
+}
@@ -0,0 +1,844 @@
+use crate::app::{FilterState, SortOrder};
+use crate::date_utils::format_relative_date;
+use crate::error::{AppError, ConfigError};
+use crate::item::Priority;
+use crate::storage;
+use crate::theme::Theme;
+use crate::title;
+use crate::webhook::WebhookEvent;
+use chrono::{NaiveDate, NaiveDateTime, Weekday};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{BorderType, Padding};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Write};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TodoTemplate {
+    pub name: String,
+    pub description: String,
+    pub priority: Priority,
+    pub tags: Vec<String>,
+    pub due_offset_days: Option<i64>,
+}
+
+// Where the Add screen puts new items
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NewItemPosition {
+    Top,
+    #[default]
+    Bottom,
+    AfterSelected,
+}
+
+// What the main list shows when the app starts
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupScreen {
+    #[default]
+    AllItems,
+    TodayView,
+    OverdueFirst,
+    LastSelected,
+}
+
+// Where done items are shown relative to the rest of the list
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DoneItemsPosition {
+    #[default]
+    #[serde(alias = "inline")]
+    Mixed,
+    Top,
+    Bottom,
+    Hidden,
+}
+
+// Drawn between the groups of the main list, e.g. done items moved to the bottom
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeparatorStyle {
+    #[default]
+    None,
+    Line,
+    BlankLine,
+    DashedLine,
+}
+
+// How done items are drawn in the main list
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DoneStyle {
+    #[default]
+    StrikethroughGray,
+    Dim,
+    Hidden,
+    Normal,
+}
+
+// Settings a single list can override, stored with the list in the save file
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_order: Option<SortOrder>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_progress_bar: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub done_items_position: Option<DoneItemsPosition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_incomplete: Option<bool>,
+}
+
+impl ListConfig {
+    pub fn apply<'a>(&self, config: &'a Config) -> ListSettings<'a> {
+        ListSettings {
+            sort_order: self.sort_order.unwrap_or(config.sort_order),
+            show_progress_bar: self.show_progress_bar.unwrap_or(config.show_progress_bar),
+            done_items_position: self.done_items_position.unwrap_or(config.done_items_position),
+            filter_incomplete: self.filter_incomplete.unwrap_or(config.filter_incomplete),
+            config,
+        }
+    }
+}
+
+// The settings ListConfig covers as a list ends up with them, the rest are read from the
+// config itself
+#[derive(Clone, Copy)]
+pub struct ListSettings<'a> {
+    pub sort_order: SortOrder,
+    pub show_progress_bar: bool,
+    pub done_items_position: DoneItemsPosition,
+    pub filter_incomplete: bool,
+    config: &'a Config,
+}
+
+impl ListSettings<'_> {
+    pub fn hide_done(&self) -> bool {
+        self.filter_incomplete
+            || self.done_items_position == DoneItemsPosition::Hidden
+            || self.config.done_style == DoneStyle::Hidden
+    }
+}
+
+// Where the last session left off, written back on exit
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub list: usize,
+    // Index into the list's items, so it survives filters and sorting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected: Option<usize>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub search: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub due_today: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub overdue_only: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub incomplete_only: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub search_history: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub templates: Vec<TodoTemplate>,
+    pub open_urls: bool,
+    pub encrypt: bool,
+    pub encryption_key_env: String,
+    pub longest_streak: u32,
+    pub new_item_position: NewItemPosition,
+    pub wrap_navigation: bool,
+    pub tab_opens_next: bool,
+    pub key_repeat_debounce_ms: u64,
+    pub scroll_acceleration: bool,
+    pub toggle_done_key: KeySpec,
+    pub add_key: KeySpec,
+    pub delete_key: KeySpec,
+    pub search_key: KeySpec,
+    pub quit_key: KeySpec,
+    pub show_item_numbers: bool,
+    pub relative_dates: bool,
+    pub date_format: String,
+    pub datetime_format: String,
+    pub allow_past_due_dates: bool,
+    pub set_window_title: bool,
+    pub highlight_symbol: String,
+    pub incomplete_symbol: String,
+    pub complete_symbol: String,
+    // Top, right, bottom, left, as in CSS
+    pub list_padding: [u16; 4],
+    pub max_list_width: Option<u16>,
+    pub border_type: String,
+    pub list_title_format: String,
+    pub empty_list_message: String,
+    pub aging_enabled: bool,
+    pub aging_warn_days: u32,
+    pub aging_critical_days: u32,
+    pub stale_item_days: u32,
+    pub max_note_len: Option<usize>,
+    pub search_highlight_style: String,
+    pub duplicate_threshold: f64,
+    pub auto_capitalize: bool,
+    pub abbreviations: HashMap<String, String>,
+    pub sort_order: SortOrder,
+    pub show_progress_bar: bool,
+    pub spinner_fps: u8,
+    pub done_items_position: DoneItemsPosition,
+    pub group_separator_style: SeparatorStyle,
+    pub filter_incomplete: bool,
+    pub done_style: DoneStyle,
+    pub webhook_url: Option<String>,
+    pub webhook_events: Vec<WebhookEvent>,
+    pub webhook_secret: Option<String>,
+    pub first_day_of_week: Weekday,
+    pub language: String,
+    pub restore_selection: bool,
+    pub persist_session: bool,
+    pub file_path: Option<String>,
+    pub backup_count: usize,
+    pub undo_max_depth: usize,
+    pub startup_screen: StartupScreen,
+    pub startup_message: Option<String>,
+    pub show_sidebar: bool,
+    pub theme: Theme,
+    pub saved_filters: BTreeMap<String, FilterState>,
+    pub sidebar_width: u16,
+    pub ascii_mode: bool,
+    pub screen_reader_mode: bool,
+    pub high_contrast: bool,
+    pub swipe_gestures: bool,
+    pub mouse_drag_reorder: bool,
+    pub enable_daily_review: bool,
+    pub last_daily_review: Option<NaiveDate>,
+    pub last_weekly_review: Option<NaiveDate>,
+    pub check_for_updates: bool,
+    pub last_update_check: Option<NaiveDate>,
+    pub session: Session,
+    #[serde(skip)]
+    pub search_style: Style,
+    // Parsed from border_type when the config is loaded
+    #[serde(skip)]
+    pub borders: BorderType,
+    // Where the config was loaded from, saving is a no-op without it
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+const INCOMPLETE_SYMBOL: &str = "☐";
+const COMPLETE_SYMBOL: &str = "✓";
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            templates: vec![],
+            open_urls: true,
+            encrypt: false,
+            encryption_key_env: "TODO_TUI_KEY".to_owned(),
+            longest_streak: 0,
+            new_item_position: NewItemPosition::Bottom,
+            wrap_navigation: false,
+            tab_opens_next: false,
+            key_repeat_debounce_ms: 0,
+            scroll_acceleration: false,
+            toggle_done_key: KeySpec::new(KeyCode::Char(' '), KeyModifiers::NONE),
+            add_key: KeySpec::new(KeyCode::Char('a'), KeyModifiers::NONE),
+            delete_key: KeySpec::new(KeyCode::Char('d'), KeyModifiers::NONE),
+            search_key: KeySpec::new(KeyCode::Char('/'), KeyModifiers::NONE),
+            quit_key: KeySpec::new(KeyCode::Char('q'), KeyModifiers::NONE),
+            show_item_numbers: false,
+            relative_dates: true,
+            date_format: "%Y-%m-%d".to_owned(),
+            datetime_format: "%Y-%m-%d %H:%M".to_owned(),
+            allow_past_due_dates: false,
+            set_window_title: true,
+            highlight_symbol: "  ".to_owned(),
+            incomplete_symbol: INCOMPLETE_SYMBOL.to_owned(),
+            complete_symbol: COMPLETE_SYMBOL.to_owned(),
+            list_padding: [0; 4],
+            max_list_width: None,
+            border_type: "rounded".to_owned(),
+            list_title_format: "{list_name}".to_owned(),
+            empty_list_message: "No todos yet!\n\nPress 'a' to add your first item".to_owned(),
+            aging_enabled: true,
+            aging_warn_days: 7,
+            aging_critical_days: 14,
+            stale_item_days: 30,
+            max_note_len: None,
+            search_highlight_style: "yellow bold".to_owned(),
+            duplicate_threshold: 0.92,
+            auto_capitalize: true,
+            abbreviations: HashMap::new(),
+            sort_order: SortOrder::Manual,
+            show_progress_bar: false,
+            spinner_fps: 10,
+            done_items_position: DoneItemsPosition::Mixed,
+            group_separator_style: SeparatorStyle::None,
+            filter_incomplete: false,
+            done_style: DoneStyle::StrikethroughGray,
+            webhook_url: None,
+            webhook_events: WebhookEvent::ALL.to_vec(),
+            webhook_secret: None,
+            first_day_of_week: Weekday::Mon,
+            language: "en".to_owned(),
+            restore_selection: true,
+            persist_session: true,
+            file_path: None,
+            backup_count: 3,
+            undo_max_depth: 50,
+            startup_screen: StartupScreen::AllItems,
+            startup_message: None,
+            show_sidebar: false,
+            theme: Theme::default(),
+            saved_filters: BTreeMap::new(),
+            sidebar_width: 20,
+            ascii_mode: false,
+            screen_reader_mode: false,
+            high_contrast: false,
+            swipe_gestures: false,
+            mouse_drag_reorder: false,
+            enable_daily_review: false,
+            last_daily_review: None,
+            last_weekly_review: None,
+            check_for_updates: true,
+            last_update_check: None,
+            session: Session::default(),
+            search_style: Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            borders: BorderType::Rounded,
+            path: None,
+        }
+    }
+}
+
+impl Config {
+    // Missing file means defaults. A setting with a bad value keeps its default and an unknown
+    // one is ignored, both with a warning, and a file that isn't TOML at all is ignored whole
+    // From --config or config_path()
+    pub fn load(path: Option<&Path>) -> Config {
+        let Some(path) = path else {
+            return Config::default();
+        };
+        if !path.exists() {
+            let _ = storage::save_atomic(path, generate_config_template().as_bytes());
+        }
+        let parsed = match fs::read_to_string(path) {
+            Ok(text) => Config::parse(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok((Config::default(), vec![])),
+            Err(e) => Err(e.into()),
+        };
+        match parsed {
+            Ok((config, problems)) if problems.is_empty() => Config {
+                path: Some(path.to_owned()),
+                ..config
+            },
+            Ok((config, problems)) => {
+                for problem in problems {
+                    eprintln!("Warning: {}: {}", path.display(), problem);
+                }
+                // Saving would drop what couldn't be read, the file stays as it is until fixed
+                config
+            }
+            Err(e) => {
+                // Not remembering the path keeps the broken file from being overwritten
+                eprintln!("Warning: ignoring invalid config {}: {}", path.display(), e);
+                Config::default()
+            }
+        }
+    }
+
+    // Strict version of load for a given file, an unreadable file or any bad setting is an error
+    pub fn read(path: &Path) -> Result<Config, AppError> {
+        let config = match fs::read_to_string(path) {
+            Ok(text) => match Config::parse(&text)? {
+                (config, problems) if problems.is_empty() => config,
+                (_, problems) => return Err(AppError::InvalidInput(problems.join(", "))),
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Config::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Config {
+            path: Some(path.to_owned()),
+            ..config
+        })
+    }
+
+    // The settings in `text` with what's wrong with them, one problem per setting. A setting
+    // whose value can't be used keeps its default so it doesn't cost the others theirs
+    fn parse(text: &str) -> Result<(Config, Vec<String>), AppError> {
+        let mut table: toml::Table = toml::from_str(text).map_err(|e| AppError::Parse(e.to_string()))?;
+        let check = |table: toml::Table| -> Result<Config, String> {
+            let config: Config = toml::Value::Table(table).try_into().map_err(|e: toml::de::Error| e.message().to_owned())?;
+            config.validate().map_err(|e| e.to_string())
+        };
+        let mut problems = vec![];
+        table.retain(|key, value| match check(toml::Table::from_iter([(key.to_owned(), value.clone())])) {
+            Ok(_) => true,
+            Err(e) => {
+                problems.push(format!("ignoring {}: {}", key, e));
+                false
+            }
+        });
+        // Remapped keys can still take one another's
+        let config = loop {
+            let config: Config = toml::Value::Table(table.clone()).try_into().map_err(|e: toml::de::Error| AppError::Parse(e.to_string()))?;
+            match config.validate() {
+                Ok(config) => break config,
+                Err(ConfigError::KeyConflict(setting, key)) if table.remove(&setting).is_some() => {
+                    problems.push(format!("ignoring {}: {}", setting, ConfigError::KeyConflict(setting.clone(), key)));
+                }
+                Err(e) => return Err(AppError::InvalidInput(e.to_string())),
+            }
+        };
+        // Whatever serde skipped over comes back missing
+        let known = toml::Table::try_from(&config).map_err(|e| AppError::Parse(e.to_string()))?;
+        for key in table.keys().filter(|key| !known.contains_key(*key)) {
+            let similar = HELP.iter().map(|(name, _)| *name).find(|name| strsim::jaro_winkler(key, name) > 0.9);
+            match similar {
+                Some(name) => problems.push(format!("unknown setting {}, did you mean {}?", key, name)),
+                None => problems.push(format!("unknown setting {}", key)),
+            }
+        }
+        Ok((config, problems))
+    }
+}
+
+impl Config {
+    // Checks serde can't do, filling in the parsed forms of string settings
+    pub fn validate(mut self) -> Result<Config, ConfigError> {
+        self.borders = parse_border_type(&self.border_type)?;
+        self.search_style = parse_style(&self.search_highlight_style)?;
+        if let Some(placeholder) = title::invalid_placeholder(&self.list_title_format) {
+            return Err(ConfigError::InvalidTitleFormat(placeholder));
+        }
+        check_symbol("incomplete_symbol", &self.incomplete_symbol)?;
+        check_symbol("complete_symbol", &self.complete_symbol)?;
+        check_date_format(&self.date_format)?;
+        check_datetime_format(&self.datetime_format)?;
+        // The defaults of the remappable keys are free for one another
+        let settings = self.key_settings();
+        for (i, &(setting, key, _)) in settings.iter().enumerate() {
+            let mut fixed = MAIN_KEYS.iter().filter(|&&spec| !settings.iter().any(|&(_, _, default)| default == spec));
+            if let Some(&taken) = fixed.find(|&&spec| parse_key_spec(spec).ok() == Some(key)) {
+                return Err(ConfigError::KeyConflict(setting.to_owned(), taken.to_owned()));
+            }
+            if settings[..i].iter().any(|&(_, other, _)| other == key) {
+                return Err(ConfigError::KeyConflict(setting.to_owned(), key.to_string()));
+            }
+        }
+        Ok(self)
+    }
+
+    // Main list keys the config can change, with the one each has by default
+    fn key_settings(&self) -> [(&'static str, KeySpec, &'static str); 5] {
+        [
+            ("toggle_done_key", self.toggle_done_key, "space"),
+            ("add_key", self.add_key, "a"),
+            ("delete_key", self.delete_key, "d"),
+            ("search_key", self.search_key, "/"),
+            ("quit_key", self.quit_key, "q"),
+        ]
+    }
+
+    pub fn save(&self) -> Result<(), AppError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        storage::save_atomic(path, self.render()?.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl Config {
+    // Screen reader mode implies plain ASCII symbols
+    pub fn ascii(&self) -> bool {
+        self.ascii_mode || self.screen_reader_mode
+    }
+
+    // (done, not done) marks, ASCII mode swaps the Unicode defaults for [x] and [ ]
+    pub fn done_symbols<'a>(&'a self) -> (&'a str, &'a str) {
+        let ascii = self.ascii();
+        let swap = |symbol: &'a str, default: &str, replacement: &'a str| {
+            if ascii && symbol == default { replacement } else { symbol }
+        };
+        (
+            swap(&self.complete_symbol, COMPLETE_SYMBOL, "[x]"),
+            swap(&self.incomplete_symbol, INCOMPLETE_SYMBOL, "[ ]"),
+        )
+    }
+
+    // Space between the border of the main list and its items
+    pub fn list_padding(&self) -> Padding {
+        let [top, right, bottom, left] = self.list_padding;
+        Padding::new(left, right, top, bottom)
+    }
+
+    // Time per spinner frame, spinner_fps kept within 1 to 30
+    pub fn spinner_interval(&self) -> Duration {
+        Duration::from_millis(1000 / u64::from(self.spinner_fps.clamp(1, 30)))
+    }
+
+    pub fn format_date(&self, date: NaiveDate, today: NaiveDate) -> String {
+        if self.relative_dates {
+            format_relative_date(date, today)
+        } else {
+            date.format(&self.date_format).to_string()
+        }
+    }
+}
+
+// Every item has one of the marks in front of it, at most as wide as "[ ]"
+fn check_symbol(setting: &str, symbol: &str) -> Result<(), ConfigError> {
+    let printable = symbol.chars().all(|c| c.width().is_some_and(|width| width > 0));
+    if symbol.is_empty() || !printable || symbol.width() > 3 {
+        return Err(ConfigError::InvalidSymbol(setting.to_owned(), symbol.to_owned()));
+    }
+    Ok(())
+}
+
+// Formats are also used to read dates back in, so a formatted sample must parse again
+fn check_date_format(format: &str) -> Result<(), ConfigError> {
+    let sample = NaiveDate::from_ymd_opt(2024, 12, 31).expect("valid date");
+    let invalid = || ConfigError::InvalidDateFormat(format.to_owned());
+    let mut text = String::new();
+    write!(text, "{}", sample.format(format)).map_err(|_| invalid())?;
+    match NaiveDate::parse_from_str(&text, format) {
+        Ok(date) if date == sample => Ok(()),
+        _ => Err(invalid()),
+    }
+}
+
+fn check_datetime_format(format: &str) -> Result<(), ConfigError> {
+    let sample = NaiveDate::from_ymd_opt(2024, 12, 31)
+        .and_then(|date| date.and_hms_opt(23, 59, 0))
+        .expect("valid date");
+    let invalid = || ConfigError::InvalidDateFormat(format.to_owned());
+    let mut text = String::new();
+    write!(text, "{}", sample.format(format)).map_err(|_| invalid())?;
+    match NaiveDateTime::parse_from_str(&text, format) {
+        Ok(datetime) if datetime == sample => Ok(()),
+        _ => Err(invalid()),
+    }
+}
+
+pub fn parse_border_type(value: &str) -> Result<BorderType, ConfigError> {
+    match value {
+        "rounded" => Ok(BorderType::Rounded),
+        "plain" => Ok(BorderType::Plain),
+        "double" => Ok(BorderType::Double),
+        "thick" => Ok(BorderType::Thick),
+        _ => Err(ConfigError::InvalidBorderType(value.to_owned())),
+    }
+}
+
+// Space separated colors and modifiers, e.g. "yellow bold" or "black on_cyan"
+pub fn parse_style(value: &str) -> Result<Style, ConfigError> {
+    let mut style = Style::new();
+    for word in value.split_whitespace() {
+        style = match word {
+            "bold" => style.add_modifier(Modifier::BOLD),
+            "dim" => style.add_modifier(Modifier::DIM),
+            "italic" => style.add_modifier(Modifier::ITALIC),
+            "underlined" => style.add_modifier(Modifier::UNDERLINED),
+            "reversed" => style.add_modifier(Modifier::REVERSED),
+            _ => match word.strip_prefix("on_") {
+                Some(color) => style.bg(Color::from_str(color).map_err(|_| invalid_style(value))?),
+                None => style.fg(Color::from_str(word).map_err(|_| invalid_style(value))?),
+            },
+        };
+    }
+    Ok(style)
+}
+
+fn invalid_style(value: &str) -> ConfigError {
+    ConfigError::InvalidStyle(value.to_owned())
+}
+
+// A key with its modifiers, written as e.g. "space", "x", "ctrl+d" or "f2" in the config
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeySpec {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeySpec {
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> KeySpec {
+        KeySpec { code, modifiers }
+    }
+
+    // Shift is already in the character, so it's only compared for other keys
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        let ignored = match key.code {
+            KeyCode::Char(_) => KeyModifiers::SHIFT,
+            _ => KeyModifiers::NONE,
+        };
+        key.code == self.code && key.modifiers.difference(ignored) == self.modifiers
+    }
+}
+
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("space", KeyCode::Char(' ')),
+    ("enter", KeyCode::Enter),
+    ("tab", KeyCode::Tab),
+    ("esc", KeyCode::Esc),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+];
+
+// Keys the main list already uses, in the spelling of parse_key_spec
+const MAIN_KEYS: &[&str] = &[
+    "a", "i", "s", "t", "T", "v", "q", "#", "d", "n", "o", "p", "+", "-", "A", "U", "x", "c", "X", "S", "F", "/", ":", "L", "j", "k", "g", "G", "w", "?", "Q", "@",
+    "0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "enter", "esc", "tab", "up", "down", "alt+up", "alt+down", "ctrl+a", "ctrl+e", "ctrl+/",
+    "ctrl+7", "ctrl+c", "ctrl+C", "ctrl+d", "ctrl+D", "ctrl+o", "ctrl+O", "ctrl+r", "ctrl+s", "ctrl+m", "ctrl+t", "ctrl+T", "ctrl+p", "ctrl+y", "ctrl+z", "ctrl+Z",
+    "ctrl+home", "ctrl+end",
+];
+
+// Modifiers joined to the key with +, e.g. "ctrl+shift+tab"; names are case-insensitive but
+// single characters are taken as written
+pub fn parse_key_spec(value: &str) -> Result<KeySpec, ConfigError> {
+    let invalid = || ConfigError::InvalidKey(value.to_owned());
+    let mut parts: Vec<&str> = value.split('+').collect();
+    // "ctrl++" is Ctrl and the + key
+    if value == "+" || value.ends_with("++") {
+        parts.truncate(parts.len() - 2);
+        parts.push("+");
+    }
+    let (key, modifiers) = parts.split_last().ok_or_else(invalid)?;
+    let mut spec = KeySpec::new(KeyCode::Null, KeyModifiers::NONE);
+    for modifier in modifiers {
+        spec.modifiers |= match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return Err(invalid()),
+        };
+    }
+    let lower = key.to_ascii_lowercase();
+    spec.code = if let Some((_, code)) = KEY_NAMES.iter().find(|(name, _)| *name == lower) {
+        *code
+    } else if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse().ok()).filter(|n| (1..=12).contains(n)) {
+        KeyCode::F(n)
+    } else {
+        let mut chars = key.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => return Err(invalid()),
+        }
+    };
+    // Letters come with Shift already applied, "shift+a" is "A"
+    if let KeyCode::Char(c) = spec.code {
+        if spec.modifiers.contains(KeyModifiers::SHIFT) {
+            spec.code = KeyCode::Char(c.to_ascii_uppercase());
+            spec.modifiers.remove(KeyModifiers::SHIFT);
+        }
+    }
+    Ok(spec)
+}
+
+impl fmt::Display for KeySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl"),
+            (KeyModifiers::ALT, "alt"),
+            (KeyModifiers::SHIFT, "shift"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        match (self.code, KEY_NAMES.iter().find(|(_, code)| *code == self.code)) {
+            (_, Some((name, _))) => f.write_str(name),
+            (KeyCode::F(n), _) => write!(f, "f{}", n),
+            (KeyCode::Char(c), _) => write!(f, "{}", c),
+            (code, _) => write!(f, "{:?}", code),
+        }
+    }
+}
+
+impl TryFrom<String> for KeySpec {
+    type Error = ConfigError;
+
+    fn try_from(value: String) -> Result<KeySpec, ConfigError> {
+        parse_key_spec(&value)
+    }
+}
+
+impl From<KeySpec> for String {
+    fn from(spec: KeySpec) -> String {
+        spec.to_string()
+    }
+}
+
+// Written above each setting in config.toml, keys without an entry get no comment
+const HELP: &[(&str, &str)] = &[
+    ("templates", "Items to pre-fill the Add screen with, picked with Ctrl+T (list of tables with\ndescription, priority, tags and due_offset_days)"),
+    ("open_urls", "o opens the first URL of the selected item (true/false)"),
+    ("encrypt", "Keep the lists in todos.enc, encrypted with AES-256-GCM (true/false)"),
+    ("encryption_key_env", "Environment variable holding the password, asked for when unset (string)"),
+    ("longest_streak", "Your best streak of days with an item done, updated by the app (integer)"),
+    ("new_item_position", "Where new items go: bottom, top or after_selected (string)"),
+    ("wrap_navigation", "Up on the first item jumps to the last one and back (true/false)"),
+    ("tab_opens_next", "Tab selects the next incomplete item instead of switching to the sidebar (true/false)"),
+    ("key_repeat_debounce_ms", "While Up/Down is held, drop key repeats arriving faster than this, 0 keeps them\nall (milliseconds)"),
+    ("scroll_acceleration", "Holding Up/Down skips every other item after the first five (true/false)"),
+    ("toggle_done_key", "Marks the selected item done/undone, e.g. \"space\", \"m\" or \"ctrl+x\"; keys the list\nalready uses are refused (key)"),
+    ("add_key", "Opens the Add screen (key)"),
+    ("delete_key", "Deletes the selected item after confirming (key)"),
+    ("search_key", "Starts a search of the list (key)"),
+    ("quit_key", "Saves and quits (key)"),
+    ("show_item_numbers", "Prefix items with their position in the list (true/false)"),
+    ("relative_dates", "\"tomorrow\", \"in 3 days\"; false shows dates in date_format (true/false)"),
+    ("date_format", "Due dates and typed dates, e.g. \"%d.%m.%Y\" (strftime string)"),
+    ("datetime_format", "Reminders and timestamps (strftime string)"),
+    ("allow_past_due_dates", "- can move a due date before today, otherwise it clears the date (true/false)"),
+    ("set_window_title", "Show the number of pending items in the terminal title (true/false)"),
+    ("highlight_symbol", "Drawn before the selected item, e.g. \"▶ \" (string)"),
+    ("incomplete_symbol", "Mark of items not done yet, up to 3 columns wide; [ ] in ascii_mode (string)"),
+    ("complete_symbol", "Mark of done items, up to 3 columns wide; [x] in ascii_mode (string)"),
+    ("list_padding", "Space inside the list border: top, right, bottom, left (four integers)"),
+    ("max_list_width", "Center the list at most this wide, full width when unset (integer)"),
+    ("border_type", "rounded, plain, double or thick (string)"),
+    ("list_title_format", "List title with {list_name}, {total}, {pending}, {done} and {overdue} (string)"),
+    ("empty_list_message", "Shown in the middle of a list without items, \\n starts a new line (string)"),
+    ("aging_enabled", "Dim incomplete items as they age (true/false)"),
+    ("aging_warn_days", "Days until an incomplete item dims (integer)"),
+    ("aging_critical_days", "Days until it turns gray, struck through after twice that (integer)"),
+    ("max_note_len", "Show how much of this many characters the notes use in the note editor (integer)"),
+    ("stale_item_days", "Incomplete items older than this are up for review after the daily prompt (integer)"),
+    ("search_highlight_style", "Colors, on_<color> backgrounds and modifiers, e.g. \"yellow bold\" (string)"),
+    ("duplicate_threshold", "Adding an item this similar to an existing one asks first (0 to 1)"),
+    ("auto_capitalize", "Uppercase the first letter typed on the Add screen (true/false)"),
+    ("abbreviations", "Expanded when followed by a space on the Add and Edit screens, e.g. mtg = \"Meeting\""),
+    ("sort_order", "manual, by_due_date, by_priority, by_done, alphabetical or smart (string)"),
+    ("show_progress_bar", "Done/total gauge under the list (true/false)"),
+    ("spinner_fps", "Frames per second of the status bar spinner, 1 to 30 (integer)"),
+    ("done_items_position", "mixed, top, bottom or hidden (string)"),
+    ("group_separator_style", "none, line, blank_line or dashed_line between the groups of the list (string)"),
+    ("filter_incomplete", "Hide done items (true/false)"),
+    ("done_style", "strikethrough_gray, dim, normal or hidden (string)"),
+    ("webhook_url", "POST each added, completed, deleted or edited item here (URL)"),
+    ("webhook_events", "Which of item_added, item_completed, item_deleted and item_edited to send (list)"),
+    ("webhook_secret", "Sign webhook bodies, sent as X-Signature: sha256=<hex HMAC> (string)"),
+    ("first_day_of_week", "Start of the weeks in the digest and statistics, e.g. \"Sun\" (string)"),
+    ("language", "en, de, or a ~/.config/todo-tui/locales/<language>.toml of your own (string)"),
+    ("restore_selection", "Reopen the list and item selected on exit (true/false)"),
+    ("persist_session", "Bring back the search and filters of the last run from [session] (true/false)"),
+    ("file_path", "Keep the lists in this file instead of the data directory, --file overrides it (path)"),
+    ("backup_count", "Copies of the previous saves kept as todos.json.bak1 and up, 0 keeps none (integer)"),
+    ("undo_max_depth", "Changes Ctrl+Z can undo, older ones are forgotten (integer)"),
+    ("startup_screen", "all_items, today_view, overdue_first or last_selected (string)"),
+    ("startup_message", "Shown for 3 seconds at startup, {pending} is the number of pending items (string)"),
+    ("show_sidebar", "Lists and their item counts on the left, Tab moves between the panes (true/false)"),
+    ("theme", "name is slate, nord, gruvbox, solarized or light; any color of it can be changed,\n\"#rrggbb\" or e.g. \"blue\": background, foreground, title, item_pending_fg, item_done_fg,\nitem_aged_fg, item_selected_bg, marked_bg, input_border, list_border, inactive_border,\nsecondary_fg, muted_fg, faint_fg, separator_fg and status_bar_bg (table)"),
+    ("saved_filters", "Filters saved from the command palette, by name"),
+    ("sidebar_width", "Width of the sidebar in columns (integer)"),
+    ("ascii_mode", "[ ]/[x] and +-| borders instead of Unicode symbols (true/false)"),
+    ("screen_reader_mode", "Plain text with no colors or alignment for screen readers, implies ascii_mode\n(true/false)"),
+    ("high_contrast", "White on black with double borders, overrides the colors (true/false)"),
+    ("swipe_gestures", "Drag across the list: right asks to delete, left archives, up/down move the item (true/false)"),
+    ("mouse_drag_reorder", "Drag an item with the left button to move it there (true/false)"),
+    ("enable_daily_review", "Ask for up to three tasks for today on the first start of each day (true/false)"),
+    ("last_daily_review", "Written by the app (date)"),
+    ("last_weekly_review", "Written by the app (date)"),
+    ("check_for_updates", "Look for a newer release on crates.io once a day (true/false)"),
+    ("last_update_check", "Written by the app (date)"),
+    ("session", "The list, selection, search, filters and search history of the last run, written by the app"),
+];
+
+// Settings without a default value, shown with an example in the template
+const EXAMPLES: &[(&str, &str)] = &[
+    ("max_list_width", "100"),
+    ("max_note_len", "500"),
+    ("file_path", "\"~/Dropbox/todos.json\""),
+    ("startup_message", "\"Good morning! You have {pending} pending tasks.\""),
+    ("webhook_url", "\"https://example.com/todo-hook\""),
+    ("webhook_secret", "\"...\""),
+];
+
+impl Config {
+    // The config file: each setting with its help above it, and commented out while it has
+    // its default value so the defaults can change under it
+    pub fn render(&self) -> Result<String, AppError> {
+        let to_text = |e: toml::ser::Error| AppError::Parse(e.to_string());
+        let text = toml::to_string_pretty(self).map_err(to_text)?;
+        let values: toml::Table = toml::from_str(&text).map_err(|e| AppError::Parse(e.to_string()))?;
+        let defaults = toml::Table::try_from(Config::default()).map_err(to_text)?;
+        let (settings, tables) = match text.find("\n[") {
+            Some(end) => text.split_at(end + 1),
+            None => (text.as_str(), ""),
+        };
+        let mut text = settings.trim_end().to_owned();
+        for (key, example) in EXAMPLES.iter().filter(|(key, _)| !values.contains_key(*key)) {
+            let _ = write!(text, "\n{} = {}", key, example);
+        }
+        text.push_str("\n\n");
+        text.push_str(tables);
+
+        let mut out = String::from(
+            "# todo-tui settings, the ones still at their default are commented out. Remove the #\n\
+             # in front of a setting to change it\n\n",
+        );
+        let mut in_table = false;
+        let mut commented = false;
+        for line in text.lines() {
+            let key = if let Some(header) = line.strip_prefix('[') {
+                in_table = true;
+                Some(header.trim_matches(['[', ']']))
+            } else if in_table || line.starts_with(char::is_whitespace) || line == "]" {
+                None
+            } else {
+                line.split_once(" = ").map(|(key, _)| key)
+            };
+            if let Some(key) = key {
+                if let Some((_, help)) = HELP.iter().find(|(name, _)| *name == key) {
+                    for help in help.lines() {
+                        let _ = writeln!(out, "# {}", help);
+                    }
+                }
+                let key = key.split('.').next().unwrap_or(key);
+                commented = values.get(key) == defaults.get(key);
+            }
+            if commented && !line.is_empty() {
+                let _ = writeln!(out, "# {}", line);
+            } else {
+                let _ = writeln!(out, "{}", line);
+            }
+        }
+        Ok(out)
+    }
+}
+
+// Written when there is no config.toml yet, for first-time users to start from
+pub fn generate_config_template() -> String {
+    Config::default().render().unwrap_or_default()
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("todo-tui").join("config.toml"))
+}
@@ -0,0 +1,37 @@
+use crate::item::Priority;
+
+// Entries of the menu a right click on an item opens
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuAction {
+    Edit,
+    Delete,
+    ToggleDone,
+    Duplicate,
+    SetPriority,
+    AddTag,
+}
+
+impl MenuAction {
+    pub const ALL: &'static [MenuAction] = &[
+        MenuAction::Edit,
+        MenuAction::Delete,
+        MenuAction::ToggleDone,
+        MenuAction::Duplicate,
+        MenuAction::SetPriority,
+        MenuAction::AddTag,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MenuAction::Edit => "Edit",
+            MenuAction::Delete => "Delete",
+            MenuAction::ToggleDone => "Toggle done",
+            MenuAction::Duplicate => "Duplicate",
+            MenuAction::SetPriority => "Set priority…",
+            MenuAction::AddTag => "Add tag…",
+        }
+    }
+}
+
+// What "Set priority…" offers, highest first
+pub const PRIORITIES: [Priority; 4] = [Priority::High, Priority::Medium, Priority::Low, Priority::None];
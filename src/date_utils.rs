@@ -0,0 +1,150 @@
+use crate::config::Config;
+use crate::error::AppError;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+
+// "today", "tomorrow", "in 3 days", "2 weeks ago", ...
+pub fn format_relative_date(date: NaiveDate, today: NaiveDate) -> String {
+    let days = (date - today).num_days();
+    match days {
+        0 => "today".to_owned(),
+        1 => "tomorrow".to_owned(),
+        -1 => "yesterday".to_owned(),
+        _ => {
+            let n = days.abs();
+            let (count, unit) = match n {
+                0..=13 => (n, "day"),
+                14..=59 => (n / 7, "week"),
+                60..=729 => (n / 30, "month"),
+                _ => (n / 365, "year"),
+            };
+            let plural = if count == 1 { "" } else { "s" };
+            if days > 0 {
+                format!("in {} {}{}", count, unit, plural)
+            } else {
+                format!("{} {}{} ago", count, unit, plural)
+            }
+        }
+    }
+}
+
+// "just now", "5m ago", "3h ago", "2d ago"
+pub fn format_ago(at: DateTime<Local>, now: DateTime<Local>) -> String {
+    let minutes = (now - at).num_minutes();
+    match minutes {
+        ..1 => "just now".to_owned(),
+        1..60 => format!("{}m ago", minutes),
+        60..1440 => format!("{}h ago", minutes / 60),
+        _ => format!("{}d ago", minutes / 1440),
+    }
+}
+
+// "2024-06-01 14:00" (in datetime_format), "today 18:30" or "tomorrow 9am"
+pub fn parse_reminder(text: &str, now: DateTime<Local>, config: &Config) -> Result<DateTime<Local>, AppError> {
+    let text = text.trim();
+    let invalid = || AppError::InvalidInput(format!("invalid reminder \"{}\"", text));
+    let naive = match NaiveDateTime::parse_from_str(text, &config.datetime_format) {
+        Ok(naive) => naive,
+        Err(_) => {
+            let (day, time) = text.split_once(' ').ok_or_else(invalid)?;
+            let date = match day.to_lowercase().as_str() {
+                "today" => now.date_naive(),
+                "tomorrow" => now.date_naive() + Duration::days(1),
+                _ => NaiveDate::parse_from_str(day, &config.date_format).map_err(|_| invalid())?,
+            };
+            date.and_time(parse_time(time).ok_or_else(invalid)?)
+        }
+    };
+    Local.from_local_datetime(&naive).earliest().ok_or_else(invalid)
+}
+
+// A due date as typed: today, tomorrow or in date_format, empty for none
+pub fn parse_due(text: &str, today: NaiveDate, config: &Config) -> Result<Option<NaiveDate>, AppError> {
+    let text = text.trim();
+    match text.to_lowercase().as_str() {
+        "" => Ok(None),
+        "today" => Ok(Some(today)),
+        "tomorrow" => Ok(Some(today + Duration::days(1))),
+        _ => NaiveDate::parse_from_str(text, &config.date_format).map(Some).map_err(|_| {
+            AppError::InvalidInput(format!("invalid due date \"{}\", use {}", text, config.date_format))
+        }),
+    }
+}
+
+// "14:00", "9am", "9:30pm"
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    let text = text.trim().to_lowercase();
+    if let Ok(time) = NaiveTime::parse_from_str(&text, "%H:%M") {
+        return Some(time);
+    }
+    let (clock, pm) = match (text.strip_suffix("am"), text.strip_suffix("pm")) {
+        (Some(clock), _) => (clock, false),
+        (_, Some(clock)) => (clock, true),
+        _ => return None,
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+    if !(1..=12).contains(&hour) {
+        return None;
+    }
+    let hour = match (hour, pm) {
+        (12, false) => 0,
+        (12, true) => 12,
+        (h, true) => h + 12,
+        (h, false) => h,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_dates() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+        let day = |d: i64| today + chrono::Duration::days(d);
+        assert_eq!(format_relative_date(day(0), today), "today");
+        assert_eq!(format_relative_date(day(1), today), "tomorrow");
+        assert_eq!(format_relative_date(day(-1), today), "yesterday");
+        assert_eq!(format_relative_date(day(3), today), "in 3 days");
+        assert_eq!(format_relative_date(day(-3), today), "3 days ago");
+        assert_eq!(format_relative_date(day(14), today), "in 2 weeks");
+        assert_eq!(format_relative_date(day(-90), today), "3 months ago");
+        assert_eq!(format_relative_date(day(800), today), "in 2 years");
+    }
+
+    #[test]
+    fn reminders() {
+        let now = Local.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+        let at = |y, mo, d, h, mi| Local.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap();
+        let config = Config::default();
+        let parse_reminder = |text| parse_reminder(text, now, &config);
+        assert_eq!(parse_reminder("2024-06-01 14:00").unwrap(), at(2024, 6, 1, 14, 0));
+        assert_eq!(parse_reminder("tomorrow 9am").unwrap(), at(2024, 5, 11, 9, 0));
+        assert_eq!(parse_reminder("today 9:30pm").unwrap(), at(2024, 5, 10, 21, 30));
+        assert_eq!(parse_reminder("Tomorrow 12am").unwrap(), at(2024, 5, 11, 0, 0));
+        assert!(parse_reminder("someday").is_err());
+        assert!(parse_reminder("tomorrow 13pm").is_err());
+    }
+
+    #[test]
+    fn reminders_never_panic() {
+        let now = Local.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+        let config = Config::default();
+        let parts = [
+            "", " ", "today", "Tomorrow", "2024-06-01", "2024-02-30", "9", "12", "13", ":", "30", "99",
+            "am", "pm", "PM", "ü", "4294967296",
+        ];
+        // Every run of three parts, an error is fine but a panic is not
+        for a in parts {
+            for b in parts {
+                for c in parts {
+                    let _ = parse_reminder(&format!("{}{}{}", a, b, c), now, &config);
+                    let _ = parse_reminder(&format!("{} {}{}", a, b, c), now, &config);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::theme::Theme;
+use std::fmt;
+use std::io;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum AppError {
+    Io(io::Error),
+    Parse(String),
+    InvalidInput(String),
+    NotFound(String),
+    Crypto(String),
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Io(e) => write!(f, "{}", e),
+            AppError::Parse(msg) => write!(f, "parse error: {}", msg),
+            AppError::InvalidInput(msg) => write!(f, "{}", msg),
+            AppError::NotFound(msg) => write!(f, "{}", msg),
+            AppError::Crypto(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<io::Error> for AppError {
+    fn from(value: io::Error) -> Self {
+        AppError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(value: serde_json::Error) -> Self {
+        AppError::Parse(value.to_string())
+    }
+}
+
+// Something in the loaded lists that the app never produces itself, found by
+// AppState::integrity_check
+#[derive(Debug, PartialEq, Eq)]
+pub enum IntegrityError {
+    OrphanedSubtask { child_id: Uuid, missing_parent: Uuid },
+    DuplicateUuid(Uuid),
+    CompletedBeforeCreated(Uuid),
+    // Subtasks that are each other's ancestors, in parent order, so none of them can be done
+    // after all of its subtasks
+    BlockingCycle(Vec<Uuid>),
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrityError::OrphanedSubtask { child_id, missing_parent } => write!(
+                f,
+                "item {} is a subtask of {}, which is not in its list",
+                child_id, missing_parent
+            ),
+            IntegrityError::DuplicateUuid(id) => write!(f, "more than one item has the id {}", id),
+            IntegrityError::CompletedBeforeCreated(id) => {
+                write!(f, "item {} was completed before it was created", id)
+            }
+            IntegrityError::BlockingCycle(ids) => {
+                let ids: Vec<String> = ids.iter().map(Uuid::to_string).collect();
+                write!(f, "items {} are subtasks of each other", ids.join(" -> "))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    InvalidBorderType(String),
+    InvalidTitleFormat(String),
+    InvalidStyle(String),
+    InvalidDateFormat(String),
+    InvalidTheme(String),
+    // The color under [theme] and its value
+    InvalidColor(String, String),
+    UnknownColor(String),
+    InvalidKey(String),
+    // The setting and its value
+    InvalidSymbol(String, String),
+    // The setting and the key it would take from the main list
+    KeyConflict(String, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::InvalidBorderType(value) => write!(
+                f,
+                "invalid border_type \"{}\", expected rounded, plain, double or thick",
+                value
+            ),
+            ConfigError::InvalidTitleFormat(placeholder) => write!(
+                f,
+                "invalid list_title_format placeholder \"{{{}}}\"",
+                placeholder
+            ),
+            ConfigError::InvalidStyle(value) => write!(f, "invalid style \"{}\"", value),
+            ConfigError::InvalidDateFormat(value) => write!(
+                f,
+                "invalid date format \"{}\", dates written with it could not be read back",
+                value
+            ),
+            ConfigError::InvalidTheme(value) => write!(f, "unknown theme \"{}\"", value),
+            ConfigError::InvalidColor(name, value) => write!(
+                f,
+                "invalid {} color \"{}\", expected e.g. \"#rrggbb\" or \"blue\"",
+                name, value
+            ),
+            ConfigError::UnknownColor(name) => write!(
+                f,
+                "unknown color \"{}\" under [theme], expected name or one of {}",
+                name,
+                Theme::default().colors().map(|(name, _)| name).join(", ")
+            ),
+            ConfigError::InvalidKey(value) => write!(
+                f,
+                "invalid key \"{}\", expected e.g. \"space\", \"x\", \"ctrl+x\" or \"f2\"",
+                value
+            ),
+            ConfigError::InvalidSymbol(setting, value) => {
+                write!(f, "invalid {} \"{}\", expected 1 to 3 printable columns", setting, value)
+            }
+            ConfigError::KeyConflict(setting, key) => {
+                write!(f, "{} \"{}\" is already bound on the main list", setting, key)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
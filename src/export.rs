@@ -0,0 +1,156 @@
+use crate::app::AppState;
+use crate::item::{Priority, TodoItem};
+use chrono::{Duration, Local, NaiveDate};
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExportFormat {
+    Markdown,
+    PlainText,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::PlainText => "txt",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+impl AppState {
+    pub fn export_weekly_digest(&self, week_start: NaiveDate) -> String {
+        let week_end = week_start + Duration::days(7);
+        let in_week = |date: NaiveDate| week_start <= date && date < week_end;
+        let today = Local::now().date_naive();
+
+        let completed: Vec<&TodoItem> = self
+            .all_items()
+            .filter(|item| item.completed_at.is_some_and(|at| in_week(at.date_naive())))
+            .collect();
+        let added: Vec<&TodoItem> = self
+            .all_items()
+            .filter(|item| in_week(item.created_at.date_naive()))
+            .collect();
+        let overdue: Vec<&TodoItem> = self
+            .all_items()
+            .filter(|item| !item.done && item.due_date.is_some_and(|due| due < today))
+            .collect();
+
+        let mut out = format!(
+            "# Weekly digest {} to {}\n",
+            week_start.format(&self.config.date_format),
+            (week_end - Duration::days(1)).format(&self.config.date_format)
+        );
+        for (heading, items) in [
+            ("Completed this week", &completed),
+            ("Added this week", &added),
+            ("Overdue", &overdue),
+        ] {
+            let _ = writeln!(out, "\n## {} ({})\n", heading, items.len());
+            for item in items.iter() {
+                let due = match item.due_date {
+                    Some(due) if heading == "Overdue" => format!(" (due {})", due.format(&self.config.date_format)),
+                    _ => String::new(),
+                };
+                let _ = writeln!(out, "- {}{}", item.description, due);
+            }
+        }
+        out
+    }
+
+    // The items of the active list as shown, "[x] description" when done and "[ ] description"
+    // when not, after the item's id with `with_ids`
+    pub fn export_plaintext(&self, with_ids: bool) -> String {
+        let mut out = String::new();
+        for item in self.visible_indices().into_iter().filter_map(|i| self.get(i)) {
+            if with_ids {
+                let _ = write!(out, "{} ", item.id);
+            }
+            let _ = writeln!(out, "[{}] {}", if item.done { 'x' } else { ' ' }, item.description);
+        }
+        out
+    }
+}
+
+// Markdown is a task list with the priority and due date after the description, the CSV has
+// the columns import_csv reads back
+pub fn export(items: &[&TodoItem], format: ExportFormat, writer: &mut dyn io::Write) -> io::Result<()> {
+    if format == ExportFormat::Csv {
+        writeln!(writer, "done,description,priority,due_date,tags")?;
+    }
+    for item in items {
+        let mark = if item.done { 'x' } else { ' ' };
+        let due = item.due_date.map(|due| due.format("%Y-%m-%d").to_string());
+        match format {
+            ExportFormat::Markdown => {
+                let mut details = vec![];
+                if item.priority != Priority::None {
+                    details.push(format!("priority: {}", item.priority.label()));
+                }
+                if let Some(due) = &due {
+                    details.push(format!("due: {}", due));
+                }
+                let details = if details.is_empty() { String::new() } else { format!(" ({})", details.join(", ")) };
+                writeln!(writer, "- [{}] {}{}", mark, item.description, details)?;
+            }
+            ExportFormat::PlainText => writeln!(writer, "[{}] {}", mark, item.description)?,
+            ExportFormat::Csv => {
+                let fields = [
+                    item.done.to_string(),
+                    item.description.clone(),
+                    item.priority.label().to_owned(),
+                    due.unwrap_or_default(),
+                    item.tags.join(" "),
+                ];
+                let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+                writeln!(writer, "{}", fields.join(","))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Quoted when it holds a comma, quote or line break, with quotes doubled
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+// Exports land in the current directory unless --export-dir names another
+pub fn write_export(dir: Option<&Path>, file_name: &str, contents: &[u8]) -> io::Result<PathBuf> {
+    let path = dir.map_or_else(|| PathBuf::from(file_name), |dir| dir.join(file_name));
+    fs::write(&path, contents)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_export_reads_back() {
+        let item = TodoItem::builder()
+            .description("Call \"Bob\", then Alice")
+            .priority(Priority::High)
+            .due(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap())
+            .tags(vec!["work".to_owned(), "phone".to_owned()])
+            .build()
+            .unwrap();
+        let mut out = vec![];
+        export(&[&item], ExportFormat::Csv, &mut out).unwrap();
+        let items = AppState::import_csv(&String::from_utf8(out).unwrap()).unwrap();
+        assert_eq!(items[0].description, item.description);
+        assert_eq!(items[0].priority, item.priority);
+        assert_eq!(items[0].due_date, item.due_date);
+        assert_eq!(items[0].tags, item.tags);
+    }
+}
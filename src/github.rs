@@ -0,0 +1,76 @@
+use crate::error::AppError;
+use crate::item::TodoItem;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::env;
+use std::panic;
+use std::thread;
+
+pub const TOKEN_ENV: &str = "GITHUB_TOKEN";
+
+#[derive(Deserialize)]
+struct Issue {
+    number: u64,
+    title: String,
+    html_url: String,
+    #[serde(default)]
+    labels: Vec<Label>,
+    milestone: Option<Milestone>,
+    // Set when the "issue" is a pull request, which the issues endpoint also returns
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Milestone {
+    due_on: Option<DateTime<Utc>>,
+}
+
+// Open issues of `owner/repo` as items, the first 100 of them. GITHUB_TOKEN is sent when
+// set, without it only public repositories can be read
+pub fn fetch_issues(repo: &str) -> Result<Vec<TodoItem>, AppError> {
+    let repo = repo.trim();
+    if repo.split('/').filter(|part| !part.is_empty()).count() != 2 {
+        return Err(AppError::InvalidInput(format!("expected owner/repo, got '{}'", repo)));
+    }
+    let url = format!("https://api.github.com/repos/{}/issues?state=open&per_page=100", repo);
+    // reqwest's blocking client panics on a thread running the event loop's runtime
+    let (status, body) = thread::scope(|scope| scope.spawn(|| get(&url)).join())
+        .unwrap_or_else(|e| panic::resume_unwind(e))
+        .map_err(|e| AppError::Io(std::io::Error::other(e)))?;
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err(AppError::NotFound(format!("no repository {}", repo)));
+    }
+    if !status.is_success() {
+        return Err(AppError::InvalidInput(format!("GitHub answered {} for {}", status, repo)));
+    }
+    let issues: Vec<Issue> = serde_json::from_str(&body)?;
+    Ok(issues.into_iter().filter(|issue| issue.pull_request.is_none()).filter_map(to_item).collect())
+}
+
+fn get(url: &str) -> reqwest::Result<(reqwest::StatusCode, String)> {
+    let mut request = reqwest::blocking::Client::new()
+        .get(url)
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "todo-tui");
+    if let Ok(token) = env::var(TOKEN_ENV) {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send()?;
+    let status = response.status();
+    Ok((status, response.text()?))
+}
+
+fn to_item(issue: Issue) -> Option<TodoItem> {
+    TodoItem::builder()
+        .description(&format!("#{}: {}", issue.number, issue.title))
+        .tags(issue.labels.into_iter().map(|label| label.name).collect())
+        .due(issue.milestone.and_then(|m| m.due_on).map(|due| due.date_naive()))
+        .url(issue.html_url)
+        .build()
+        .ok()
+}
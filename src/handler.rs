@@ -0,0 +1,2164 @@
+use crate::app::{AddField, AppState, CurrentScreen, SortOrder, INBOX_LIST};
+use crate::context_menu::{MenuAction, PRIORITIES};
+use crate::date_utils::parse_due;
+use crate::export::{self, ExportFormat};
+use crate::item::{TodoItem, TodoItemBuilder, LABEL_COLORS};
+use crate::github;
+use crate::palette::{self, Command};
+use crate::qr::QrCode;
+use crate::review::ReviewStep;
+use crate::theme::THEMES;
+use crate::strings::fill;
+use crate::webhook::WebhookEvent;
+use chrono::{Duration, Local, NaiveDate};
+use ratatui::crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::Rect;
+use tui_input::backend::crossterm::EventHandler;
+use tui_input::Input;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::time::Instant;
+
+// Longest gap between the clicks of a double click
+const DOUBLE_CLICK: std::time::Duration = std::time::Duration::from_millis(300);
+
+impl AppState {
+    pub fn handle_key_event(&mut self, key: KeyEvent) {
+        if key.kind == KeyEventKind::Release {
+            // Skip events that are not KeyEventKind::Press
+            return;
+        }
+        self.status_message = None;
+        if self.macro_recording && !self.macro_playing {
+            self.macro_buffer.push(key);
+        }
+        let typing = self.current_screen.is_text_input() || self.review_editing_due;
+        if key.code == KeyCode::Char('i') && !typing {
+            // Quick capture into the Inbox from anywhere
+            self.inbox_return_screen = self.current_screen;
+            self.current_screen = CurrentScreen::Inbox;
+            return;
+        }
+        if key.code == KeyCode::Char('?') && !typing && self.current_screen != CurrentScreen::Help {
+            self.help_return_screen = self.current_screen;
+            self.help_scroll = 0;
+            self.current_screen = CurrentScreen::Help;
+            return;
+        }
+        match self.current_screen {
+            CurrentScreen::Main => self.handle_main_key(key),
+            CurrentScreen::Edit => self.handle_edit_key(key),
+            CurrentScreen::Add => self.handle_add_key(key),
+            CurrentScreen::PasteMultiple => self.handle_paste_multiple_key(key),
+            CurrentScreen::Inbox => self.handle_inbox_key(key),
+            CurrentScreen::TemplatePicker => self.handle_template_picker_key(key),
+            CurrentScreen::CommandPalette => self.handle_command_palette_key(key),
+            CurrentScreen::ConfirmDedup => self.handle_confirm_dedup_key(key),
+            CurrentScreen::SearchReplace => self.handle_search_replace_key(key),
+            CurrentScreen::ColorPicker => self.handle_color_picker_key(key),
+            CurrentScreen::ConfirmClearColor => self.handle_confirm_clear_color_key(key),
+            CurrentScreen::Focus => self.handle_focus_key(key),
+            CurrentScreen::NoteEditor => self.handle_note_editor_key(key),
+            CurrentScreen::AddComment => self.handle_add_comment_key(key),
+            CurrentScreen::PasswordPrompt => self.handle_password_prompt_key(key),
+            CurrentScreen::Stats => self.handle_stats_key(key),
+            CurrentScreen::ConfirmClearDone => self.handle_confirm_clear_done_key(key),
+            CurrentScreen::ConfirmBulk => self.handle_confirm_bulk_key(key),
+            CurrentScreen::Search => self.handle_search_key(key),
+            CurrentScreen::ConfirmDiscardNote => self.handle_confirm_discard_note_key(key),
+            CurrentScreen::ConfirmDiscard => self.handle_confirm_discard_key(key),
+            CurrentScreen::DuplicateWarning => self.handle_duplicate_warning_key(key),
+            CurrentScreen::SpellCheck => self.handle_spell_check_key(key),
+            CurrentScreen::GlobalSearch => self.handle_global_search_key(key),
+            CurrentScreen::MergeListPicker => self.handle_merge_list_picker_key(key),
+            CurrentScreen::ConfirmMerge => self.handle_confirm_merge_key(key),
+            CurrentScreen::SplitListName => self.handle_split_list_name_key(key),
+            CurrentScreen::SaveFilterName => self.handle_save_filter_name_key(key),
+            CurrentScreen::MoveToPosition => self.handle_move_to_position_key(key),
+            CurrentScreen::LoadFilter => self.handle_load_filter_key(key),
+            CurrentScreen::ConfirmSplit => self.handle_confirm_split_key(key),
+            CurrentScreen::ConfirmDelete => self.handle_confirm_delete_key(key),
+            CurrentScreen::RecentlyDone => self.handle_recently_done_key(key),
+            CurrentScreen::DailyPrompt => self.handle_daily_prompt_key(key),
+            CurrentScreen::WeeklyReview => self.handle_weekly_review_key(key),
+            CurrentScreen::StaleItemsReview => self.handle_stale_review_key(key),
+            CurrentScreen::Timeline => self.handle_timeline_key(key),
+            CurrentScreen::ItemDetail => self.handle_item_detail_key(key),
+            CurrentScreen::LinkSearch => self.handle_link_search_key(key),
+            CurrentScreen::ParentPicker => self.handle_parent_picker_key(key),
+            CurrentScreen::GithubImport => self.handle_github_import_key(key),
+            CurrentScreen::Import => self.handle_import_key(key),
+            CurrentScreen::BatchTag => self.handle_batch_tag_key(key),
+            CurrentScreen::ThemePreview => self.handle_theme_preview_key(key),
+            CurrentScreen::UndoHistory => self.handle_undo_history_key(key),
+            CurrentScreen::ContextMenu | CurrentScreen::PriorityMenu => self.handle_context_menu_key(key),
+            CurrentScreen::SortMenu => self.handle_sort_menu_key(key),
+            CurrentScreen::TagFilter => self.handle_tag_filter_key(key),
+            CurrentScreen::Help => self.handle_help_key(key),
+            CurrentScreen::Archive => self.handle_archive_key(key),
+            CurrentScreen::ConfirmGithubImport => self.handle_confirm_github_import_key(key),
+            CurrentScreen::Exit => {}
+        }
+    }
+
+    // Swipes on the main list, sent as left button drags by iTerm2 and WezTerm
+    pub fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        match self.current_screen {
+            CurrentScreen::ContextMenu | CurrentScreen::PriorityMenu => {
+                self.handle_context_menu_mouse(mouse);
+                return;
+            }
+            CurrentScreen::Main => {}
+            _ => return,
+        }
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Right) => {
+                self.open_context_menu(mouse.column, mouse.row);
+                return;
+            }
+            MouseEventKind::ScrollUp => {
+                self.todo_list_state.select_previous();
+                return;
+            }
+            MouseEventKind::ScrollDown => {
+                self.todo_list_state.select_next();
+                return;
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(position) = self.item_row_at(mouse.column, mouse.row) {
+                    self.todo_list_state.select(Some(position));
+                    let now = Instant::now();
+                    let double = self.last_click.is_some_and(|(row, at)| {
+                        row == position && now.duration_since(at) < DOUBLE_CLICK
+                    });
+                    self.last_click = Some((position, now));
+                    if double {
+                        self.last_click = None;
+                        self.drag_source = None;
+                        self.drag_start = None;
+                        self.edit_selected();
+                        return;
+                    }
+                }
+            }
+            _ => {}
+        }
+        if self.config.mouse_drag_reorder {
+            match mouse.kind {
+                MouseEventKind::Down(MouseButton::Left) => {
+                    self.drag_source = self.item_row_at(mouse.column, mouse.row);
+                    self.drag_target = self.drag_source.unwrap_or(0);
+                    self.drag_position = (mouse.column, mouse.row);
+                }
+                MouseEventKind::Drag(MouseButton::Left) if self.drag_source.is_some() => {
+                    self.drag_target = self.drag_row(mouse.row);
+                    self.drag_position = (mouse.column, mouse.row);
+                    return;
+                }
+                MouseEventKind::Up(MouseButton::Left) => {
+                    // A press without moving to another row is left to the swipes
+                    if let Some(source) = self.drag_source.take().filter(|&source| source != self.drag_target) {
+                        self.drag_start = None;
+                        self.reorder(source, self.drag_target);
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !self.config.swipe_gestures {
+            return;
+        }
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.drag_start = Some((mouse.column, mouse.row));
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                let Some((column, row)) = self.drag_start.take() else {
+                    return;
+                };
+                let dx = mouse.column as i32 - column as i32;
+                let dy = mouse.row as i32 - row as i32;
+                if dx > 20 {
+                    if self.selected_item_index().is_some() {
+                        self.current_screen = CurrentScreen::ConfirmDelete;
+                    }
+                } else if dx < -20 {
+                    self.archive_selected();
+                } else if dy.abs() > 3 {
+                    self.move_selected(dy < 0);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Row of the visible item drawn at the cell, spacer rows count as the item above
+    fn item_row_at(&self, column: u16, row: u16) -> Option<usize> {
+        if !self.item_area.contains((column, row).into()) {
+            return None;
+        }
+        Some(self.drag_row(row))
+    }
+
+    // Row of the item the mouse is over, the first or last one shown when above or below them;
+    // a separator row counts as the item above it
+    fn drag_row(&self, row: u16) -> usize {
+        let (start, end) = self.render_window;
+        let row = row.saturating_sub(self.item_area.y);
+        let offset = self.item_rows.iter().rposition(|&top| top <= row).unwrap_or(0);
+        (start + offset).min(end.saturating_sub(1))
+    }
+
+    // Below the selected row, where a right click on it would have opened it
+    fn open_marked_priority_menu(&mut self) {
+        let row = self.todo_list_state.selected().and_then(|row| row.checked_sub(self.render_window.0));
+        let top = row.and_then(|row| self.item_rows.get(row)).copied().unwrap_or(0);
+        self.context_menu_position = (self.item_area.x + 4, self.item_area.y + top);
+        self.context_menu_state.select(Some(0));
+        self.current_screen = CurrentScreen::PriorityMenu;
+    }
+
+    // Select the item drawn at the cell and open the context menu there
+    fn open_context_menu(&mut self, column: u16, row: u16) {
+        let Some(position) = self.item_row_at(column, row) else {
+            return;
+        };
+        let Some(&index) = self.visible_indices().get(position) else {
+            return;
+        };
+        self.todo_list_state.select(Some(position));
+        self.context_menu_item = index;
+        self.context_menu_position = (column, row);
+        self.context_menu_state.select(Some(0));
+        self.current_screen = CurrentScreen::ContextMenu;
+    }
+
+    // A click on an entry runs it, anywhere else closes the menu
+    fn handle_context_menu_mouse(&mut self, mouse: MouseEvent) {
+        if !matches!(mouse.kind, MouseEventKind::Down(_)) {
+            return;
+        }
+        let area = self.context_menu_area;
+        let inner = Rect::new(area.x + 1, area.y + 1, area.width.saturating_sub(2), area.height.saturating_sub(2));
+        if mouse.kind != MouseEventKind::Down(MouseButton::Left) || !inner.contains((mouse.column, mouse.row).into()) {
+            self.current_screen = CurrentScreen::Main;
+            return;
+        }
+        self.context_menu_state.select(Some((mouse.row - inner.y) as usize));
+        self.handle_context_menu_key(KeyEvent::from(KeyCode::Enter));
+    }
+
+    fn handle_context_menu_key(&mut self, key: KeyEvent) {
+        let priorities = self.current_screen == CurrentScreen::PriorityMenu;
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Up => {
+                self.context_menu_state.select_previous();
+            }
+            KeyCode::Down => {
+                self.context_menu_state.select_next();
+            }
+            KeyCode::Enter if priorities => {
+                let selected = self.context_menu_state.selected().unwrap_or(0);
+                match PRIORITIES.get(selected) {
+                    Some(&priority) if !self.marked.is_empty() => self.set_marked_priority(priority),
+                    Some(&priority) => self.set_priority(self.context_menu_item, priority),
+                    None => {}
+                }
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Enter => {
+                let selected = self.context_menu_state.selected().unwrap_or(0);
+                let Some(&action) = MenuAction::ALL.get(selected) else {
+                    return;
+                };
+                self.current_screen = CurrentScreen::Main;
+                self.run_menu_action(action);
+            }
+            _ => {}
+        }
+    }
+
+    fn run_menu_action(&mut self, action: MenuAction) {
+        let index = self.context_menu_item;
+        match action {
+            MenuAction::Edit => self.edit_selected(),
+            MenuAction::Delete => self.current_screen = CurrentScreen::ConfirmDelete,
+            MenuAction::ToggleDone => self.toggle_selected(),
+            MenuAction::Duplicate => self.duplicate_item(index),
+            MenuAction::SetPriority => {
+                let current = self.get(index).map(|item| item.priority);
+                let row = PRIORITIES.iter().position(|&p| Some(p) == current).unwrap_or(0);
+                self.context_menu_state.select(Some(row));
+                self.current_screen = CurrentScreen::PriorityMenu;
+            }
+            MenuAction::AddTag => {
+                self.tag_input.reset();
+                self.tag_item = Some(index);
+                self.current_screen = CurrentScreen::BatchTag;
+            }
+        }
+    }
+
+    fn handle_main_key(&mut self, key: KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if self.export_pending {
+            self.export_pending = false;
+            self.handle_export_key(key);
+            return;
+        }
+        if self.undo_pending {
+            self.undo_pending = false;
+            if key.code == KeyCode::Char('h') {
+                self.open_undo_history();
+                return;
+            }
+        }
+        if self.replay_pending {
+            self.replay_pending = false;
+            // A macro that replays itself would never end
+            if key.code == KeyCode::Char('q') && !self.macro_recording && !self.macro_playing {
+                self.macro_position = 0;
+                self.macro_playing = true;
+                if self.macro_buffer.is_empty() {
+                    self.flash(self.strings.no_macro.clone());
+                }
+            }
+            return;
+        }
+        if self.g_pending {
+            self.g_pending = false;
+            if key.code == KeyCode::Char('g') {
+                // Go to item N, or the first one without a count
+                let len = self.visible_indices().len();
+                let row = self.consume_prefix();
+                if len > 0 {
+                    self.todo_list_state.select(Some(row.min(len) - 1));
+                }
+                return;
+            }
+        }
+        match key.code {
+            KeyCode::Char(c @ '0'..='9') if !ctrl => {
+                // Count for the next motion, as in vim
+                if self.numeric_prefix.len() < 6 {
+                    self.numeric_prefix.push(c);
+                }
+                return;
+            }
+            KeyCode::Up | KeyCode::Down | KeyCode::Char('j' | 'k' | 'g' | 'G') => {}
+            _ => self.numeric_prefix.clear(),
+        }
+        if self.config.tab_opens_next && key.code == KeyCode::Tab {
+            let last = self.visible_indices().len().saturating_sub(1);
+            let row = self.next_incomplete_after(self.todo_list_state.selected().unwrap_or(last));
+            if row.is_some() {
+                self.todo_list_state.select(row);
+            }
+            return;
+        }
+        if self.config.show_sidebar && key.code == KeyCode::Tab {
+            self.sidebar_focused = !self.sidebar_focused;
+            self.sidebar_state.select(Some(self.active_list));
+            return;
+        }
+        if self.config.show_sidebar && self.sidebar_focused {
+            match key.code {
+                KeyCode::Up => self.sidebar_state.select_previous(),
+                KeyCode::Down => self.sidebar_state.select_next(),
+                KeyCode::Enter => {
+                    if let Some(list) = self.sidebar_state.selected().filter(|&l| l < self.lists.len()) {
+                        self.switch_list(list);
+                        self.sidebar_focused = false;
+                    }
+                }
+                KeyCode::Esc => self.sidebar_focused = false,
+                _ if self.config.quit_key.matches(&key) => self.current_screen = CurrentScreen::Exit,
+                _ => {}
+            }
+            return;
+        }
+        if self.config.toggle_done_key.matches(&key) {
+            // Mark selected, or everything marked with v
+            if self.marked.is_empty() {
+                self.toggle_selected();
+            } else {
+                self.toggle_marked_done();
+            }
+            return;
+        }
+        match key.code {
+            KeyCode::Char('a') if ctrl => self.open_archive(),
+            KeyCode::Char('e') if ctrl => {
+                // Export, the next key picks what
+                self.export_pending = true;
+                self.flash(self.strings.export_keys.clone());
+            }
+            // Terminals send Ctrl+/ as Ctrl+7
+            KeyCode::Char('/') | KeyCode::Char('7') if ctrl => {
+                // Search every list
+                self.global_search_input.reset();
+                self.global_search_state.select(None);
+                self.current_screen = CurrentScreen::GlobalSearch;
+            }
+            // Most terminals send Ctrl+Shift+D as Ctrl+D
+            KeyCode::Char('d' | 'D') if ctrl => {
+                self.distraction_free = !self.distraction_free;
+            }
+            // And Ctrl+Shift+O as Ctrl+O
+            KeyCode::Char('o' | 'O') if ctrl => self.outline_mode = !self.outline_mode,
+            // Only where Ctrl+Shift+C arrives apart from Ctrl+C, otherwise through the palette
+            KeyCode::Char('C') if ctrl => self.copy_selected_share(),
+            KeyCode::Char('c') if ctrl => self.copy_selected(),
+            KeyCode::Char('r') if ctrl => {
+                // Today's wins
+                let any = !self.recently_done().is_empty();
+                self.recent_state.select(any.then_some(0));
+                self.current_screen = CurrentScreen::RecentlyDone;
+            }
+            KeyCode::Char('s') if ctrl => {
+                // Review misspelled words
+                if !self.spell_checker.is_available() {
+                    self.flash(self.strings.spell_check_unavailable.clone());
+                } else if self.start_spell_check() {
+                    self.current_screen = CurrentScreen::SpellCheck;
+                } else {
+                    self.flash(self.strings.no_misspelled_words.clone());
+                }
+            }
+            KeyCode::Char('Q') => {
+                // Start recording a macro, or stop without keeping the Q
+                if self.macro_recording {
+                    self.macro_buffer.pop();
+                    self.macro_recording = false;
+                } else if !self.macro_playing {
+                    self.macro_buffer.clear();
+                    self.macro_recording = true;
+                }
+            }
+            KeyCode::Char('@') => {
+                // Replay the macro, @q as in vim
+                self.replay_pending = true;
+            }
+            _ if self.config.add_key.matches(&key) => {
+                // Add new item
+                self.set_add_field(AddField::Description);
+                self.current_screen = CurrentScreen::Add;
+            }
+            KeyCode::Enter if self.selected_item_index().is_some() => {
+                self.current_screen = CurrentScreen::ItemDetail;
+            }
+            _ if self.config.quit_key.matches(&key) => {
+                // Quit
+                self.current_screen = CurrentScreen::Exit;
+            }
+            KeyCode::Char('t') if !ctrl => self.open_timeline(),
+            KeyCode::Char('#') if self.selected_item_index().is_some() => {
+                self.position_input.reset();
+                self.input_error = None;
+                self.current_screen = CurrentScreen::MoveToPosition;
+            }
+            KeyCode::Char('v') => self.toggle_mark(),
+            KeyCode::Char('p') if !self.marked.is_empty() => self.open_marked_priority_menu(),
+            _ if self.config.delete_key.matches(&key)
+                && (self.selected_item_index().is_some() || !self.marked.is_empty()) =>
+            {
+                // Delete selected or marked, after confirming
+                self.current_screen = CurrentScreen::ConfirmDelete;
+            }
+            KeyCode::Char('n') => {
+                // Edit notes of selected
+                self.edit_selected_notes();
+            }
+            KeyCode::Char('o') => {
+                // Open the first URL of selected
+                self.open_selected_url();
+            }
+            KeyCode::Char('A') => {
+                // Mark all done
+                self.start_bulk(true);
+            }
+            KeyCode::Char('U') => {
+                // Mark all undone
+                self.start_bulk(false);
+            }
+            KeyCode::Char('x') => self.compact_mode = !self.compact_mode,
+            KeyCode::Char('w') if !ctrl => self.wrap = !self.wrap,
+            KeyCode::Char('c') => {
+                if let Some(index) = self.selected_item_index() {
+                    self.toggle_collapse(index);
+                }
+            }
+            KeyCode::Char('X') => {
+                // Remove all done items, after confirming
+                if self.done_count() > 0 {
+                    self.current_screen = CurrentScreen::ConfirmClearDone;
+                } else {
+                    self.flash(self.strings.no_completed_items.clone());
+                }
+            }
+            KeyCode::Char('S') => {
+                // Statistics
+                self.update_streak();
+                self.current_screen = CurrentScreen::Stats;
+            }
+            KeyCode::Char('F') if self.selected_item_index().is_some() => {
+                // Show only the selected item
+                self.comment_state.select(None);
+                self.current_screen = CurrentScreen::Focus;
+            }
+            KeyCode::Home if ctrl => {
+                if let Some(sel_index) = self.selected_item_index() {
+                    self.move_item_to_top(sel_index);
+                }
+            }
+            KeyCode::End if ctrl => {
+                if let Some(sel_index) = self.selected_item_index() {
+                    self.move_item_to_bottom(sel_index);
+                }
+            }
+            // Only terminals with the kitty keyboard protocol tell Ctrl+M from Enter, the
+            // palette has "Move to parent…" for the rest
+            KeyCode::Char('m') if ctrl => self.open_parent_picker(),
+            // Ctrl+Shift+T, which most terminals send as Ctrl+T
+            KeyCode::Char('t' | 'T') if ctrl => {
+                if self.filter_state().is_empty() {
+                    self.flash(self.strings.tag_needs_filter.clone());
+                } else {
+                    self.tag_input.reset();
+                    self.tag_item = None;
+                    self.current_screen = CurrentScreen::BatchTag;
+                }
+            }
+            KeyCode::Char('p') if ctrl => {
+                if let Some(index) = self.selected_item_index() {
+                    self.promote_subtask(index);
+                }
+            }
+            KeyCode::Char(c @ ('+' | '-')) => {
+                let days = if c == '+' { 1 } else { -1 };
+                let shifted = self.selected_item_index().and_then(|index| self.shift_due(index, days));
+                if let Some(due) = shifted {
+                    let today = Local::now().date_naive();
+                    let text = match due {
+                        Some(due) => fill(&self.strings.due, &[("due", &self.config.format_date(due, today))]),
+                        None => self.strings.due_cleared.clone(),
+                    };
+                    self.flash(text);
+                }
+            }
+            KeyCode::Char('s') => self.open_sort_menu(),
+            KeyCode::Char('T') => self.open_tag_filter(),
+            KeyCode::Char('p') => {
+                if let Some(index) = self.selected_item_index() {
+                    let priority = self.get(index).map(|item| item.priority.cycle()).unwrap_or_default();
+                    self.set_priority(index, priority);
+                    self.flash(fill(&self.strings.priority, &[("priority", &priority.label())]));
+                }
+            }
+            KeyCode::Char('z') if ctrl => {
+                self.undo();
+                self.undo_pending = true;
+            }
+            KeyCode::Char('y' | 'Z') if ctrl => self.redo(),
+            _ if self.config.search_key.matches(&key) => {
+                // Search
+                self.current_screen = CurrentScreen::Search;
+            }
+            KeyCode::Esc if !self.marked.is_empty() => self.marked.clear(),
+            KeyCode::Esc if self.has_filter() => {
+                self.clear_filters();
+                self.todo_list_state.select(Some(0));
+            }
+            KeyCode::Char(':') => {
+                // Command palette
+                self.palette_input.reset();
+                self.palette_state.select(Some(0));
+                self.current_screen = CurrentScreen::CommandPalette;
+            }
+            KeyCode::Char('L') => {
+                // Switch to the next list
+                self.next_list();
+            }
+            KeyCode::Up | KeyCode::Down if key.modifiers.contains(KeyModifiers::ALT) => {
+                self.move_selected(key.code == KeyCode::Up);
+            }
+            KeyCode::Up | KeyCode::Char('k') => match self.consume_prefix() {
+                1 => match self.scroll_step(false) {
+                    Some(1) => self.select_previous_wrap(),
+                    Some(count) => self.select_by(count, false),
+                    None => {}
+                },
+                count => self.select_by(count, false),
+            },
+            KeyCode::Down | KeyCode::Char('j') => match self.consume_prefix() {
+                1 => match self.scroll_step(true) {
+                    Some(1) => self.select_next_wrap(),
+                    Some(count) => self.select_by(count, true),
+                    None => {}
+                },
+                count => self.select_by(count, true),
+            },
+            KeyCode::Char('g') if !ctrl => self.g_pending = true,
+            KeyCode::Char('G') => {
+                // Go to item N, or the last one without a count
+                let len = self.visible_indices().len();
+                let row = if self.numeric_prefix.is_empty() { len } else { self.consume_prefix() };
+                if len > 0 {
+                    self.todo_list_state.select(Some(row.min(len) - 1));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_password_prompt_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Exit;
+            }
+            KeyCode::Enter => {
+                let password = self.password_input.value_and_reset();
+                match self.unlock(password) {
+                    Ok(()) => {
+                        self.password_error = None;
+                        self.current_screen = CurrentScreen::Main;
+                    }
+                    Err(_) => {
+                        self.password_attempts_left = self.password_attempts_left.saturating_sub(1);
+                        if self.password_attempts_left == 0 {
+                            self.current_screen = CurrentScreen::Exit;
+                        } else {
+                            let count = self.password_attempts_left;
+                            let error = fill(&self.strings.wrong_password, &[("count", &count)]);
+                            self.password_error = Some(error);
+                        }
+                    }
+                }
+            }
+            _ => {
+                self.password_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_export_key(&mut self, key: KeyEvent) {
+        let today = Local::now().date_naive();
+        let (file_name, contents) = match key.code {
+            KeyCode::Char('w') => {
+                let week_start = today.week(self.config.first_day_of_week).first_day();
+                let file_name = format!("weekly-digest-{}.md", week_start.format("%Y-%m-%d"));
+                (file_name, self.export_weekly_digest(week_start))
+            }
+            KeyCode::Char(c @ ('t' | 'm' | 'c')) => {
+                let format = match c {
+                    't' => ExportFormat::PlainText,
+                    'm' => ExportFormat::Markdown,
+                    _ => ExportFormat::Csv,
+                };
+                let items: Vec<&TodoItem> = self.visible_indices().into_iter().filter_map(|i| self.get(i)).collect();
+                let mut contents = vec![];
+                // Writing to a Vec can't fail
+                let _ = export::export(&items, format, &mut contents);
+                let file_name = format!("todos-{}.{}", today.format("%Y-%m-%d"), format.extension());
+                (file_name, String::from_utf8_lossy(&contents).into_owned())
+            }
+            _ => return,
+        };
+        match export::write_export(self.export_dir.as_deref(), &file_name, contents.as_bytes()) {
+            Ok(path) => self.flash(fill(&self.strings.exported_to, &[("path", &path.display())])),
+            Err(e) => {
+                let args: [(&str, &dyn Display); 2] = [("file", &file_name), ("error", &e)];
+                self.flash(fill(&self.strings.export_failed, &args));
+            }
+        }
+    }
+
+    fn handle_search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.search_input.reset();
+                self.search_history_idx = None;
+                self.todo_list_state.select(Some(0));
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Enter => {
+                // Keep the results on screen
+                self.record_search();
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.search_fuzzy = !self.search_fuzzy;
+                self.todo_list_state.select(Some(0));
+            }
+            // Up in an empty input goes through the earlier searches, a typed query
+            // moves between its results as before
+            KeyCode::Up | KeyCode::Down
+                if self.search_history_idx.is_some()
+                    || (key.code == KeyCode::Up
+                        && self.search_input.value().is_empty()
+                        && !self.search_history.is_empty()) =>
+            {
+                self.recall_search(key.code == KeyCode::Up);
+            }
+            KeyCode::Up => {
+                self.select_previous_wrap();
+            }
+            KeyCode::Down => {
+                self.select_next_wrap();
+            }
+            _ => {
+                self.search_input.handle_event(&Event::Key(key));
+                self.search_history_idx = None;
+                self.todo_list_state.select(Some(0));
+            }
+        }
+    }
+
+    fn handle_global_search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Enter => {
+                let results = self.global_search_results();
+                let selected = self.global_search_state.selected().and_then(|i| results.get(i));
+                if let Some(&(list, index)) = selected {
+                    self.open_search_result(list, index);
+                    self.current_screen = CurrentScreen::Main;
+                }
+            }
+            KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.search_fuzzy = !self.search_fuzzy;
+                self.global_search_state.select(Some(0));
+            }
+            KeyCode::Up => {
+                self.global_search_state.select_previous();
+            }
+            KeyCode::Down => {
+                self.global_search_state.select_next();
+            }
+            _ => {
+                self.global_search_input.handle_event(&Event::Key(key));
+                self.global_search_state.select(Some(0));
+            }
+        }
+    }
+
+    // Small changes happen right away, larger ones ask first
+    fn start_bulk(&mut self, done: bool) {
+        self.bulk_done = done;
+        if self.bulk_affected(done) > 5 {
+            self.current_screen = CurrentScreen::ConfirmBulk;
+        } else {
+            self.run_bulk();
+        }
+    }
+
+    fn run_bulk(&mut self) {
+        if self.bulk_done {
+            self.mark_all_done();
+        } else {
+            self.mark_all_undone();
+        }
+        self.update_streak();
+    }
+
+    fn handle_confirm_bulk_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') => {
+                self.run_bulk();
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_weekly_review_key(&mut self, key: KeyEvent) {
+        if self.review_editing_due {
+            match key.code {
+                KeyCode::Esc => self.review_editing_due = false,
+                KeyCode::Enter => {
+                    let text = self.review_due_input.value().trim().to_owned();
+                    let due = match text.as_str() {
+                        "" => Ok(None),
+                        text => NaiveDate::parse_from_str(text, &self.config.date_format).map(Some),
+                    };
+                    match (due, self.review_selected()) {
+                        (Ok(due), Some((list, index))) => {
+                            self.reschedule(list, index, due);
+                            self.review_editing_due = false;
+                        }
+                        (Err(_), _) => {
+                            self.flash(fill(&self.strings.invalid_date, &[("format", &self.config.date_format)]));
+                        }
+                        _ => self.review_editing_due = false,
+                    }
+                }
+                _ => {
+                    self.review_due_input.handle_event(&Event::Key(key));
+                }
+            }
+            return;
+        }
+        match key.code {
+            KeyCode::Esc => self.current_screen = CurrentScreen::Main,
+            KeyCode::Right => self.review_move(true),
+            KeyCode::Left => self.review_move(false),
+            KeyCode::Up => self.review_state.select_previous(),
+            KeyCode::Down => self.review_state.select_next(),
+            KeyCode::Char('d') => self.finish_review_step(),
+            KeyCode::Enter if self.review_step() == ReviewStep::Overdue => {
+                let due = self.review_selected().and_then(|(l, i)| self.lists[l].get(i)?.due_date);
+                if let Some(due) = due {
+                    self.review_due_input = due.format(&self.config.date_format).to_string().into();
+                    self.review_editing_due = true;
+                }
+            }
+            KeyCode::Char(' ') if self.review_step() == ReviewStep::Inbox => {
+                if let Some((list, index)) = self.review_selected() {
+                    self.checkpoint("Toggle done");
+                    if let Some(item) = self.lists[list].get_mut(index) {
+                        item.set_done(true);
+                    }
+                }
+            }
+            KeyCode::Enter if self.review_step() == ReviewStep::Inbox => {
+                // File the item into the first regular list
+                let target = (0..self.lists.len()).find(|&l| self.lists[l].name != INBOX_LIST);
+                if let (Some((list, index)), Some(target)) = (self.review_selected(), target) {
+                    self.checkpoint("Move to list");
+                    self.move_to_list(list, index, target);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_daily_prompt_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.finish_daily_review(),
+            KeyCode::Enter => {
+                let description = self.daily_input.value_and_reset();
+                let builder = TodoItem::builder().description(&description).tags(vec!["today".to_owned()]);
+                match builder.build() {
+                    Ok(todo_item) => {
+                        self.checkpoint("Add");
+                        self.add_item(todo_item);
+                        self.daily_count += 1;
+                        if self.daily_count == 3 {
+                            self.finish_daily_review();
+                        }
+                    }
+                    // Enter on an empty line is done early
+                    Err(_) => self.finish_daily_review(),
+                }
+            }
+            _ => {
+                self.daily_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_timeline_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.current_screen = CurrentScreen::Main,
+            KeyCode::Up => self.timeline_state.select_previous(),
+            KeyCode::Down => self.timeline_state.select_next(),
+            // A week at a time
+            KeyCode::Left => self.timeline_start -= Duration::days(7),
+            KeyCode::Right => self.timeline_start += Duration::days(7),
+            KeyCode::Enter => {
+                let row = self.timeline_state.selected();
+                if let Some(index) = row.and_then(|row| self.timeline_items().get(row).copied()) {
+                    self.edit_item(index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_stale_review_key(&mut self, key: KeyEvent) {
+        let selected = self.stale_selected();
+        match (key.code, selected) {
+            (KeyCode::Esc, _) => self.current_screen = CurrentScreen::Main,
+            (KeyCode::Up, _) => self.stale_state.select_previous(),
+            (KeyCode::Down, _) => self.stale_state.select_next(),
+            (KeyCode::Char('s'), Some((list, index))) => self.snooze_stale(list, index),
+            (KeyCode::Char('d'), Some((list, index))) => self.delete_stale(list, index),
+            (KeyCode::Enter, Some((list, index))) => self.dismiss_stale(list, index),
+            _ => return,
+        }
+        if self.current_screen == CurrentScreen::StaleItemsReview {
+            self.after_stale_action();
+        }
+    }
+
+    fn handle_recently_done_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Up => {
+                self.recent_state.select_previous();
+            }
+            KeyCode::Down => {
+                self.recent_state.select_next();
+            }
+            KeyCode::Char('u') => {
+                let recent = self.recently_done();
+                if let Some(&index) = self.recent_state.selected().and_then(|row| recent.get(row)) {
+                    self.checkpoint("Mark not done");
+                    if let Some(item) = self.get_mut(index) {
+                        item.set_done(false);
+                    }
+                    if recent.len() == 1 {
+                        self.recent_state.select(None);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_confirm_delete_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') if !self.marked.is_empty() => {
+                let removed = self.delete_marked();
+                self.flash(fill(&self.strings.deleted_marked, &[("count", &removed)]));
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Char('y') => {
+                if let Some(index) = self.selected_item_index() {
+                    self.checkpoint("Delete");
+                    self.remove_at(index);
+                    // The one above takes the selection, the first item stays selected
+                    let row = self.todo_list_state.selected().map(|row| row.saturating_sub(1));
+                    self.todo_list_state.select(row);
+                    self.normalize_selection();
+                }
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_confirm_clear_done_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') => {
+                let removed = self.remove_all_done();
+                self.flash(fill(&self.strings.cleared_items, &[("count", &removed)]));
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Char('n') | KeyCode::Char('c') | KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_stats_key(&mut self, key: KeyEvent) {
+        if let KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('S') = key.code {
+            self.current_screen = CurrentScreen::Main;
+        }
+    }
+
+    fn handle_focus_key(&mut self, key: KeyEvent) {
+        if self.qr_code.take().is_some() {
+            return;
+        }
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Char(' ') => {
+                self.toggle_selected();
+            }
+            KeyCode::Char('e') => {
+                self.edit_selected();
+            }
+            KeyCode::Char('n') => {
+                self.edit_selected_notes();
+            }
+            KeyCode::Char('c') => {
+                self.comment_input.reset();
+                self.current_screen = CurrentScreen::AddComment;
+            }
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.delete_selected_comment();
+            }
+            KeyCode::Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                // The description for a phone to scan, with the link when it isn't part of it
+                let Some(item) = self.selected_item_index().and_then(|i| self.get(i)) else {
+                    return;
+                };
+                let mut text = item.description.clone();
+                if let Some(url) = item.first_url().filter(|url| !item.description.contains(url)) {
+                    text = format!("{}\n{}", text, url);
+                }
+                match QrCode::encode(text.as_bytes()) {
+                    Some(qr) => self.qr_code = Some(qr),
+                    None => self.flash(self.strings.too_long_for_qr.clone()),
+                }
+            }
+            KeyCode::Char('l') => {
+                self.link_input.reset();
+                self.link_state.select(Some(0));
+                self.current_screen = CurrentScreen::LinkSearch;
+            }
+            KeyCode::Enter => {
+                // Jump to the selected related item, its rows follow the comments
+                let Some(item) = self.selected_item_index().and_then(|i| self.get(i)) else {
+                    return;
+                };
+                let row = self.comment_state.selected().and_then(|row| row.checked_sub(item.comments.len()));
+                let target = row.and_then(|row| self.resolve_links(item.id).get(row).map(|link| link.id));
+                if let Some((list, index)) = target.and_then(|id| self.find_item(id)) {
+                    self.open_search_result(list, index);
+                    self.comment_state.select(None);
+                }
+            }
+            KeyCode::Up => {
+                self.comment_state.select_previous();
+            }
+            KeyCode::Down => {
+                let rows = self.selected_item_index().and_then(|i| self.get(i)).map_or(0, |item| {
+                    item.comments.len() + self.resolve_links(item.id).len()
+                });
+                let next = self.comment_state.selected().map_or(0, |row| row + 1);
+                if next < rows {
+                    self.comment_state.select(Some(next));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_item_detail_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter => self.current_screen = CurrentScreen::Main,
+            KeyCode::Char('e') => self.edit_selected(),
+            KeyCode::Char('n') => self.edit_selected_notes(),
+            _ => {}
+        }
+    }
+
+    fn copy_selected(&mut self) {
+        let Some(item) = self.selected_item_index().and_then(|i| self.get(i)) else {
+            return;
+        };
+        self.clipboard_pending = Some(item.description.clone());
+        self.flash(fill(&self.strings.copied, &[("count", &1)]));
+    }
+
+    fn copy_selected_share(&mut self) {
+        let Some(item) = self.selected_item_index().and_then(|i| self.get(i)) else {
+            return;
+        };
+        self.clipboard_pending = Some(item.share_text());
+        self.flash(self.strings.copied_share.clone());
+    }
+
+    fn open_parent_picker(&mut self) {
+        if self.selected_item_index().is_some() {
+            self.parent_input.reset();
+            self.parent_state.select(Some(0));
+            self.current_screen = CurrentScreen::ParentPicker;
+        }
+    }
+
+    fn handle_parent_picker_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Enter => {
+                // Row 0 is "None", the candidates follow
+                let candidates = self.parent_candidates();
+                let choice = match self.parent_state.selected() {
+                    Some(0) => Some(None),
+                    Some(row) => candidates.get(row - 1).and_then(|&i| self.get(i)).map(|item| Some(item.id)),
+                    None => None,
+                };
+                if let (Some(child), Some(parent)) = (self.selected_item_index(), choice) {
+                    self.reparent(child, parent);
+                }
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Up => {
+                self.parent_state.select_previous();
+            }
+            KeyCode::Down => {
+                self.parent_state.select_next();
+            }
+            _ => {
+                self.parent_input.handle_event(&Event::Key(key));
+                self.parent_state.select(Some(0));
+            }
+        }
+    }
+
+    fn handle_link_search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Focus;
+            }
+            KeyCode::Enter => {
+                let candidates = self.link_candidates();
+                let target = self.link_state.selected().and_then(|row| candidates.get(row).copied());
+                let target = target.and_then(|(list, index)| self.lists[list].get(index)).map(|item| item.id);
+                let focused = self.selected_item_index().and_then(|i| self.get(i)).map(|item| item.id);
+                if let (Some(focused), Some(target)) = (focused, target) {
+                    self.toggle_link(focused, target);
+                }
+                self.current_screen = CurrentScreen::Focus;
+            }
+            KeyCode::Up => {
+                self.link_state.select_previous();
+            }
+            KeyCode::Down => {
+                self.link_state.select_next();
+            }
+            _ => {
+                self.link_input.handle_event(&Event::Key(key));
+                self.link_state.select(Some(0));
+            }
+        }
+    }
+
+    fn handle_add_comment_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Focus;
+            }
+            KeyCode::Enter => {
+                let text = self.comment_input.value_and_reset();
+                if let Some(sel_index) = self.selected_item_index() {
+                    if !text.trim().is_empty() {
+                        self.checkpoint("Add comment");
+                        if let Some(item) = self.get_mut(sel_index) {
+                            item.comments.push((Local::now(), text));
+                        }
+                    }
+                }
+                self.current_screen = CurrentScreen::Focus;
+            }
+            _ => {
+                self.comment_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn delete_selected_comment(&mut self) {
+        let (Some(sel_index), Some(comment)) = (self.selected_item_index(), self.comment_state.selected())
+        else {
+            return;
+        };
+        let count = self.get(sel_index).map_or(0, |item| item.comments.len());
+        if comment < count {
+            self.checkpoint("Delete comment");
+            if let Some(item) = self.get_mut(sel_index) {
+                item.comments.remove(comment);
+            }
+            self.comment_state.select(if count > 1 { Some(comment.min(count - 2)) } else { None });
+        }
+    }
+
+    fn open_selected_url(&mut self) {
+        let Some(url) = self
+            .selected_item_index()
+            .and_then(|i| self.get(i))
+            .and_then(|item| item.first_url())
+            .map(str::to_owned)
+        else {
+            return;
+        };
+        if !self.config.open_urls {
+            self.flash(self.strings.open_urls_disabled.clone());
+            return;
+        }
+        match open::that(&url) {
+            Ok(()) => self.flash(self.strings.opening_in_browser.clone()),
+            Err(e) => {
+                let args: [(&str, &dyn Display); 2] = [("url", &url), ("error", &e)];
+                self.flash(fill(&self.strings.open_failed, &args));
+            }
+        }
+    }
+
+    fn edit_selected_notes(&mut self) {
+        if let Some(sel_index) = self.selected_item_index() {
+            self.open_notes(sel_index);
+            self.note_return_screen = self.current_screen;
+            self.current_screen = CurrentScreen::NoteEditor;
+        }
+    }
+
+    fn handle_note_editor_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                if self.notes_changed() {
+                    self.current_screen = CurrentScreen::ConfirmDiscardNote;
+                } else {
+                    self.current_screen = self.note_return_screen;
+                }
+            }
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.save_notes();
+                self.current_screen = self.note_return_screen;
+            }
+            _ => {
+                self.note_editor.input(key);
+            }
+        }
+    }
+
+    fn handle_confirm_discard_note_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') => {
+                self.current_screen = self.note_return_screen;
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.current_screen = CurrentScreen::NoteEditor;
+            }
+            _ => {}
+        }
+    }
+
+    // Esc with a typed description in Add or Edit
+    fn handle_confirm_discard_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') => {
+                self.reset_add_fields();
+                self.input_error = None;
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.current_screen = self.discard_return_screen;
+            }
+            _ => {}
+        }
+    }
+
+    fn edit_selected(&mut self) {
+        if let Some(sel_index) = self.selected_item_index() {
+            self.edit_item(sel_index);
+        }
+    }
+
+    fn edit_item(&mut self, index: usize) {
+        if let Some(e) = self.get(index).cloned() {
+            self.input = e.description.clone().into();
+            self.reminder_input = e
+                .reminder_at
+                .map(|at| at.format(&self.config.datetime_format).to_string())
+                .unwrap_or_default()
+                .into();
+            self.edit_due_input = e
+                .due_date
+                .map(|due| due.format(&self.config.date_format).to_string())
+                .unwrap_or_default()
+                .into();
+            self.set_add_field(AddField::Description);
+            self.currently_editing = Some(e);
+            self.edit_index = index;
+            self.current_screen = CurrentScreen::Edit;
+        }
+    }
+
+    fn toggle_selected(&mut self) {
+        if let Some(sel_index) = self.selected_item_index() {
+            self.checkpoint("Toggle done");
+            if let Some(item) = self.get_mut(sel_index) {
+                item.set_done(!item.done);
+            }
+            if let Some(item) = self.get(sel_index).filter(|item| item.done) {
+                self.notify(WebhookEvent::ItemCompleted, item);
+            }
+            self.update_streak();
+        }
+    }
+
+    fn handle_edit_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                let original = self.currently_editing.as_ref().map(|e| e.description.as_str());
+                if !self.input.value().is_empty() && original != Some(self.input.value()) {
+                    self.discard_return_screen = CurrentScreen::Edit;
+                    self.current_screen = CurrentScreen::ConfirmDiscard;
+                } else {
+                    self.input_error = None;
+                    self.current_screen = CurrentScreen::Main;
+                }
+            }
+            KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                // Color label, offer to clear an existing one first
+                let current = self.currently_editing.as_ref().and_then(|e| e.color_label);
+                if current.is_some() {
+                    self.current_screen = CurrentScreen::ConfirmClearColor;
+                } else {
+                    self.color_picker_index = 0;
+                    self.current_screen = CurrentScreen::ColorPicker;
+                }
+            }
+            KeyCode::Enter => {
+                let today = Local::now().date_naive();
+                let edited = parse_due(self.edit_due_input.value(), today, &self.config)
+                    .and_then(|due| Ok((due, self.reminder_from_input()?)))
+                    .and_then(|(due, reminder)| {
+                        TodoItemBuilder::from(self.currently_editing.clone().unwrap_or_default())
+                            .description(self.input.value())
+                            .due(due)
+                            .reminder(reminder)
+                            .build()
+                    });
+                match edited {
+                    Err(e) => self.input_error = Some(e.to_string()),
+                    Ok(item) => {
+                        if self.currently_editing.take().is_some() {
+                            self.checkpoint("Edit item");
+                            self.input.reset();
+                            self.check_spelling(&item);
+                            self.replace(item, self.edit_index);
+                        }
+                        self.reminder_input.reset();
+                        self.input_error = None;
+                        self.current_screen = CurrentScreen::Main;
+                    }
+                }
+            }
+            KeyCode::Tab => self.set_add_field(match self.add_active_field {
+                AddField::Description => AddField::Due,
+                AddField::Due => AddField::Reminder,
+                _ => AddField::Description,
+            }),
+            _ => {
+                self.active_input().handle_event(&Event::Key(key));
+                if self.add_active_field == AddField::Description && key.code == KeyCode::Char(' ') {
+                    expand_abbreviation(&mut self.input, &self.config.abbreviations);
+                }
+            }
+        }
+    }
+
+    // Input of the Add/Edit screens that has the focus
+    fn active_input(&mut self) -> &mut Input {
+        if self.current_screen == CurrentScreen::Add {
+            let [due, priority, tags] = &mut self.add_fields;
+            return match self.add_active_field {
+                AddField::Description => &mut self.input,
+                AddField::Due => due,
+                AddField::Priority => priority,
+                AddField::Tags => tags,
+                AddField::Reminder => &mut self.reminder_input,
+            };
+        }
+        match self.add_active_field {
+            AddField::Due => &mut self.edit_due_input,
+            AddField::Reminder => &mut self.reminder_input,
+            _ => &mut self.input,
+        }
+    }
+
+    // Bracketed paste, text pasted into the terminal arrives in one piece
+    pub fn handle_paste(&mut self, text: &str) {
+        if !self.current_screen.is_text_input() {
+            return;
+        }
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let lines: Vec<String> =
+            text.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_owned).collect();
+        let description = self.current_screen == CurrentScreen::Add && self.add_active_field == AddField::Description;
+        if description && lines.len() > 1 {
+            self.pasted_lines = lines;
+            self.current_screen = CurrentScreen::PasteMultiple;
+            return;
+        }
+        // Anywhere else it is taken as typed, line breaks become spaces outside the note editor
+        for c in text.trim_end_matches('\n').chars() {
+            let code = match c {
+                '\n' if self.current_screen == CurrentScreen::NoteEditor => KeyCode::Enter,
+                '\n' => KeyCode::Char(' '),
+                c => KeyCode::Char(c),
+            };
+            self.handle_key_event(KeyEvent::from(code));
+        }
+    }
+
+    fn handle_paste_multiple_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') => {
+                let lines = std::mem::take(&mut self.pasted_lines);
+                match self.add_pasted_items(&lines) {
+                    Ok(()) => {
+                        self.flash(fill(&self.strings.pasted_items, &[("count", &lines.len())]));
+                        self.current_screen = CurrentScreen::Main;
+                    }
+                    Err(e) => {
+                        self.input_error = Some(e.to_string());
+                        self.current_screen = CurrentScreen::Add;
+                    }
+                }
+            }
+            KeyCode::Char('n') => {
+                let joined = std::mem::take(&mut self.pasted_lines).join(" ");
+                for c in joined.chars() {
+                    self.input.handle_event(&Event::Key(KeyEvent::from(KeyCode::Char(c))));
+                }
+                self.current_screen = CurrentScreen::Add;
+            }
+            KeyCode::Esc => {
+                self.pasted_lines.clear();
+                self.current_screen = CurrentScreen::Add;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_add_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                if self.input.value().is_empty() {
+                    self.input_error = None;
+                    self.current_screen = CurrentScreen::Main;
+                } else {
+                    self.discard_return_screen = CurrentScreen::Add;
+                    self.current_screen = CurrentScreen::ConfirmDiscard;
+                }
+            }
+            KeyCode::Enter => {
+                self.duplicate_of = self.similar_item(self.input.value());
+                if self.duplicate_of.is_some() {
+                    self.current_screen = CurrentScreen::DuplicateWarning;
+                } else {
+                    self.confirm_add();
+                }
+            }
+            KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                // Pick a template to pre-fill the new item
+                if !self.config.templates.is_empty() {
+                    self.template_state.select(Some(0));
+                    self.current_screen = CurrentScreen::TemplatePicker;
+                }
+            }
+            KeyCode::Tab => self.set_add_field(self.add_active_field.next()),
+            KeyCode::BackTab => self.set_add_field(self.add_active_field.previous()),
+            _ => {
+                self.active_input().handle_event(&Event::Key(key));
+                if self.add_active_field == AddField::Description {
+                    if key.code == KeyCode::Char(' ') {
+                        expand_abbreviation(&mut self.input, &self.config.abbreviations);
+                    }
+                    self.capitalize_first_letter();
+                }
+            }
+        }
+    }
+
+    // Typing the first letter of a new item in lowercase uppercases it
+    fn capitalize_first_letter(&mut self) {
+        let value = self.input.value();
+        if self.config.auto_capitalize
+            && self.input.cursor() == 1
+            && value.starts_with(|c: char| c.is_ascii_lowercase())
+        {
+            let value = value[..1].to_ascii_uppercase() + &value[1..];
+            self.input = Input::new(value).with_cursor(1);
+        }
+    }
+
+    fn confirm_add(&mut self) {
+        match self.take_add_draft() {
+            Ok(todo_item) => {
+                self.checkpoint("Add item");
+                self.check_spelling(&todo_item);
+                self.add_item(todo_item);
+                self.input_error = None;
+                self.current_screen = CurrentScreen::Main;
+            }
+            Err(e) => {
+                self.input_error = Some(e.to_string());
+                self.current_screen = CurrentScreen::Add;
+            }
+        }
+    }
+
+    fn handle_duplicate_warning_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') => {
+                self.duplicate_of = None;
+                self.confirm_add();
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.duplicate_of = None;
+                self.current_screen = CurrentScreen::Add;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_spell_check_key(&mut self, key: KeyEvent) {
+        let more = match key.code {
+            KeyCode::Enter => self.accept_suggestion(),
+            KeyCode::Char('s') | KeyCode::Tab => self.skip_misspelled(),
+            KeyCode::Up => {
+                self.spell_state.select_previous();
+                true
+            }
+            KeyCode::Down => {
+                self.spell_state.select_next();
+                true
+            }
+            KeyCode::Esc => false,
+            _ => true,
+        };
+        if !more {
+            self.spell_queue.clear();
+            self.current_screen = CurrentScreen::Main;
+        }
+    }
+
+    fn handle_inbox_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.inbox_input.reset();
+                self.current_screen = self.inbox_return_screen;
+            }
+            KeyCode::Enter => {
+                let description = self.inbox_input.value_and_reset();
+                if let Ok(todo_item) = TodoItem::builder().description(&description).build() {
+                    self.checkpoint("Capture to Inbox");
+                    self.add_to_inbox(todo_item);
+                }
+                self.current_screen = self.inbox_return_screen;
+            }
+            _ => {
+                self.inbox_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_template_picker_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Add;
+            }
+            KeyCode::Enter => {
+                if let Some(template) = self
+                    .template_state
+                    .selected()
+                    .and_then(|i| self.config.templates.get(i))
+                    .cloned()
+                {
+                    self.apply_template(&template);
+                }
+                self.current_screen = CurrentScreen::Add;
+            }
+            KeyCode::Up => {
+                self.template_state.select_previous();
+            }
+            KeyCode::Down => {
+                self.template_state.select_next();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_command_palette_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Enter => {
+                let commands = palette::matching(self.palette_input.value());
+                self.current_screen = CurrentScreen::Main;
+                if let Some(&command) = self.palette_state.selected().and_then(|i| commands.get(i)) {
+                    self.run_command(command);
+                }
+            }
+            KeyCode::Up => {
+                self.palette_state.select_previous();
+            }
+            KeyCode::Down => {
+                self.palette_state.select_next();
+            }
+            _ => {
+                self.palette_input.handle_event(&Event::Key(key));
+                self.palette_state.select(Some(0));
+            }
+        }
+    }
+
+    fn handle_confirm_dedup_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') => {
+                self.remove_duplicates();
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_search_replace_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Enter => {
+                let find = self.find_input.value().to_owned();
+                let replace = self.replace_input.value().to_owned();
+                let count = self.search_replace(&find, &replace, self.search_case_sensitive);
+                self.flash(fill(&self.strings.replaced_occurrences, &[("count", &count)]));
+                self.current_screen = CurrentScreen::Main;
+            }
+            // Ctrl+I arrives as Tab in most terminals
+            KeyCode::Char('i') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.search_case_sensitive = !self.search_case_sensitive;
+            }
+            KeyCode::Tab => {
+                self.search_case_sensitive = !self.search_case_sensitive;
+            }
+            KeyCode::Up | KeyCode::Down => {
+                self.replace_field_active = !self.replace_field_active;
+            }
+            _ => {
+                let input = if self.replace_field_active {
+                    &mut self.replace_input
+                } else {
+                    &mut self.find_input
+                };
+                input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_color_picker_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Edit;
+            }
+            KeyCode::Enter => {
+                if let Some(e) = &mut self.currently_editing {
+                    e.color_label = Some(LABEL_COLORS[self.color_picker_index]);
+                }
+                self.current_screen = CurrentScreen::Edit;
+            }
+            KeyCode::Left | KeyCode::Up => {
+                self.color_picker_index =
+                    (self.color_picker_index + LABEL_COLORS.len() - 1) % LABEL_COLORS.len();
+            }
+            KeyCode::Right | KeyCode::Down => {
+                self.color_picker_index = (self.color_picker_index + 1) % LABEL_COLORS.len();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_confirm_clear_color_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') => {
+                if let Some(e) = &mut self.currently_editing {
+                    e.color_label = None;
+                }
+                self.current_screen = CurrentScreen::Edit;
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Edit;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn run_command(&mut self, command: Command) {
+        match command {
+            Command::RemoveDuplicates => self.remove_duplicates(),
+            Command::ArchiveCompleted => {
+                let count = self.archive_completed();
+                if count > 0 {
+                    self.flash(fill(&self.strings.archived_items, &[("count", &count)]));
+                } else {
+                    self.flash(self.strings.no_completed_items.clone());
+                }
+            }
+            Command::Archive => self.open_archive(),
+            Command::SearchReplace => {
+                self.find_input.reset();
+                self.replace_input.reset();
+                self.replace_field_active = false;
+                self.current_screen = CurrentScreen::SearchReplace;
+            }
+            Command::SplitList => {
+                if self.filter_state().is_empty() {
+                    self.flash(self.strings.split_needs_filter.clone());
+                } else {
+                    self.split_name_input.reset();
+                    self.current_screen = CurrentScreen::SplitListName;
+                }
+            }
+            Command::WeeklyReview => self.start_weekly_review(),
+            Command::StaleItems => {
+                if self.stale_items().is_empty() {
+                    let days = self.config.stale_item_days;
+                    self.flash(fill(&self.strings.no_stale_items, &[("days", &days)]));
+                } else {
+                    self.start_stale_review();
+                }
+            }
+            Command::MoveToParent => self.open_parent_picker(),
+            Command::Pomodoro => self.toggle_pomodoro(),
+            Command::CopyShareLink => self.copy_selected_share(),
+            Command::EditConfig => self.edit_config_pending = true,
+            Command::UndoHistory => self.open_undo_history(),
+            Command::ChangeTheme => {
+                self.theme_preview = THEMES.iter().position(|theme| theme.name == self.config.theme.name).unwrap_or(0);
+                self.current_screen = CurrentScreen::ThemePreview;
+            }
+            Command::SaveFilter => {
+                self.filter_name_input = Input::new(self.active_filter.clone().unwrap_or_default());
+                self.current_screen = CurrentScreen::SaveFilterName;
+            }
+            Command::LoadFilter => {
+                if self.config.saved_filters.is_empty() {
+                    self.flash(self.strings.no_saved_filters.clone());
+                } else {
+                    self.saved_filter_state.select(Some(0));
+                    self.current_screen = CurrentScreen::LoadFilter;
+                }
+            }
+            Command::Import => {
+                self.import_input.reset();
+                self.current_screen = CurrentScreen::Import;
+            }
+            Command::GithubImport => {
+                self.github_input.reset();
+                self.current_screen = CurrentScreen::GithubImport;
+            }
+            Command::MergeLists => {
+                if self.lists.len() < 2 {
+                    self.flash(self.strings.no_list_to_merge_into.clone());
+                } else {
+                    self.merge_state.select(Some(0));
+                    self.current_screen = CurrentScreen::MergeListPicker;
+                }
+            }
+        }
+    }
+
+    fn handle_save_filter_name_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Enter => {
+                let name = self.filter_name_input.value().trim().to_owned();
+                match self.save_filter(&name) {
+                    Ok(()) => {
+                        self.flash(fill(&self.strings.filter_saved, &[("name", &name)]));
+                        self.active_filter = Some(name);
+                        self.current_screen = CurrentScreen::Main;
+                    }
+                    Err(e) => self.flash(e.to_string()),
+                }
+            }
+            _ => {
+                self.filter_name_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_move_to_position_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.input_error = None;
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Enter => {
+                let len = self.items().len();
+                match self.position_input.value().trim().parse::<usize>() {
+                    Ok(position @ 1..) if position <= len => {
+                        if let Some(index) = self.selected_item_index() {
+                            self.move_item_to_position(index, position - 1);
+                        }
+                        self.input_error = None;
+                        self.current_screen = CurrentScreen::Main;
+                    }
+                    _ => self.input_error = Some(fill(&self.strings.invalid_position, &[("count", &len)])),
+                }
+            }
+            KeyCode::Char(c) if !c.is_ascii_digit() => {}
+            _ => {
+                self.position_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_load_filter_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Enter => {
+                let selected = self.saved_filter_state.selected().unwrap_or(0);
+                if let Some((name, filter)) = self.config.saved_filters.iter().nth(selected) {
+                    let (name, filter) = (name.clone(), filter.clone());
+                    self.apply_filter(&filter);
+                    self.active_filter = Some(name);
+                }
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Up => {
+                self.saved_filter_state.select_previous();
+            }
+            KeyCode::Down => {
+                self.saved_filter_state.select_next();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_split_list_name_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Enter => match self.validate_list_name(self.split_name_input.value()) {
+                Ok(()) => self.current_screen = CurrentScreen::ConfirmSplit,
+                Err(e) => self.flash(e.to_string()),
+            },
+            _ => {
+                self.split_name_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_undo_history_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Up => {
+                self.undo_history_state.select_previous();
+            }
+            KeyCode::Down => {
+                self.undo_history_state.select_next();
+            }
+            KeyCode::Enter => {
+                let len = self.undo_stack.entries().len();
+                if let Some(row) = self.undo_history_state.selected().filter(|&row| row < len) {
+                    self.undo_to(len - 1 - row);
+                }
+                self.current_screen = CurrentScreen::Main;
+            }
+            _ => {}
+        }
+    }
+
+    fn open_archive(&mut self) {
+        self.archive_state.select((!self.archived.is_empty()).then_some(0));
+        self.current_screen = CurrentScreen::Archive;
+    }
+
+    fn handle_archive_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Up => {
+                self.archive_state.select_previous();
+            }
+            KeyCode::Down => {
+                self.archive_state.select_next();
+            }
+            KeyCode::Char('r') => {
+                let Some(description) = self.archive_state.selected().and_then(|row| self.restore_archived(row)) else {
+                    return;
+                };
+                if self.archived.is_empty() {
+                    self.archive_state.select(None);
+                } else if self.archive_state.selected() >= Some(self.archived.len()) {
+                    self.archive_state.select(Some(self.archived.len() - 1));
+                }
+                self.flash(fill(&self.strings.restored_item, &[("description", &description)]));
+            }
+            _ => {}
+        }
+    }
+
+    // Scrolling past the end is undone when the help is drawn
+    fn handle_help_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q' | '?') => self.current_screen = self.help_return_screen,
+            KeyCode::Up | KeyCode::Char('k') => self.help_scroll = self.help_scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.help_scroll = self.help_scroll.saturating_add(1),
+            KeyCode::PageUp => self.help_scroll = self.help_scroll.saturating_sub(10),
+            KeyCode::PageDown => self.help_scroll = self.help_scroll.saturating_add(10),
+            KeyCode::Home => self.help_scroll = 0,
+            KeyCode::End => self.help_scroll = u16::MAX,
+            _ => {}
+        }
+    }
+
+    fn handle_sort_menu_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Up => {
+                self.sort_menu_state.select_previous();
+            }
+            KeyCode::Down => {
+                self.sort_menu_state.select_next();
+            }
+            KeyCode::Enter => {
+                if let Some(&order) = self.sort_menu_state.selected().and_then(|row| SortOrder::ALL.get(row)) {
+                    self.set_sort_order(order);
+                }
+                self.current_screen = CurrentScreen::Main;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_tag_filter_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Up => {
+                self.tag_filter_state.select_previous();
+            }
+            KeyCode::Down => {
+                self.tag_filter_state.select_next();
+            }
+            KeyCode::Enter => {
+                let tags = self.unfiltered_tags();
+                if let Some(tag) = self.tag_filter_state.selected().and_then(|row| tags.get(row)) {
+                    self.add_tag_filter(tag.clone());
+                }
+                self.current_screen = CurrentScreen::Main;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_theme_preview_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Right => {
+                self.theme_preview = (self.theme_preview + 1) % THEMES.len();
+            }
+            KeyCode::Left => {
+                self.theme_preview = (self.theme_preview + THEMES.len() - 1) % THEMES.len();
+            }
+            KeyCode::Enter => {
+                let name = THEMES[self.theme_preview].name;
+                self.config.theme = self.config.theme.with_base(&THEMES[self.theme_preview]);
+                match self.save_config() {
+                    Ok(()) => self.flash(fill(&self.strings.theme_saved, &[("name", &name)])),
+                    Err(e) => self.flash(fill(&self.strings.config_save_failed, &[("error", &e)])),
+                }
+                self.current_screen = CurrentScreen::Main;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_batch_tag_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Enter => {
+                let tag = self.tag_input.value_and_reset();
+                let tag = tag.trim();
+                if !tag.is_empty() {
+                    match self.tag_item {
+                        Some(index) => self.add_tag_to_items(tag, &[index]),
+                        None => {
+                            let indices = self.visible_indices();
+                            self.add_tag_to_items(tag, &indices);
+                            self.flash(fill(&self.strings.tagged_items, &[("count", &indices.len())]));
+                        }
+                    }
+                }
+                self.current_screen = CurrentScreen::Main;
+            }
+            _ => {
+                self.tag_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_github_import_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Enter => {
+                let repo = self.github_input.value().trim().to_owned();
+                match github::fetch_issues(&repo) {
+                    Ok(issues) if issues.is_empty() => {
+                        self.flash(fill(&self.strings.no_open_issues, &[("repo", &repo)]));
+                        self.current_screen = CurrentScreen::Main;
+                    }
+                    Ok(issues) => {
+                        self.github_issues = issues;
+                        self.current_screen = CurrentScreen::ConfirmGithubImport;
+                    }
+                    Err(e) => {
+                        self.flash(fill(&self.strings.github_import_failed, &[("error", &e)]));
+                    }
+                }
+            }
+            _ => {
+                self.github_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_import_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Enter => {
+                let path = self.import_input.value().trim().to_owned();
+                let duplicates_before = self.duplicate_count();
+                match self.import_file(&path) {
+                    Ok(count) => {
+                        self.flash(fill(&self.strings.imported_items, &[("count", &count)]));
+                        self.current_screen = if self.duplicate_count() > duplicates_before {
+                            CurrentScreen::ConfirmDedup
+                        } else {
+                            CurrentScreen::Main
+                        };
+                    }
+                    Err(e) => {
+                        let args: [(&str, &dyn Display); 2] = [("file", &path), ("error", &e)];
+                        self.flash(fill(&self.strings.import_failed, &args));
+                    }
+                }
+            }
+            _ => {
+                self.import_input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    fn handle_confirm_github_import_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') => {
+                let issues = std::mem::take(&mut self.github_issues);
+                let count = issues.len();
+                self.checkpoint("Import GitHub issues");
+                self.extend_items(issues);
+                self.flash(fill(&self.strings.imported_issues, &[("count", &count)]));
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.github_issues.clear();
+                self.current_screen = CurrentScreen::Main;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_confirm_split_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') => {
+                let filter = self.filter_state();
+                let name = self.split_name_input.value_and_reset();
+                self.checkpoint("Split list");
+                if let Err(e) = self.split_list_by_filter(&filter, &name) {
+                    self.flash(e.to_string());
+                }
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.current_screen = CurrentScreen::SplitListName;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_merge_list_picker_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Enter if self.merge_target().is_some() => {
+                self.current_screen = CurrentScreen::ConfirmMerge;
+            }
+            KeyCode::Up => {
+                self.merge_state.select_previous();
+            }
+            KeyCode::Down => {
+                self.merge_state.select_next();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_confirm_merge_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') => {
+                if let Some(target) = self.merge_target() {
+                    let name = self.lists[self.active_list].name.clone();
+                    self.checkpoint("Merge lists");
+                    self.merge_lists(self.active_list, target);
+                    let target = &self.lists[self.active_list].name;
+                    let args: [(&str, &dyn Display); 2] = [("source", &name), ("target", target)];
+                    self.flash(fill(&self.strings.merged_lists, &args));
+                }
+                self.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.current_screen = CurrentScreen::Main;
+            }
+            _ => {}
+        }
+    }
+
+    fn remove_duplicates(&mut self) {
+        let removed = self.deduplicate();
+        self.flash(fill(&self.strings.removed_duplicates, &[("count", &removed)]));
+    }
+}
+
+// Replace the word just finished by a space with its expansion, "mtg " becomes "Meeting "
+pub fn expand_abbreviation(input: &mut Input, abbrevs: &HashMap<String, String>) {
+    let chars: Vec<char> = input.value().chars().collect();
+    let cursor = input.cursor();
+    if cursor == 0 || chars.get(cursor - 1) != Some(&' ') {
+        return;
+    }
+    let start = chars[..cursor - 1]
+        .iter()
+        .rposition(|&c| c == ' ')
+        .map_or(0, |i| i + 1);
+    let word: String = chars[start..cursor - 1].iter().collect();
+    // Lowercase too, the first word may have been auto-capitalized
+    let expansion = abbrevs.get(&word).or_else(|| abbrevs.get(&word.to_lowercase()));
+    if let Some(expansion) = expansion {
+        let before: String = chars[..start].iter().collect();
+        let after: String = chars[cursor - 1..].iter().collect();
+        let new_cursor = cursor - word.chars().count() + expansion.chars().count();
+        *input = Input::new(before + expansion + &after).with_cursor(new_cursor);
+    }
+}
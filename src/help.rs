@@ -0,0 +1,94 @@
+use crate::config::{Config, KeySpec};
+use KeyDescription::{Fixed, Setting};
+
+// How a key is shown in the help overlay, the ones the config can change as they are set
+pub enum KeyDescription {
+    Fixed(&'static str),
+    Setting(fn(&Config) -> KeySpec),
+}
+
+impl KeyDescription {
+    pub fn text(&self, config: &Config) -> String {
+        match self {
+            KeyDescription::Fixed(key) => (*key).to_owned(),
+            KeyDescription::Setting(key) => key(config).to_string(),
+        }
+    }
+}
+
+pub type ActionDescription = &'static str;
+
+// Every key the help overlay lists, by the screen it works on
+pub const KEYS: &[(&str, &[(KeyDescription, ActionDescription)])] = &[
+    ("Everywhere but text inputs", &[
+        (Fixed("?"), "Show or close this help"),
+        (Fixed("i"), "Quick capture into the Inbox"),
+    ]),
+    ("Main list", &[
+        (Setting(|config| config.add_key), "Add an item, !high #tag @YYYY-MM-DD in it set its fields"),
+        (Fixed("Enter"), "Show every field of the selected item"),
+        (Setting(|config| config.toggle_done_key), "Mark the selected item done or undone"),
+        (Setting(|config| config.delete_key), "Delete the selected item, after confirming"),
+        (Setting(|config| config.search_key), "Search descriptions"),
+        (Fixed("Up/Down, k/j"), "Select an item, a count moves further, e.g. 5j"),
+        (Fixed("gg/G"), "Go to the first or last item, 3gg or 3G to the third"),
+        (Fixed("#"), "Move the selected item to a position"),
+        (Fixed("Alt+Up/Alt+Down"), "Move the selected item past its neighbour"),
+        (Fixed("Ctrl+Home/Ctrl+End"), "Move the selected item to the top or bottom"),
+        (Fixed("p"), "Cycle the priority of the selected item"),
+        (Fixed("+/-"), "Move the due date a day later or earlier"),
+        (Fixed("v"), "Mark the selected item for d, Space and p"),
+        (Fixed("s"), "Pick the order the list is shown in"),
+        (Fixed("T"), "Show only the items with a tag"),
+        (Fixed("c"), "Collapse or expand the subtasks of the selected item"),
+        (Fixed("Ctrl+M"), "Make the selected item a subtask of another"),
+        (Fixed("Ctrl+P"), "Promote the selected subtask to an item of its own"),
+        (Fixed("n"), "Edit the notes of the selected item"),
+        (Fixed("o"), "Open the first URL of the selected item"),
+        (Fixed("F"), "Focus on the selected item"),
+        (Fixed("t"), "Timeline of the items with a due date"),
+        (Fixed("L"), "Switch to the next list"),
+        (Fixed("x"), "Compact rows or a blank line after every item"),
+        (Fixed("w"), "Wrap long descriptions onto more rows"),
+        (Fixed("Ctrl+O"), "Number the items as an outline"),
+        (Fixed("Ctrl+D"), "Distraction-free mode"),
+        (Fixed("A/U"), "Mark all items done or undone"),
+        (Fixed("X"), "Remove all done items"),
+        (Fixed("Ctrl+A"), "Archived items, r restores one"),
+        (Fixed("S"), "Statistics and completion streaks"),
+        (Fixed("Ctrl+R"), "Items completed in the last 24 hours"),
+        (Fixed("Ctrl+S"), "Step through misspelled words"),
+        (Fixed("Ctrl+/"), "Search descriptions and notes in all lists"),
+        (Fixed(":"), "Command palette"),
+        (Fixed("Ctrl+C"), "Copy the description of the selected item"),
+        (Fixed("Ctrl+Shift+C"), "Copy the selected item as a share link"),
+        (Fixed("Ctrl+T"), "Tag every item shown by the search or filter"),
+        (Fixed("Ctrl+Z"), "Undo, h right after it lists the older steps"),
+        (Fixed("Ctrl+Y"), "Redo"),
+        (Fixed("Q, @q"), "Record a macro (Q, as q quits), replay it"),
+        (Fixed("Ctrl+E"), "Export, then w, t, m or c picks what"),
+        (Setting(|config| config.quit_key), "Quit"),
+    ]),
+    ("Item details", &[
+        (Fixed("e"), "Edit the item"),
+        (Fixed("n"), "Open its notes"),
+        (Fixed("Enter/Esc"), "Back to the list"),
+    ]),
+    ("Focus", &[
+        (Fixed("Space"), "Mark the item done or undone"),
+        (Fixed("e"), "Edit the item"),
+        (Fixed("n"), "Open its notes"),
+        (Fixed("c"), "Add a comment"),
+        (Fixed("Up/Down, Ctrl+D"), "Select a comment, delete it"),
+        (Fixed("l"), "Link the item to another one, or unlink it"),
+        (Fixed("Ctrl+Q"), "Show the description and link as a QR code"),
+        (Fixed("Esc"), "Back to the list"),
+    ]),
+    ("Add and Edit", &[
+        (Fixed("Enter"), "Save the item"),
+        (Fixed("Esc"), "Back to the list, asking before changes are lost"),
+        (Fixed("Tab/Shift+Tab"), "Move between the fields"),
+        (Fixed("Ctrl+T"), "Pick a template to pre-fill the new item (Add only)"),
+        (Fixed("Ctrl+L"), "Pick a color label (Edit only)"),
+    ]),
+];
@@ -0,0 +1,484 @@
+use crate::app::AppState;
+use crate::error::AppError;
+use crate::item::{Priority, TodoItem};
+use crate::storage;
+use chrono::{DateTime, NaiveDate};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum ImportFormat {
+    #[default]
+    Plain,
+    Json,
+    TodoTxt,
+    Csv,
+    GoogleTasks,
+    OrgMode,
+}
+
+impl FromStr for ImportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(ImportFormat::Plain),
+            "json" => Ok(ImportFormat::Json),
+            "todotxt" => Ok(ImportFormat::TodoTxt),
+            "csv" => Ok(ImportFormat::Csv),
+            "google-tasks" => Ok(ImportFormat::GoogleTasks),
+            "org" => Ok(ImportFormat::OrgMode),
+            _ => Err(format!(
+                "unknown format '{}', expected plain, json, todotxt, csv, google-tasks or org",
+                s
+            )),
+        }
+    }
+}
+
+impl ImportFormat {
+    // Guessed from the file name, a JSON object rather than an array is a Google Tasks export
+    pub fn for_file(path: &Path, text: &str) -> Self {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+        match path.extension().unwrap_or_default().to_string_lossy().to_lowercase().as_str() {
+            "csv" => ImportFormat::Csv,
+            "org" => ImportFormat::OrgMode,
+            "json" if text.trim_start().starts_with('{') => ImportFormat::GoogleTasks,
+            "json" => ImportFormat::Json,
+            _ if name == "todo.txt" => ImportFormat::TodoTxt,
+            _ => ImportFormat::Plain,
+        }
+    }
+}
+
+// Google Tasks exports: Takeout writes task lists under "items", the API a list's tasks
+#[derive(Deserialize)]
+struct GoogleExport {
+    #[serde(default)]
+    items: Vec<GoogleTaskList>,
+    #[serde(default)]
+    tasks: Vec<GoogleTask>,
+}
+
+#[derive(Deserialize)]
+struct GoogleTaskList {
+    #[serde(default, alias = "tasks")]
+    items: Vec<GoogleTask>,
+}
+
+#[derive(Deserialize)]
+struct GoogleTask {
+    id: Option<String>,
+    #[serde(default)]
+    title: String,
+    due: Option<String>,
+    status: Option<String>,
+    notes: Option<String>,
+    parent: Option<String>,
+}
+
+impl AppState {
+    pub fn import(s: &str, format: ImportFormat) -> Result<Vec<TodoItem>, AppError> {
+        match format {
+            ImportFormat::Plain => Ok(Self::import_plain(s)),
+            ImportFormat::Json => Self::import_json(s),
+            ImportFormat::TodoTxt => Ok(Self::import_todotxt(s)),
+            ImportFormat::Csv => Self::import_csv(s),
+            ImportFormat::GoogleTasks => Self::import_google_tasks(s),
+            ImportFormat::OrgMode => Self::import_orgmode(s),
+        }
+    }
+
+    // Subtasks keep their parent when it is in the same export, tasks without a title
+    // are skipped
+    pub fn import_google_tasks(json: &str) -> Result<Vec<TodoItem>, AppError> {
+        let export: GoogleExport = serde_json::from_str(json)?;
+        let tasks: Vec<GoogleTask> =
+            export.tasks.into_iter().chain(export.items.into_iter().flat_map(|list| list.items)).collect();
+        let mut ids = HashMap::new();
+        let mut items = vec![];
+        for task in &tasks {
+            let mut builder = TodoItem::builder()
+                .description(task.title.trim())
+                .done(task.status.as_deref() == Some("completed"));
+            if let Some(due) = &task.due {
+                let due = DateTime::parse_from_rfc3339(due)
+                    .map_err(|e| AppError::Parse(format!("invalid due date '{}': {}", due, e)))?;
+                builder = builder.due(due.date_naive());
+            }
+            let Ok(mut item) = builder.build() else {
+                continue;
+            };
+            if item.done {
+                item.completed_at = Some(item.created_at);
+            }
+            item.notes = task.notes.clone().filter(|notes| !notes.trim().is_empty());
+            if let Some(id) = &task.id {
+                ids.insert(id.as_str(), item.id);
+            }
+            items.push((item, task.parent.as_deref()));
+        }
+        Ok(items
+            .into_iter()
+            .map(|(item, parent)| TodoItem {
+                parent_id: parent.and_then(|parent| ids.get(parent).copied()),
+                ..item
+            })
+            .collect())
+    }
+
+    // Only TODO and DONE headlines become items, deeper headlines are subtasks of the
+    // nearest imported one above them. Body lines other than planning and drawers go
+    // into the notes
+    pub fn import_orgmode(s: &str) -> Result<Vec<TodoItem>, AppError> {
+        let mut items: Vec<TodoItem> = vec![];
+        // Open headlines by level, None for the ones that were skipped
+        let mut outline: Vec<(usize, Option<usize>)> = vec![];
+        let mut current = None;
+        let mut in_drawer = false;
+        for (line_no, line) in s.lines().enumerate() {
+            let level = line.chars().take_while(|&c| c == '*').count();
+            if level > 0 && line[level..].starts_with(' ') {
+                while outline.last().is_some_and(|&(l, _)| l >= level) {
+                    outline.pop();
+                }
+                in_drawer = false;
+                current = None;
+                if let Some(mut item) = parse_org_headline(&line[level..]) {
+                    if item.done {
+                        item.completed_at = Some(item.created_at);
+                    }
+                    item.parent_id = outline.iter().rev().find_map(|&(_, index)| index).map(|p| items[p].id);
+                    items.push(item);
+                    current = Some(items.len() - 1);
+                }
+                outline.push((level, current));
+                continue;
+            }
+            let Some(index) = current else {
+                continue;
+            };
+            let item = &mut items[index];
+            let trimmed = line.trim();
+            if let Some(tags) = trimmed.strip_prefix(":TAGS:") {
+                item.tags.extend(tags.split([' ', ':']).filter(|t| !t.is_empty()).map(str::to_owned));
+            } else if in_drawer {
+                in_drawer = !trimmed.eq_ignore_ascii_case(":END:");
+            } else if trimmed.len() > 1 && trimmed.starts_with(':') && trimmed.ends_with(':') {
+                // :PROPERTIES:, :LOGBOOK: and the like
+                in_drawer = true;
+            } else if ["DEADLINE:", "SCHEDULED:", "CLOSED:"].iter().any(|k| trimmed.starts_with(k)) {
+                let planning = |keyword: &str| -> Result<Option<NaiveDate>, AppError> {
+                    let Some(rest) = trimmed.split(keyword).nth(1) else {
+                        return Ok(None);
+                    };
+                    let date = rest.trim_start().trim_start_matches(['<', '[']).get(..10).unwrap_or(rest);
+                    NaiveDate::parse_from_str(date, "%Y-%m-%d").map(Some).map_err(|e| {
+                        let line = line_no + 1;
+                        AppError::Parse(format!("line {}: invalid {} '{}': {}", line, keyword, date.trim(), e))
+                    })
+                };
+                // A deadline wins over the scheduled date
+                if let Some(due) = planning("DEADLINE:")?.or(planning("SCHEDULED:")?) {
+                    item.due_date = Some(due);
+                }
+            } else if !trimmed.is_empty() || item.notes.is_some() {
+                let notes = item.notes.get_or_insert_with(String::new);
+                if !notes.is_empty() {
+                    notes.push('\n');
+                }
+                notes.push_str(line.trim_end());
+            }
+        }
+        for item in &mut items {
+            item.notes = item.notes.take().map(|n| n.trim_end().to_owned()).filter(|n| !n.is_empty());
+        }
+        Ok(items)
+    }
+
+    // Read a file and add its items to the active list, the format is guessed from the name
+    pub fn import_file(&mut self, path: &str) -> Result<usize, AppError> {
+        let path = storage::expand_home(path);
+        let text = std::fs::read_to_string(&path)?;
+        let items = Self::import(&text, ImportFormat::for_file(&path, &text))?;
+        let count = items.len();
+        if count > 0 {
+            self.checkpoint("Import file");
+            self.extend_items(items);
+        }
+        Ok(count)
+    }
+
+    // Tasks for --import, one per line. Lines starting with # are comments, [x] ones are
+    // already done and a leading [ ] is dropped, so --print --plain output reads back
+    pub fn import_from_file(path: &Path) -> io::Result<Vec<TodoItem>> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(text.lines().filter_map(parse_task_line).collect())
+    }
+
+    // One item per non-empty line
+    pub fn import_plain(s: &str) -> Vec<TodoItem> {
+        s.lines()
+            .filter_map(|line| TodoItem::builder().description(line.trim()).build().ok())
+            .collect()
+    }
+
+    // Either an array of item objects or an array of plain descriptions
+    pub fn import_json(s: &str) -> Result<Vec<TodoItem>, AppError> {
+        if let Ok(descriptions) = serde_json::from_str::<Vec<String>>(s) {
+            return Ok(Self::import_plain(&descriptions.join("\n")));
+        }
+        Ok(serde_json::from_str(s)?)
+    }
+
+    // See https://github.com/todotxt/todo.txt for the format
+    pub fn import_todotxt(s: &str) -> Vec<TodoItem> {
+        s.lines().filter_map(parse_todotxt_line).collect()
+    }
+
+    // Expects the header written by the CSV export, a headerless file is read as
+    // one description per row
+    pub fn import_csv(s: &str) -> Result<Vec<TodoItem>, AppError> {
+        let mut rows = s.lines().filter(|line| !line.trim().is_empty()).enumerate();
+        let Some((_, first)) = rows.next() else {
+            return Ok(vec![]);
+        };
+        let header = split_csv_line(first)?;
+        let column = |name: &str| header.iter().position(|h| h.trim().eq_ignore_ascii_case(name));
+        let Some(description_col) = column("description") else {
+            let mut items = vec![];
+            for line in s.lines() {
+                if let Some(description) = split_csv_line(line)?.first() {
+                    items.extend(TodoItem::builder().description(description.trim()).build());
+                }
+            }
+            return Ok(items);
+        };
+        let (done_col, priority_col, due_col, tags_col) =
+            (column("done"), column("priority"), column("due_date"), column("tags"));
+
+        let mut items = vec![];
+        for (line_no, line) in rows {
+            let fields = split_csv_line(line)?;
+            let field = |col: Option<usize>| col.and_then(|c| fields.get(c)).map(|f| f.trim());
+            let mut builder = TodoItem::builder()
+                .description(field(Some(description_col)).unwrap_or_default())
+                .done(matches!(field(done_col), Some("true" | "x" | "X" | "1" | "yes")))
+                .priority(match field(priority_col) {
+                    Some("high") => Priority::High,
+                    Some("medium") => Priority::Medium,
+                    Some("low") => Priority::Low,
+                    _ => Priority::None,
+                })
+                .tags(field(tags_col).map_or(vec![], |tags| {
+                    tags.split_whitespace().map(str::to_owned).collect()
+                }));
+            if let Some(due) = field(due_col).filter(|d| !d.is_empty()) {
+                builder = builder.due(NaiveDate::parse_from_str(due, "%Y-%m-%d").map_err(|e| {
+                    AppError::Parse(format!("line {}: invalid due date '{}': {}", line_no + 1, due, e))
+                })?);
+            }
+            // Rows without a description are skipped
+            items.extend(builder.build());
+        }
+        Ok(items)
+    }
+}
+
+fn parse_task_line(line: &str) -> Option<TodoItem> {
+    let line = line.trim();
+    if line.starts_with('#') {
+        return None;
+    }
+    let (done, description) = match line.get(..3) {
+        Some("[x]" | "[X]") => (true, &line[3..]),
+        Some("[ ]") => (false, &line[3..]),
+        _ => (false, line),
+    };
+    let mut item = TodoItem::builder().description(description.trim()).done(done).build().ok()?;
+    if item.done {
+        item.completed_at = Some(item.created_at);
+    }
+    Some(item)
+}
+
+fn parse_todotxt_line(line: &str) -> Option<TodoItem> {
+    let mut builder = TodoItem::builder();
+    let mut words = line.split_whitespace().peekable();
+    if words.peek() == Some(&"x") {
+        builder = builder.done(true);
+        words.next();
+    }
+    if let Some(priority) = words.peek().and_then(|w| parse_todotxt_priority(w)) {
+        builder = builder.priority(priority);
+        words.next();
+    }
+    // Completion and creation dates are positional, skip them
+    while words
+        .peek()
+        .is_some_and(|w| NaiveDate::parse_from_str(w, "%Y-%m-%d").is_ok())
+    {
+        words.next();
+    }
+    let mut description = vec![];
+    let mut tags = vec![];
+    for word in words {
+        if let Some(due) = word
+            .strip_prefix("due:")
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        {
+            builder = builder.due(due);
+        } else if let Some(tag) = word
+            .strip_prefix('+')
+            .or_else(|| word.strip_prefix('@'))
+            .filter(|t| !t.is_empty())
+        {
+            tags.push(tag.to_owned());
+        } else {
+            description.push(word);
+        }
+    }
+    builder.description(&description.join(" ")).tags(tags).build().ok()
+}
+
+// The part of a headline after its stars: keyword, priority cookie, title and trailing tags
+fn parse_org_headline(rest: &str) -> Option<TodoItem> {
+    let rest = rest.trim();
+    let (done, rest) = match rest.split_once(' ').unwrap_or((rest, "")) {
+        ("TODO", rest) => (false, rest.trim_start()),
+        ("DONE", rest) => (true, rest.trim_start()),
+        _ => return None,
+    };
+    let (priority, rest) = match rest.get(..4) {
+        Some("[#A]") => (Priority::High, &rest[4..]),
+        Some("[#B]") => (Priority::Medium, &rest[4..]),
+        Some("[#C]") => (Priority::Low, &rest[4..]),
+        _ => (Priority::None, rest),
+    };
+    let rest = rest.trim();
+    let mut tags = vec![];
+    let mut title = rest;
+    let (head, last) = rest.rsplit_once(char::is_whitespace).unwrap_or(("", rest));
+    if last.len() > 1 && last.starts_with(':') && last.ends_with(':') && !last.contains("::") {
+        tags = last.split(':').filter(|t| !t.is_empty()).map(str::to_owned).collect();
+        title = head.trim_end();
+    }
+    TodoItem::builder().description(title).done(done).priority(priority).tags(tags).build().ok()
+}
+
+fn parse_todotxt_priority(word: &str) -> Option<Priority> {
+    match word {
+        "(A)" => Some(Priority::High),
+        "(B)" => Some(Priority::Medium),
+        "(C)" => Some(Priority::Low),
+        _ if word.len() == 3 && word.starts_with('(') && word.ends_with(')') => Some(Priority::None),
+        _ => None,
+    }
+}
+
+// Minimal RFC 4180 field splitting for a single line: quoted fields, "" escapes
+fn split_csv_line(line: &str) -> Result<Vec<String>, AppError> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(AppError::Parse(format!("unterminated quote in '{}'", line)));
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Fragments of every import format plus the characters the parsers split on; the
+    // inputs are built from random runs of them, the same ones on every run
+    const FRAGMENTS: &[&str] = &[
+        "x", "(A)", "(é)", "due:", "2024-02-30", "2024-06-01", "+", "@", "+work", " ", "\n", ",", "\"",
+        "\"\"", "description,done,priority,due_date,tags", "true", "high", "[", "]", "{", "}",
+        "\"id\":", "null", "ü", "🙂", "\r", "\"tasks\":", "\"parent\":", "\"due\":", "\"2024-06-01T00:00:00.000Z\"",
+        "* ", "** TODO ", "DONE ", "[#A]", ":tag:", "DEADLINE: <", ":TAGS:", ":END:",
+    ];
+
+    #[test]
+    fn google_tasks_keep_their_parent() {
+        let json = r#"{"items": [{"title": "Home", "items": [
+            {"id": "a", "title": "Paint the shed", "status": "needsAction", "due": "2024-06-01T00:00:00.000Z"},
+            {"id": "b", "title": "Buy paint", "status": "completed", "notes": "White", "parent": "a"}
+        ]}]}"#;
+        let items = AppState::import_google_tasks(json).unwrap();
+        assert_eq!(items[0].due_date, NaiveDate::from_ymd_opt(2024, 6, 1));
+        assert!(!items[0].done && items[1].done);
+        assert_eq!(items[1].notes.as_deref(), Some("White"));
+        assert_eq!(items[1].parent_id, Some(items[0].id));
+    }
+
+    #[test]
+    fn org_headlines_nest() {
+        let org = "* TODO [#A] Paint the shed :home:diy:\n  DEADLINE: <2024-06-01 Sat>\n\
+            Two coats\n** Notes\n*** DONE Buy paint\n** TODO Sand :PROPERTIES:\n* Ideas\n";
+        let items = AppState::import_orgmode(org).unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].priority, Priority::High);
+        assert_eq!(items[0].tags, ["home", "diy"]);
+        assert_eq!(items[0].due_date, NaiveDate::from_ymd_opt(2024, 6, 1));
+        assert_eq!(items[0].notes.as_deref(), Some("Two coats"));
+        assert!(items[1].done);
+        assert_eq!(items[1].parent_id, Some(items[0].id));
+        assert_eq!(items[2].parent_id, Some(items[0].id));
+    }
+
+    #[test]
+    fn task_file_skips_comments() {
+        let path = std::env::temp_dir().join(format!("todo-tui-import-{}.txt", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "# groceries\nMilk\n\n[x] Bread\n  [ ] Eggs\n").unwrap();
+        let items = AppState::import_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let descriptions: Vec<_> = items.iter().map(|item| item.description.as_str()).collect();
+        assert_eq!(descriptions, ["Milk", "Bread", "Eggs"]);
+        assert!(!items[0].done && items[1].done && !items[2].done);
+    }
+
+    // A quick check on every cargo test, the fuzz targets in fuzz/ go much further
+    #[test]
+    fn imports_never_panic() {
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..5_000 {
+            let len = next() % 24;
+            let input: String = (0..len).map(|_| FRAGMENTS[(next() % FRAGMENTS.len() as u64) as usize]).collect();
+            for format in [
+                ImportFormat::Plain,
+                ImportFormat::Json,
+                ImportFormat::TodoTxt,
+                ImportFormat::Csv,
+                ImportFormat::GoogleTasks,
+                ImportFormat::OrgMode,
+            ] {
+                // Ok or a typed error are both fine, only a panic fails the test
+                let _ = AppState::import(&input, format);
+            }
+        }
+    }
+}
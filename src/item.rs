@@ -0,0 +1,521 @@
+use crate::config::{Config, DoneStyle};
+use crate::error::AppError;
+use crate::theme::Theme;
+use chrono::{DateTime, Local, NaiveDate};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::ListItem;
+use serde::{Deserialize, Serialize};
+use regex::Regex;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::LazyLock;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    #[default]
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+impl Priority {
+    // The inverse of label
+    pub fn parse(text: &str) -> Option<Priority> {
+        [Priority::None, Priority::Low, Priority::Medium, Priority::High]
+            .into_iter()
+            .find(|priority| priority.label().eq_ignore_ascii_case(text))
+    }
+
+    // The next one for the p key, wrapping from low back to none
+    pub fn cycle(self) -> Priority {
+        match self {
+            Priority::None => Priority::High,
+            Priority::High => Priority::Medium,
+            Priority::Medium => Priority::Low,
+            Priority::Low => Priority::None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Priority::None => "none",
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+        }
+    }
+}
+
+// Swatches offered by the color label picker
+pub const LABEL_COLORS: [(u8, u8, u8); 8] = [
+    (239, 68, 68),
+    (249, 115, 22),
+    (234, 179, 8),
+    (34, 197, 94),
+    (6, 182, 212),
+    (59, 130, 246),
+    (168, 85, 247),
+    (236, 72, 153),
+];
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TodoItem {
+    pub id: Uuid,
+    pub done: bool,
+    pub description: String,
+    pub priority: Priority,
+    pub tags: Vec<String>,
+    pub due_date: Option<NaiveDate>,
+    pub created_at: DateTime<Local>,
+    pub color_label: Option<(u8, u8, u8)>,
+    pub notes: Option<String>,
+    pub comments: Vec<(DateTime<Local>, String)>,
+    pub completed_at: Option<DateTime<Local>>,
+    pub reminder_at: Option<DateTime<Local>>,
+    // Ids of related items, see AppState::toggle_link
+    pub links: Vec<Uuid>,
+    // Set on subtasks, the id of the item they belong to
+    pub parent_id: Option<Uuid>,
+    // Subtasks are hidden from the main list while set
+    pub collapsed: bool,
+    // Where the item came from, e.g. a GitHub issue, opened with o
+    pub url: Option<String>,
+}
+
+impl Default for TodoItem {
+    fn default() -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            done: false,
+            description: String::new(),
+            priority: Priority::None,
+            tags: vec![],
+            due_date: None,
+            created_at: Local::now(),
+            color_label: None,
+            notes: None,
+            comments: vec![],
+            completed_at: None,
+            reminder_at: None,
+            links: vec![],
+            parent_id: None,
+            collapsed: false,
+            url: None,
+        }
+    }
+}
+
+impl TodoItem {
+    pub fn builder() -> TodoItemBuilder {
+        TodoItemBuilder::default()
+    }
+
+    // Keeps completed_at in step with the done flag
+    pub fn set_done(&mut self, done: bool) {
+        if done && !self.done {
+            self.completed_at = Some(Local::now());
+        } else if !done {
+            self.completed_at = None;
+        }
+        self.done = done;
+    }
+
+    pub fn validate_description(description: &str) -> Result<(), AppError> {
+        if description.trim().is_empty() {
+            return Err(AppError::InvalidInput(
+                "description must not be empty".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct TodoItemBuilder {
+    item: TodoItem,
+}
+
+// Changes to an existing item, checked by build like a new one
+impl From<TodoItem> for TodoItemBuilder {
+    fn from(item: TodoItem) -> Self {
+        Self { item }
+    }
+}
+
+impl TodoItemBuilder {
+    pub fn id(mut self, id: Uuid) -> Self {
+        self.item.id = id;
+        self
+    }
+
+    pub fn description(mut self, s: &str) -> Self {
+        self.item.description = s.to_owned();
+        self
+    }
+
+    pub fn done(mut self, b: bool) -> Self {
+        self.item.done = b;
+        self
+    }
+
+    pub fn priority(mut self, p: Priority) -> Self {
+        self.item.priority = p;
+        self
+    }
+
+    // None clears it
+    pub fn due(mut self, d: impl Into<Option<NaiveDate>>) -> Self {
+        self.item.due_date = d.into();
+        self
+    }
+
+    pub fn tags(mut self, v: Vec<String>) -> Self {
+        self.item.tags = v;
+        self
+    }
+
+    pub fn reminder(mut self, at: impl Into<Option<DateTime<Local>>>) -> Self {
+        self.item.reminder_at = at.into();
+        self
+    }
+
+    pub fn url(mut self, url: String) -> Self {
+        self.item.url = Some(url);
+        self
+    }
+
+    pub fn build(self) -> Result<TodoItem, AppError> {
+        TodoItem::validate_description(&self.item.description)?;
+        Ok(self.item)
+    }
+}
+
+// Items are the same item when their ids match, whatever has been edited since
+impl PartialEq for TodoItem {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for TodoItem {}
+
+impl Hash for TodoItem {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl From<&str> for TodoItem {
+    fn from(value: &str) -> Self {
+        TodoItem {
+            description: value.to_owned(),
+            done: false,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<String> for TodoItem {
+    fn from(value: String) -> Self {
+        TodoItem {
+            description: value,
+            done: false,
+            ..Default::default()
+        }
+    }
+}
+
+impl fmt::Display for TodoItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = if self.done { "x" } else { " " };
+        write!(f, "[{}] {}", mark, self.description)
+    }
+}
+
+impl TodoItem {
+    // Incomplete items fade out the longer they sit untouched
+    fn aged_style(&self, config: &Config, theme: &Theme, now: DateTime<Local>) -> Style {
+        let style = Style::new().fg(theme.item_pending_fg);
+        if !config.aging_enabled {
+            return style;
+        }
+        let age = (now - self.created_at).num_days();
+        let (warn, critical) = (config.aging_warn_days as i64, config.aging_critical_days as i64);
+        if age > critical * 2 {
+            style
+                .fg(theme.item_aged_fg)
+                .add_modifier(Modifier::DIM | Modifier::CROSSED_OUT)
+        } else if age > critical {
+            style.fg(theme.item_aged_fg)
+        } else if age > warn {
+            style.add_modifier(Modifier::DIM)
+        } else {
+            style
+        }
+    }
+
+    // Pending items past their due date are red, the ones due today yellow
+    pub fn due_color(&self, today: NaiveDate) -> Option<Color> {
+        match self.due_date? {
+            _ if self.done => None,
+            due if due < today => Some(Color::Red),
+            due if due == today => Some(Color::Yellow),
+            _ => None,
+        }
+    }
+}
+
+static URL_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"https?://[^\s]+").unwrap());
+
+impl TodoItem {
+    pub fn first_url(&self) -> Option<&str> {
+        URL_RE.find(&self.description).map(|m| m.as_str()).or(self.url.as_deref())
+    }
+
+    // "TODO: Read the docs — https://ratatui.rs" for pasting into a chat, just the
+    // description without a link
+    pub fn share_text(&self) -> String {
+        let Some(url) = self.first_url() else {
+            return self.description.clone();
+        };
+        let description = URL_RE.replace_all(&self.description, "");
+        let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+        format!("TODO: {} — {}", description, url)
+    }
+
+    pub fn to_list_item(&self, config: &Config, theme: &Theme) -> ListItem<'static> {
+        ListItem::new(self.to_line(config, theme))
+    }
+
+    pub fn to_line(&self, config: &Config, theme: &Theme) -> Line<'static> {
+        self.to_line_with_matches(config, theme, &[], &[])
+    }
+
+    // `matches` and `misspelled` are char positions in the description, search hits win
+    pub fn to_line_with_matches(
+        &self,
+        config: &Config,
+        theme: &Theme,
+        matches: &[usize],
+        misspelled: &[usize],
+    ) -> Line<'static> {
+        if config.screen_reader_mode {
+            return Line::from(self.screen_reader_text(config, Local::now().date_naive()));
+        }
+        let label = match self.color_label {
+            Some((r, g, b)) => Span::styled(if config.ascii() { "#" } else { "█" }, Color::Rgb(r, g, b)),
+            None => Span::raw(" "),
+        };
+        let (done_mark, todo_mark) = config.done_symbols();
+        let now = Local::now();
+        let (mark, style) = match (self.done, self.due_color(now.date_naive())) {
+            (false, Some(color)) => (todo_mark, self.aged_style(config, theme, now).fg(color)),
+            (false, None) => (todo_mark, self.aged_style(config, theme, now)),
+            (true, _) => (done_mark, done_style(config.done_style, theme)),
+        };
+        // Marks of different widths still line the descriptions up
+        let width = done_mark.width().max(todo_mark.width());
+        let padding = " ".repeat(width - mark.width());
+        // The mark of a pending item takes the color of its priority
+        let mark_style = match self.priority {
+            _ if self.done => style,
+            Priority::None => style,
+            Priority::Low => style.fg(Color::Green),
+            Priority::Medium => style.fg(Color::Yellow),
+            Priority::High => style.fg(Color::Red),
+        };
+        let mut spans = vec![label, Span::styled(format!(" {}{} ", mark, padding), mark_style)];
+        // URLs stand out from the rest of the description
+        let url_style = style.fg(Color::Cyan).add_modifier(Modifier::UNDERLINED);
+        let urls: Vec<_> = URL_RE.find_iter(&self.description).map(|m| m.range()).collect();
+        let mut run = String::new();
+        let mut run_style = style;
+        for (i, (offset, c)) in self.description.char_indices().enumerate() {
+            let char_style = if matches.contains(&i) {
+                style.patch(config.search_style)
+            } else if urls.iter().any(|url| url.contains(&offset)) {
+                url_style
+            } else if misspelled.contains(&i) {
+                style.fg(Color::Red).add_modifier(Modifier::UNDERLINED)
+            } else {
+                style
+            };
+            if char_style != run_style && !run.is_empty() {
+                spans.push(Span::styled(std::mem::take(&mut run), run_style));
+            }
+            run_style = char_style;
+            run.push(c);
+        }
+        spans.push(Span::styled(run, run_style));
+        if self.notes.is_some() {
+            spans.push(Span::raw(if config.ascii() { " [notes]" } else { " 📝" }));
+        }
+        Line::from(spans)
+    }
+
+    // Everything the styled line shows, spelled out in words for screen readers
+    pub fn screen_reader_text(&self, config: &Config, today: NaiveDate) -> String {
+        let mut parts = vec![if self.done { "[DONE]" } else { "[TODO]" }.to_owned()];
+        if self.priority != Priority::None {
+            parts.push(format!("[{}]", self.priority.label().to_uppercase()));
+        }
+        if let Some(due) = self.due_date {
+            parts.push(format!("[DUE: {}]", config.format_date(due, today)));
+            if !self.done && due < today {
+                parts.push("[OVERDUE]".to_owned());
+            }
+        }
+        parts.push(self.description.clone());
+        if self.notes.is_some() {
+            parts.push("[NOTES]".to_owned());
+        }
+        if !self.comments.is_empty() {
+            parts.push(format!("[{} COMMENTS]", self.comments.len()));
+        }
+        parts.join(" ")
+    }
+}
+
+impl TodoItem {
+    // Shown right-aligned next to the item in the main list
+    pub fn metadata(&self, config: &Config, today: NaiveDate) -> Option<String> {
+        let mut parts = vec![];
+        if let Some(due) = self.due_date {
+            parts.push(config.format_date(due, today));
+        }
+        if !self.comments.is_empty() {
+            parts.push(format!("[{} notes]", self.comments.len()));
+        }
+        if !self.tags.is_empty() {
+            parts.push(self.tags.iter().map(|tag| format!("#{}", tag)).collect::<Vec<_>>().join(" "));
+        }
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join("  "))
+        }
+    }
+}
+
+// Word wraps what comes after the first `prefix` spans, the label and mark of an item line,
+// with the rows after the first indented to line up under it. Words longer than a row are
+// split
+pub fn wrap_line(line: Line<'static>, prefix: usize, width: usize) -> Vec<Line<'static>> {
+    let (head, tail) = line.spans.split_at(prefix.min(line.spans.len()));
+    let indent: usize = head.iter().map(Span::width).sum();
+    let width = width.max(indent + 1);
+    let chars: Vec<(char, Style)> =
+        tail.iter().flat_map(|span| span.content.chars().map(move |c| (c, span.style))).collect();
+    let mut rows: Vec<Vec<(char, Style)>> = vec![];
+    let mut row = vec![];
+    let mut used = indent;
+    for word in chars.chunk_by(|a, b| a.0.is_whitespace() == b.0.is_whitespace()) {
+        let word_width: usize = word.iter().map(|(c, _)| c.width().unwrap_or(0)).sum();
+        if used + word_width > width && used > indent {
+            // Neither the spaces before the break nor the one it breaks at are drawn
+            while row.last().is_some_and(|(c, _): &(char, Style)| c.is_whitespace()) {
+                row.pop();
+            }
+            rows.push(std::mem::take(&mut row));
+            used = indent;
+            if word[0].0.is_whitespace() {
+                continue;
+            }
+        }
+        for &(c, style) in word {
+            let char_width = c.width().unwrap_or(0);
+            if used + char_width > width && used > indent {
+                rows.push(std::mem::take(&mut row));
+                used = indent;
+            }
+            row.push((c, style));
+            used += char_width;
+        }
+    }
+    rows.push(row);
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let mut spans = if i == 0 { head.to_vec() } else { vec![Span::raw(" ".repeat(indent))] };
+            for run in row.chunk_by(|a, b| a.1 == b.1) {
+                spans.push(Span::styled(run.iter().map(|&(c, _)| c).collect::<String>(), run[0].1));
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+fn done_style(style: DoneStyle, theme: &Theme) -> Style {
+    match style {
+        DoneStyle::StrikethroughGray | DoneStyle::Hidden => {
+            Style::new().fg(theme.item_done_fg).add_modifier(Modifier::CROSSED_OUT)
+        }
+        DoneStyle::Dim => Style::new().fg(theme.item_pending_fg).add_modifier(Modifier::DIM),
+        DoneStyle::Normal => Style::new().fg(theme.item_pending_fg),
+    }
+}
+
+// Five cells of subtask progress with a half cell for odd tenths, e.g. "██▓░░ 5/10"
+pub fn subtask_bar(done: usize, total: usize, ascii: bool) -> String {
+    let (full, half, empty) = if ascii { ('#', '=', '.') } else { ('█', '▓', '░') };
+    let tenths = (done * 10 / total.max(1)).min(10);
+    let mut bar: String = std::iter::repeat_n(full, tenths / 2).collect();
+    if tenths % 2 == 1 {
+        bar.push(half);
+    }
+    bar.extend(std::iter::repeat_n(empty, 5 - bar.chars().count()));
+    format!("[{}] {}/{}", bar, done, total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::hash::DefaultHasher;
+
+    #[test]
+    fn identity_is_the_id() {
+        let a = TodoItem::from("Buy milk");
+        let b = TodoItem {
+            description: "Buy oat milk".to_owned(),
+            done: true,
+            ..a.clone()
+        };
+        assert!(a == b);
+        assert!(a != TodoItem::from("Buy milk"));
+
+        let hash = |item: &TodoItem| {
+            let mut hasher = DefaultHasher::new();
+            item.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&a), hash(&b));
+
+        let set: HashSet<TodoItem> = [a.clone(), b, TodoItem::from("Buy milk")].into_iter().collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&a));
+    }
+
+    #[test]
+    fn wrapped_rows_line_up_under_the_description() {
+        let line = Line::from(vec![Span::raw(" "), Span::raw(" ☐ "), Span::raw("Paint the garden shed white")]);
+        let rows: Vec<String> = wrap_line(line, 2, 14).iter().map(|row| row.to_string()).collect();
+        assert_eq!(rows, ["  ☐ Paint the", "    garden", "    shed white"]);
+        let rows = wrap_line(Line::from(vec![Span::raw(" "), Span::raw("Supercalifragilistic")]), 1, 8);
+        assert_eq!(rows.len(), 3);
+    }
+
+    #[test]
+    fn subtask_bar_fills_half_cells() {
+        assert_eq!(subtask_bar(0, 3, false), "[░░░░░] 0/3");
+        assert_eq!(subtask_bar(2, 5, false), "[██░░░] 2/5");
+        assert_eq!(subtask_bar(1, 2, false), "[██▓░░] 1/2");
+        assert_eq!(subtask_bar(4, 4, true), "[#####] 4/4");
+    }
+}
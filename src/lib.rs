@@ -0,0 +1,34 @@
+pub mod app;
+pub mod archive;
+pub mod bulk;
+pub mod cli;
+pub mod clipboard;
+pub mod config;
+pub mod context_menu;
+pub mod crypto;
+pub mod date_utils;
+pub mod error;
+pub mod export;
+pub mod github;
+pub mod handler;
+pub mod help;
+pub mod import;
+pub mod item;
+pub mod links;
+pub mod palette;
+pub mod parser;
+pub mod pomodoro;
+pub mod qr;
+pub mod review;
+pub mod search;
+pub mod server;
+pub mod spell;
+pub mod stats;
+pub mod storage;
+pub mod strings;
+pub mod theme;
+pub mod title;
+pub mod ui;
+pub mod undo;
+pub mod update;
+pub mod webhook;
@@ -0,0 +1,68 @@
+use crate::app::AppState;
+use crate::item::TodoItem;
+use crate::search::Matcher;
+use uuid::Uuid;
+
+// "See also" links between items, always kept on both ends
+impl AppState {
+    // (list, storage index) of the item with this id
+    pub fn find_item(&self, id: Uuid) -> Option<(usize, usize)> {
+        self.lists.iter().enumerate().find_map(|(l, list)| {
+            (0..list.len()).find(|&i| list.get(i).is_some_and(|item| item.id == id)).map(|i| (l, i))
+        })
+    }
+
+    fn linked_item(&self, id: Uuid) -> Option<&TodoItem> {
+        let (list, index) = self.find_item(id)?;
+        self.lists[list].get(index)
+    }
+
+    fn linked_item_mut(&mut self, id: Uuid) -> Option<&mut TodoItem> {
+        let (list, index) = self.find_item(id)?;
+        self.lists[list].get_mut(index)
+    }
+
+    // Linked items that still exist, links to deleted items are skipped
+    pub fn resolve_links(&self, id: Uuid) -> Vec<&TodoItem> {
+        let Some(item) = self.linked_item(id) else {
+            return vec![];
+        };
+        item.links.iter().filter_map(|&link| self.linked_item(link)).collect()
+    }
+
+    // Link two items, or unlink them when they already are
+    pub fn toggle_link(&mut self, a: Uuid, b: Uuid) {
+        if a == b {
+            self.flash(self.strings.cannot_link_to_itself.clone());
+            return;
+        }
+        let linked = self.linked_item(a).is_some_and(|item| item.links.contains(&b));
+        self.checkpoint(if linked { "Remove link" } else { "Add link" });
+        for (from, to) in [(a, b), (b, a)] {
+            if let Some(item) = self.linked_item_mut(from) {
+                if linked {
+                    item.links.retain(|&link| link != to);
+                } else if !item.links.contains(&to) {
+                    item.links.push(to);
+                }
+            }
+        }
+    }
+
+    // Items in any list matching the link search, except the focused one
+    pub fn link_candidates(&self) -> Vec<(usize, usize)> {
+        let focused = self.selected_item_index().and_then(|i| self.get(i)).map(|item| item.id);
+        let query = self.link_input.value();
+        let matcher = Matcher::new(true);
+        let mut rows = vec![];
+        for (l, list) in self.lists.iter().enumerate() {
+            for i in 0..list.len() {
+                let Some(item) = list.get(i) else { continue };
+                if Some(item.id) != focused && (query.is_empty() || matcher.find(query, &item.description).is_some()) {
+                    rows.push((l, i));
+                }
+            }
+        }
+        rows
+    }
+}
@@ -1,266 +1,504 @@
-use ratatui::crossterm::event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode};
+use futures_util::future::{self, Either};
+use futures_util::StreamExt;
+use todo_list::app::{AppState, CurrentScreen};
+use todo_list::cli::{self, Args, Mutation};
+use todo_list::config::{self, Config};
+use todo_list::error::AppError;
+use todo_list::storage::{self, FileBackend, StorageBackend};
+use todo_list::strings::fill;
+use todo_list::{clipboard, server, ui, webhook};
+use ratatui::crossterm::event::{
+    self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event, EventStream,
+    KeyCode, KeyEventKind, KeyModifiers,
+};
+use ratatui::crossterm::cursor::SetCursorStyle;
 use ratatui::crossterm::execute;
+use ratatui::crossterm::style::Print;
 use ratatui::crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
 };
-use ratatui::layout::Rect;
 use ratatui::prelude::{Backend, CrosstermBackend};
-use ratatui::style::palette::material::{GRAY, WHITE};
-use ratatui::style::palette::tailwind::SLATE;
-use ratatui::style::{Modifier, Style, Stylize};
-use ratatui::text::Line;
-use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, StatefulWidget};
-use ratatui::{Frame, Terminal};
-use std::io;
-use tui_input::backend::crossterm::EventHandler;
-use tui_input::Input;
+use ratatui::Terminal;
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::pin::pin;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
 
-pub enum CurrentScreen {
-    Main,
-    Add,
-    Edit,
-    Exit,
-}
+fn main() -> io::Result<()> {
+    let args = match Args::parse() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, cli::USAGE);
+            process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
 
-#[derive(Clone)]
-pub struct TodoItem {
-    pub done: bool,
-    pub description: String,
-}
+    // Command line changes never start the TUI
+    let no_tui = args.no_tui || args.serve || args.doctor || args.print || !args.mutations.is_empty();
+
+    // --file wins over the config's file_path, which wins over the data directory
+    let config_file = args.config.clone().or_else(config::config_path);
+    let config = Config::load(config_file.as_deref());
+    let mut data_path = args
+        .file
+        .clone()
+        .or_else(|| config.file_path.as_deref().map(storage::expand_home))
+        .or_else(storage::data_path);
+    let mut password = None;
+    if config.encrypt {
+        data_path = data_path.map(|path| storage::encrypted_path(&path));
+        match env::var(&config.encryption_key_env) {
+            Ok(key) => password = Some(key),
+            // Stdin is taken by the import, there is no TUI to ask in, or the imported items
+            // need the saved ones loaded first. Otherwise the file is unlocked from the password
+            // prompt once the TUI is up
+            Err(_) if args.stdin || args.import.is_some() || no_tui => password = Some(prompt_password()?),
+            Err(_) => {}
+        }
+    }
+    // Held until the lists are saved on exit, a TUI keeps the save file to itself while it
+    // runs. The server locks it for each request instead
+    let lock = match data_path.as_ref().filter(|_| !args.serve) {
+        Some(path) => match storage::lock(path) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                eprintln!("error: could not lock {}: {}", path.display(), e);
+                process::exit(1);
+            }
+        },
+        None => None,
+    };
+    let mut app = AppState::new(config, data_path.as_deref(), password.clone());
+    app.config_file = config_file;
+    app.export_dir = args.export_dir.clone();
+    if app.load_failed && no_tui {
+        eprintln!("error: {}", app.status_message.as_deref().unwrap_or_default());
+        process::exit(1);
+    }
+
+    if args.doctor {
+        let errors = app.integrity_check();
+        for error in &errors {
+            println!("{}", error);
+        }
+        if !errors.is_empty() {
+            process::exit(1);
+        }
+        println!("No problems found");
+        return Ok(());
+    }
 
-impl From<&TodoItem> for ListItem<'_> {
-    fn from(value: &TodoItem) -> Self {
-        let line = match value.done {
-            false => Line::styled(format!(" ☐ {}", value.description), WHITE),
-            true => Line::styled(
-                format!(" ✓ {}", value.description),
-                (GRAY.c500, Modifier::CROSSED_OUT),
-            ),
+    if args.serve {
+        let Some(path) = &data_path else {
+            eprintln!("error: no data directory to serve from");
+            process::exit(1);
         };
-        ListItem::new(line)
+        let port = args.port.unwrap_or(server::DEFAULT_PORT);
+        return server::serve(port, path, password.as_deref(), &app.config);
     }
-}
 
-pub struct AppState {
-    pub current_screen: CurrentScreen,
-    pub input: Input,
-    pub currently_editing: Option<TodoItem>,
-    pub edit_index: usize,
-    pub todo_list_state: ListState,
-    pub items: Vec<TodoItem>,
-}
+    if args.stdin {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        match AppState::import(&text, args.stdin_format) {
+            Ok(items) => {
+                eprintln!("Imported {} item(s)", items.len());
+                let duplicates_before = app.duplicate_count();
+                app.extend_items(items);
+                let added = app.duplicate_count() - duplicates_before;
+                if added > 0 {
+                    if no_tui {
+                        eprintln!("Warning: {} imported item(s) duplicate existing ones", added);
+                    } else {
+                        app.current_screen = CurrentScreen::ConfirmDedup;
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("error: could not import stdin: {}", e);
+                process::exit(1);
+            }
+        }
+    }
 
-impl AppState {
-    pub fn new() -> Self {
-        Self {
-            input: Input::default(),
-            current_screen: CurrentScreen::Main,
-            currently_editing: None,
-            edit_index: 0,
-            todo_list_state: ListState::default(),
-            items: vec![],
+    if let Some(path) = &args.import {
+        match AppState::import_from_file(path) {
+            Ok(items) => {
+                let message = fill(&app.strings.imported_items, &[("count", &items.len())]);
+                app.extend_items(items);
+                if no_tui {
+                    eprintln!("{}", message);
+                } else {
+                    app.flash(message);
+                }
+            }
+            Err(e) => {
+                eprintln!("error: could not import {}: {}", path.display(), e);
+                process::exit(1);
+            }
+        }
+    }
+
+    if !args.mutations.is_empty() {
+        if args.dry_run {
+            // Nothing happens for real, so nothing to report
+            app.config.webhook_url = None;
+        }
+        let before = serde_json::to_string_pretty(&app.save_file())?;
+        for mutation in &args.mutations {
+            match app.apply_mutation(mutation) {
+                // The description of the changed item is for scripts, the rest is a report
+                Ok(description) if !matches!(mutation, Mutation::Add(_) | Mutation::Delete(_)) => {
+                    println!("{}", description)
+                }
+                Ok(message) => eprintln!("{}", message),
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        if args.dry_run {
+            let after = serde_json::to_string_pretty(&app.save_file())?;
+            let diff = similar::TextDiff::from_lines(&before, &after);
+            print!("{}", diff.unified_diff().header("before", "after"));
+            return Ok(());
         }
     }
 
-    pub fn add_item(&mut self, todo_item: TodoItem) {
-        self.items.push(todo_item);
+    if args.print {
+        // Everything in the first list, not what the last session filtered it down to
+        app.clear_filters();
+        print!("{}", app.export_plaintext(args.ids));
     }
+    if no_tui && !args.stdin && args.import.is_none() && args.mutations.is_empty() {
+        // Nothing changed, leave the file and its backups alone
+        return Ok(());
+    }
+
+    if !no_tui {
+        app.start_update_check();
+        if app.current_screen == CurrentScreen::Main && app.daily_review_due() {
+            app.current_screen = CurrentScreen::DailyPrompt;
+        }
+        app.show_startup_message();
+        enable_raw_mode()?;
+        let mut stderr = io::stderr(); // This is a special case. Normally using stdout is fine
+        execute!(stderr, EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
+
+        let backend = CrosstermBackend::new(stderr);
+        let mut terminal = Terminal::new(backend)?;
+        let _cursor_guard = CursorGuard;
 
-    pub fn remove_at(&mut self, index: usize) {
-        self.items.remove(index);
+        // create app and run it
+        let signals = Signals::register()?;
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build()?;
+        let result = runtime.block_on(run_app(&mut terminal, &mut app, &signals, data_path.as_deref(), password.as_deref()));
+        let saved = matches!(result, Ok(true));
+        let restored = restore_terminal(&mut terminal, &app);
+        if signals.terminate.load(Ordering::Relaxed) {
+            drop(_cursor_guard);
+            process::exit(0);
+        }
+        // After a hangup the terminal is gone, writing to it fails but the data is still saved
+        if !signals.hangup.load(Ordering::Relaxed) {
+            restored?;
+            result?;
+        }
+        if let Err(e) = app.save_session() {
+            eprintln!("{}", fill(&app.strings.config_save_failed, &[("error", &e)]));
+        }
+        if saved {
+            drop(lock);
+            webhook::wait_for_pending();
+            return Ok(());
+        }
     }
 
-    pub fn replace(&mut self, todo_item: TodoItem, index: usize) {
-        self.items.remove(index);
-        self.items.insert(index, todo_item.clone());
+    if app.config.encrypt && password.is_none() {
+        password = app.password.take();
+        if password.is_none() {
+            // Never unlocked, leave the file alone
+            if app.password_attempts_left == 0 {
+                eprintln!("error: wrong password");
+                process::exit(1);
+            }
+            return Ok(());
+        }
     }
+    if let Some(path) = &data_path {
+        if app.load_failed {
+            eprintln!("error: {} was not saved, it could not be loaded", path.display());
+            process::exit(1);
+        }
+        if let Err(e) = save_lists(&app, path, password) {
+            eprintln!("error: could not save {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    }
+    drop(lock);
+    webhook::wait_for_pending();
+    Ok(())
+}
+
+// Read a password from the terminal without echoing it
+fn prompt_password() -> io::Result<String> {
+    eprint!("Password: ");
+    enable_raw_mode()?;
+    let mut password = String::new();
+    let result = loop {
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind != KeyEventKind::Release => match key.code {
+                KeyCode::Enter => break Ok(password),
+                KeyCode::Backspace => {
+                    password.pop();
+                }
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    break Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+                }
+                KeyCode::Char(c) => password.push(c),
+                _ => {}
+            },
+            Ok(_) => {}
+            Err(e) => break Err(e),
+        }
+    };
+    disable_raw_mode()?;
+    eprintln!();
+    result
 }
 
-impl Default for AppState {
-    fn default() -> Self {
-        Self::new()
+// Puts back the cursor shape the user had, however the TUI is left
+struct CursorGuard;
+
+impl Drop for CursorGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stderr(), SetCursorStyle::DefaultUserShape);
     }
 }
 
-fn main() -> io::Result<()> {
-    enable_raw_mode()?;
-    let mut stderr = io::stderr(); // This is a special case. Normally using stdout is fine
-    execute!(stderr, EnterAlternateScreen, EnableMouseCapture)?;
+// Flags set by the SIGTERM and SIGHUP handlers, run_app stops at the next wake-up
+#[derive(Default)]
+struct Signals {
+    terminate: Arc<AtomicBool>,
+    hangup: Arc<AtomicBool>,
+}
+
+impl Signals {
+    #[cfg(unix)]
+    fn register() -> io::Result<Self> {
+        use signal_hook::consts::{SIGHUP, SIGTERM};
+        let signals = Signals::default();
+        signal_hook::flag::register(SIGTERM, Arc::clone(&signals.terminate))?;
+        signal_hook::flag::register(SIGHUP, Arc::clone(&signals.hangup))?;
+        Ok(signals)
+    }
 
-    let backend = CrosstermBackend::new(stderr);
-    let mut terminal = Terminal::new(backend)?;
+    #[cfg(not(unix))]
+    fn register() -> io::Result<Self> {
+        Ok(Signals::default())
+    }
+
+    fn received(&self) -> bool {
+        self.terminate.load(Ordering::Relaxed) || self.hangup.load(Ordering::Relaxed)
+    }
+}
 
-    // create app and run it
-    let mut app = AppState::default();
-    run_app(&mut terminal, &mut app)?;
+fn restore_terminal<B: Backend + io::Write>(terminal: &mut Terminal<B>, app_state: &AppState) -> io::Result<()> {
+    if app_state.config.set_window_title {
+        execute!(terminal.backend_mut(), SetTitle(""))?;
+    }
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     )?;
-    terminal.show_cursor()?;
+    terminal.show_cursor()
+}
+
+// An I-beam where text is typed, a block elsewhere; ratatui hides the cursor on screens
+// that don't place it, such as Focus
+fn cursor_style(screen: CurrentScreen) -> SetCursorStyle {
+    if screen.is_text_input() {
+        SetCursorStyle::BlinkingBar
+    } else {
+        SetCursorStyle::SteadyBlock
+    }
+}
+
+// Leave the TUI for $VISUAL/$EDITOR on the config file, then come back and apply it
+fn edit_config<B: Backend + io::Write>(terminal: &mut Terminal<B>, app_state: &mut AppState) -> io::Result<()> {
+    let Some(path) = app_state.config_file.clone() else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let editor = env::var("VISUAL").or_else(|_| env::var("EDITOR")).unwrap_or_else(|_| "vi".to_owned());
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture, DisableBracketedPaste)?;
+    let status = process::Command::new(&editor).arg(&path).status();
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
+    terminal.clear()?;
+
+    let message = match status {
+        Err(e) => fill(&app_state.strings.editor_failed, &[("editor", &editor), ("error", &e)]),
+        Ok(_) => match app_state.reload_config() {
+            Ok(()) => app_state.strings.config_reloaded.clone(),
+            Err(e) => fill(&app_state.strings.config_not_reloaded, &[("error", &e)]),
+        },
+    };
+    app_state.flash(message);
     Ok(())
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, app_state: &mut AppState) -> io::Result<bool> {
+// Rotating the backups, unlike the autosaves
+fn save_lists(app_state: &AppState, path: &Path, password: Option<String>) -> Result<(), AppError> {
+    FileBackend::new(path.to_owned(), password).backups(app_state.config.backup_count).save(&app_state.save_file())
+}
+
+// How often the lists are saved while the TUI runs, on top of the save on exit
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+// Write the lists off the UI thread, without touching the backups the save on exit makes.
+// main holds the file lock all along. None while there's no data file, it couldn't be loaded
+// or an encrypted one is still locked
+fn autosave(app_state: &AppState, path: Option<&Path>, password: Option<&str>) -> Option<JoinHandle<Result<(), AppError>>> {
+    let path = path.filter(|_| !app_state.load_failed)?.to_owned();
+    let password = password.or(app_state.password.as_deref()).map(str::to_owned);
+    if app_state.config.encrypt && password.is_none() {
+        return None;
+    }
+    let save_file = app_state.save_file();
+    Some(tokio::task::spawn_blocking(move || FileBackend::new(path, password).save(&save_file)))
+}
+
+// True when the lists were saved on the way out
+async fn run_app<B: Backend + io::Write>(
+    terminal: &mut Terminal<B>,
+    app_state: &mut AppState,
+    signals: &Signals,
+    data_path: Option<&Path>,
+    password: Option<&str>,
+) -> io::Result<bool> {
+    let mut title = String::new();
+    let mut cursor_screen = None;
+    let mut events = EventStream::new();
+    let mut autosave_timer = tokio::time::interval(AUTOSAVE_INTERVAL);
+    autosave_timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick is right away, the lists were just loaded
+    autosave_timer.tick().await;
+    let mut saving = None;
+    // Kept for the whole run, on X11 what was copied is only there while it's alive
+    let mut system_clipboard = arboard::Clipboard::new().ok();
+    let mut save_failed = false;
     loop {
-        let evt = event::read()?;
-        if let Event::Key(key) = evt {
-            if key.kind == event::KeyEventKind::Release {
-                // Skip events that are not KeyEventKind::Press
+        while app_state.current_screen != CurrentScreen::Exit && !signals.received() {
+            if cursor_screen != Some(app_state.current_screen) {
+                cursor_screen = Some(app_state.current_screen);
+                execute!(terminal.backend_mut(), cursor_style(app_state.current_screen))?;
+            }
+            if app_state.config.set_window_title {
+                let pending = app_state.all_items().filter(|item| !item.done).count();
+                let new_title = format!("todo-tui — {} pending", pending);
+                if new_title != title {
+                    execute!(terminal.backend_mut(), SetTitle(&new_title))?;
+                    title = new_title;
+                }
+            }
+            app_state.normalize_selection();
+            terminal.try_draw(|frame| ui::draw(frame, app_state))?;
+            if let Some(text) = app_state.clipboard_pending.take() {
+                let copied = system_clipboard.as_mut().is_some_and(|clipboard| clipboard.set_text(text.as_str()).is_ok());
+                if !copied {
+                    execute!(terminal.backend_mut(), Print(clipboard::osc52(&text)))?;
+                }
+            }
+            if app_state.edit_config_pending {
+                app_state.edit_config_pending = false;
+                // The stream reads from the terminal in the background, it would take the editor's keys
+                drop(events);
+                edit_config(terminal, app_state)?;
+                events = EventStream::new();
+                cursor_screen = None;
                 continue;
             }
-            match app_state.current_screen {
-                CurrentScreen::Main => match key.code {
-                    KeyCode::Char('a') => {
-                        // Add new item
-                        app_state.current_screen = CurrentScreen::Add;
-                    }
-                    KeyCode::Enter => {
-                        // Edit selected
-                        if !app_state.items.is_empty() {
-                            if let Some(sel_index) = app_state.todo_list_state.selected() {
-                                if let Some(e) = Some(app_state.items[sel_index].clone()) {
-                                    app_state.input = e.description.clone().into();
-                                    app_state.currently_editing = Some(e);
-                                }
-                                app_state.current_screen = CurrentScreen::Edit;
-                            }
-                        }
-                    }
-                    KeyCode::Char('q') => {
-                        // Quit
-                        app_state.current_screen = CurrentScreen::Exit;
-                    }
-                    KeyCode::Char(' ') => {
-                        // Mark selected
-                        if !app_state.items.is_empty() {
-                            if let Some(sel_index) = app_state.todo_list_state.selected() {
-                                app_state.items[sel_index].done = !app_state.items[sel_index].done;
-                            }
-                        }
-                    }
-                    KeyCode::Up => {
-                        app_state.todo_list_state.select_previous();
-                    }
-                    KeyCode::Down => {
-                        app_state.todo_list_state.select_next();
-                    }
+            if let Some(handle) = saving.take_if(|handle: &mut JoinHandle<_>| handle.is_finished()) {
+                if let Ok(Err(e)) = handle.await {
+                    app_state.flash(fill(&app_state.strings.autosave_failed, &[("error", &e)]));
+                }
+            }
+            if let Some(key) = app_state.next_macro_key() {
+                app_state.handle_key_event(key);
+                continue;
+            }
+            // Wake up regularly so reminders fire while no key is pressed, and often enough to
+            // animate the spinner while something runs in the background
+            let mut timeout = match app_state.background_op {
+                Some(_) => app_state.config.spinner_interval(),
+                None => Duration::from_secs(1),
+            };
+            if let Some((_, until)) = &app_state.startup_message {
+                timeout = timeout.min(until.saturating_duration_since(Instant::now()));
+            }
+            let tick = pin!(autosave_timer.tick());
+            match tokio::time::timeout(timeout, future::select(events.next(), tick)).await {
+                Ok(Either::Left((Some(event), _))) => match event? {
+                    Event::Key(key) => app_state.handle_key_event(key),
+                    Event::Mouse(mouse) => app_state.handle_mouse_event(mouse),
+                    Event::Paste(text) => app_state.handle_paste(&text),
                     _ => {}
                 },
-                CurrentScreen::Edit => match key.code {
-                    KeyCode::Esc => {
-                        app_state.current_screen = CurrentScreen::Main;
-                    }
-                    KeyCode::Enter => {
-                        if let Some(sel_index) = app_state.todo_list_state.selected() {
-                            if let Some(ce) = &app_state.currently_editing {
-                                let desc = app_state.input.value_and_reset();
-                                app_state.replace(TodoItem { done: ce.done, description: desc }, sel_index);
-                            }
+                Ok(Either::Left((None, _))) => break,
+                Ok(Either::Right(_)) => {
+                    // The session too, so a crash doesn't lose it; there is nothing to keep while locked
+                    if app_state.locked_file.is_none() {
+                        if let Err(e) = app_state.save_session() {
+                            app_state.flash(fill(&app_state.strings.config_save_failed, &[("error", &e)]));
                         }
-                        app_state.current_screen = CurrentScreen::Main;
-                    }
-                    _ => {
-                        app_state.input.handle_event(&evt);
-                    }
-                },
-                CurrentScreen::Add => match key.code {
-                    KeyCode::Esc => {
-                        app_state.current_screen = CurrentScreen::Main;
-                    }
-                    KeyCode::Enter => {
-                        app_state.items.push(TodoItem {
-                            done: false,
-                            description: app_state.input.value_and_reset(),
-                        });
-                        app_state.current_screen = CurrentScreen::Main;
                     }
-                    _ => {
-                        app_state.input.handle_event(&evt);
+                    if saving.is_none() {
+                        saving = autosave(app_state, data_path, password);
                     }
-                },
+                }
                 _ => {}
-            };
-        }
-        match app_state.current_screen {
-            CurrentScreen::Add => {
-                terminal.draw(|frame| {
-                    let _ = add_ui(frame, app_state);
-                })?;
-            }
-            CurrentScreen::Edit => {
-                terminal.draw(|frame| {
-                    let _ = edit_ui(frame, app_state);
-                })?;
             }
-            CurrentScreen::Main => {
-                terminal.draw(|frame| {
-                    let _ = main_ui(frame, app_state);
-                })?;
-            }
-            CurrentScreen::Exit => break,
+            app_state.tick();
+        }
+        // Waited for, or it could write older lists after the save on exit
+        if let Some(handle) = saving.take() {
+            let _ = handle.await;
+        }
+        // Saved while the TUI is still up so a failure shows in the status bar. After a failure
+        // or a signal main tries once more
+        let Some(path) = data_path.filter(|_| !save_failed && !signals.received() && !app_state.load_failed) else {
+            return Ok(false);
         };
+        let password = password.or(app_state.password.as_deref());
+        if app_state.config.encrypt && password.is_none() {
+            return Ok(false);
+        }
+        match save_lists(app_state, path, password.map(str::to_owned)) {
+            Ok(()) => return Ok(true),
+            Err(e) => {
+                app_state.flash(fill(&app_state.strings.exit_save_failed, &[("error", &e)]));
+                app_state.current_screen = CurrentScreen::Main;
+                save_failed = true;
+            }
+        }
     }
-    Ok(true)
-}
-
-fn main_ui(frame: &mut Frame, app_state: &mut AppState) -> io::Result<()> {
-    let block = Block::bordered()
-        .border_type(ratatui::widgets::BorderType::Rounded)
-        .border_style(Style::new().fg(SLATE.c500))
-        .title(Line::from("TODO").centered().white());
-    let items: Vec<ListItem> = app_state
-        .items
-        .iter()
-        .map(|todo_item| {
-            ListItem::from(todo_item)
-        })
-        .collect();
-    let lis = List::new(items)
-        .highlight_style(Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD))
-        .block(block);
-    StatefulWidget::render(
-        lis,
-        frame.area(),
-        frame.buffer_mut(),
-        &mut app_state.todo_list_state,
-    );
-    Ok(())
-}
-
-fn edit_ui(frame: &mut Frame, app_state: &mut AppState) -> io::Result<()> {
-    if let Some(_edit_item) = &app_state.currently_editing {
-        let area = Rect::new(0, 0, frame.area().width.max(3) - 3, 3);
-        let scroll = app_state.input.visual_scroll(area.width as usize);
-        let input = Paragraph::new(app_state.input.value())
-            .style(Style::default())
-            .scroll((0, scroll as u16))
-            .block(Block::bordered().title("Edit item"));
-        frame.render_widget(input, area);
-        // Ratatui hides the cursor unless it's explicitly set. Position the  cursor past the
-        // end of the input text and one line down from the border to the input line
-        let x = app_state.input.visual_cursor().max(scroll) - scroll + 1;
-        frame.set_cursor_position((area.x + x as u16, area.y + 1));
-    } else {
-        app_state.current_screen = CurrentScreen::Main;
-    }
-    Ok(())
-}
-
-fn add_ui(frame: &mut Frame, app_state: &mut AppState) -> io::Result<()> {
-    let area = Rect::new(0, 0, frame.area().width.max(3) - 3, 3);
-    let scroll = app_state.input.visual_scroll(area.width as usize);
-    let input = Paragraph::new(app_state.input.value())
-        .style(Style::default())
-        .scroll((0, scroll as u16))
-        .block(Block::bordered().title("New item"));
-    frame.render_widget(input, area);
-    // Ratatui hides the cursor unless it's explicitly set. Position the  cursor past the
-    // end of the input text and one line down from the border to the input line
-    let x = app_state.input.visual_cursor().max(scroll) - scroll + 1;
-    frame.set_cursor_position((area.x + x as u16, area.y + 1));
-
-    Ok(())
 }
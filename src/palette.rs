@@ -0,0 +1,77 @@
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Command {
+    RemoveDuplicates,
+    SearchReplace,
+    MergeLists,
+    SplitList,
+    WeeklyReview,
+    StaleItems,
+    MoveToParent,
+    Import,
+    GithubImport,
+    EditConfig,
+    ChangeTheme,
+    UndoHistory,
+    SaveFilter,
+    LoadFilter,
+    Pomodoro,
+    CopyShareLink,
+    ArchiveCompleted,
+    Archive,
+}
+
+impl Command {
+    pub const ALL: &'static [Command] = &[
+        Command::RemoveDuplicates,
+        Command::SearchReplace,
+        Command::MergeLists,
+        Command::SplitList,
+        Command::WeeklyReview,
+        Command::StaleItems,
+        Command::MoveToParent,
+        Command::Import,
+        Command::GithubImport,
+        Command::EditConfig,
+        Command::ChangeTheme,
+        Command::UndoHistory,
+        Command::SaveFilter,
+        Command::LoadFilter,
+        Command::Pomodoro,
+        Command::CopyShareLink,
+        Command::ArchiveCompleted,
+        Command::Archive,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Command::RemoveDuplicates => "Remove duplicates",
+            Command::SearchReplace => "Search and replace",
+            Command::MergeLists => "Merge lists…",
+            Command::SplitList => "Split list by filter…",
+            Command::WeeklyReview => "Weekly review",
+            Command::StaleItems => "Review stale items",
+            Command::MoveToParent => "Move to parent…",
+            Command::Import => "Import file…",
+            Command::GithubImport => "Import GitHub issues…",
+            Command::EditConfig => "Edit configuration",
+            Command::ChangeTheme => "Change theme",
+            Command::UndoHistory => "Undo history…",
+            Command::SaveFilter => "Save current filter…",
+            Command::LoadFilter => "Load filter…",
+            Command::Pomodoro => "Start or stop a pomodoro",
+            Command::CopyShareLink => "Copy as share link",
+            Command::ArchiveCompleted => "Archive done items",
+            Command::Archive => "Show archive",
+        }
+    }
+}
+
+// Commands whose label contains the query, case-insensitive
+pub fn matching(query: &str) -> Vec<Command> {
+    let query = query.to_lowercase();
+    Command::ALL
+        .iter()
+        .copied()
+        .filter(|command| command.label().to_lowercase().contains(&query))
+        .collect()
+}
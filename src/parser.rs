@@ -0,0 +1,50 @@
+use crate::error::AppError;
+use crate::item::{Priority, TodoItem};
+use chrono::NaiveDate;
+
+// Shortcuts in the description of a new item: !low, !medium and !high set the priority, the
+// last one wins, #word adds a tag and @YYYY-MM-DD sets the due date. Other ! words and # ones
+// that don't start with a letter, like #123, stay in the description
+pub fn parse_inline_syntax(raw: &str) -> Result<TodoItem, AppError> {
+    let mut priority = Priority::None;
+    let mut due = None;
+    let mut tags: Vec<String> = vec![];
+    let mut words = vec![];
+    for word in raw.split_whitespace() {
+        if let Some(p) = word.strip_prefix('!').and_then(Priority::parse).filter(|&p| p != Priority::None) {
+            priority = p;
+        } else if let Some(tag) = word.strip_prefix('#').filter(|tag| tag.starts_with(char::is_alphabetic)) {
+            if !tags.iter().any(|t| t == tag) {
+                tags.push(tag.to_owned());
+            }
+        } else if let Some(date) = word.strip_prefix('@').filter(|date| date.starts_with(|c: char| c.is_ascii_digit())) {
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| AppError::InvalidInput(format!("invalid due date \"{}\", use @YYYY-MM-DD", date)))?;
+            if due.replace(date).is_some() {
+                return Err(AppError::InvalidInput("only one @date per item".to_owned()));
+            }
+        } else {
+            words.push(word);
+        }
+    }
+    let mut builder = TodoItem::builder().description(&words.join(" ")).priority(priority).tags(tags);
+    if let Some(due) = due {
+        builder = builder.due(due);
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortcuts_are_taken_out_of_the_description() {
+        let item = parse_inline_syntax("Call #work the bank !low @2024-06-01 about #123 !high !soon").unwrap();
+        assert_eq!(item.description, "Call the bank about #123 !soon");
+        assert_eq!(item.priority, Priority::High);
+        assert_eq!(item.tags, ["work"]);
+        assert_eq!(item.due_date, NaiveDate::from_ymd_opt(2024, 6, 1));
+        assert!(parse_inline_syntax("Call @2024-06-01 @2024-06-02").is_err());
+    }
+}
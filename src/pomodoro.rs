@@ -0,0 +1,87 @@
+use crate::app::{show_notification, AppState};
+use crate::strings::fill;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+pub const POMODORO_MINUTES: u64 = 25;
+// How long the indicator of a finished pomodoro flashes before it goes away
+const EXPIRED_FOR: Duration = Duration::from_secs(5);
+
+// A work interval on one item, started from the command palette
+pub struct Pomodoro {
+    pub item_id: Uuid,
+    pub started_at: Instant,
+    pub ends_at: Instant,
+    notified: bool,
+}
+
+impl Pomodoro {
+    pub fn new(item_id: Uuid) -> Self {
+        let started_at = Instant::now();
+        Self {
+            item_id,
+            started_at,
+            ends_at: started_at + Duration::from_secs(POMODORO_MINUTES * 60),
+            notified: false,
+        }
+    }
+
+    pub fn expired(&self) -> bool {
+        Instant::now() >= self.ends_at
+    }
+
+    // Minutes and seconds left, rounded up so it reads 25:00 at the start and 00:00 at the end
+    pub fn countdown(&self) -> String {
+        let left = self.ends_at.saturating_duration_since(Instant::now());
+        let seconds = left.as_millis().div_ceil(1000);
+        format!("{:02}:{:02}", seconds / 60, seconds % 60)
+    }
+
+    pub fn percent_done(&self) -> u128 {
+        let total = self.ends_at.duration_since(self.started_at).as_millis().max(1);
+        (self.started_at.elapsed().as_millis() * 100 / total).min(100)
+    }
+
+    // Every other second once it has run out
+    pub fn flash_on(&self) -> bool {
+        Instant::now().saturating_duration_since(self.ends_at).as_secs().is_multiple_of(2)
+    }
+}
+
+impl AppState {
+    // Start one on the selected item, or stop the one running on it
+    pub fn toggle_pomodoro(&mut self) {
+        let Some(item) = self.selected_item_index().and_then(|i| self.get(i)) else {
+            return;
+        };
+        let id = item.id;
+        if self.pomodoro.as_ref().is_some_and(|pomodoro| pomodoro.item_id == id && !pomodoro.expired()) {
+            self.pomodoro = None;
+            self.flash(self.strings.pomodoro_stopped.clone());
+        } else {
+            self.pomodoro = Some(Pomodoro::new(id));
+            self.flash(fill(&self.strings.pomodoro_started, &[("minutes", &POMODORO_MINUTES)]));
+        }
+    }
+
+    // Called every tick: notify once when time is up, clear it after the flashing or when
+    // the item is deleted
+    pub fn update_pomodoro(&mut self) {
+        let Some(pomodoro) = &mut self.pomodoro else {
+            return;
+        };
+        let (id, due) = (pomodoro.item_id, pomodoro.expired() && !pomodoro.notified);
+        let over = Instant::now() >= pomodoro.ends_at + EXPIRED_FOR;
+        pomodoro.notified |= due;
+        let item = self.find_item(id).and_then(|(list, index)| self.lists[list].get(index));
+        let (Some(item), false) = (item, over) else {
+            self.pomodoro = None;
+            return;
+        };
+        if due {
+            let description = item.description.clone();
+            show_notification("todo-tui pomodoro", description.clone());
+            self.flash(fill(&self.strings.pomodoro_done, &[("description", &description)]));
+        }
+    }
+}
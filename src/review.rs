@@ -0,0 +1,162 @@
+use crate::app::{AppState, CurrentScreen, INBOX_LIST};
+use crate::strings::fill;
+use crate::webhook::WebhookEvent;
+use chrono::{Duration, Local, NaiveDate};
+
+// The weekly review wizard, one step after another
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReviewStep {
+    Completed,
+    Overdue,
+    Inbox,
+    EmptyLists,
+}
+
+impl ReviewStep {
+    pub const ALL: [ReviewStep; 4] = [
+        ReviewStep::Completed,
+        ReviewStep::Overdue,
+        ReviewStep::Inbox,
+        ReviewStep::EmptyLists,
+    ];
+}
+
+impl AppState {
+    pub fn review_step(&self) -> ReviewStep {
+        ReviewStep::ALL[self.review_step]
+    }
+
+    // (list, storage index) of the items the current step is about
+    pub fn review_items(&self) -> Vec<(usize, usize)> {
+        let today = Local::now().date_naive();
+        let week_ago = Local::now() - Duration::days(7);
+        let step = self.review_step();
+        let mut rows = vec![];
+        for (l, list) in self.lists.iter().enumerate() {
+            for i in 0..list.len() {
+                let Some(item) = list.get(i) else { continue };
+                let keep = match step {
+                    ReviewStep::Completed => item.completed_at.is_some_and(|at| at >= week_ago),
+                    ReviewStep::Overdue => !item.done && item.due_date.is_some_and(|due| due < today),
+                    ReviewStep::Inbox => list.name == INBOX_LIST && !item.done,
+                    ReviewStep::EmptyLists => false,
+                };
+                if keep {
+                    rows.push((l, i));
+                }
+            }
+        }
+        rows
+    }
+
+    // Lists without any items, the Inbox is expected to be empty and left out
+    pub fn empty_lists(&self) -> Vec<usize> {
+        (0..self.lists.len())
+            .filter(|&l| self.lists[l].name != INBOX_LIST && self.lists[l].is_empty())
+            .collect()
+    }
+
+    pub fn start_weekly_review(&mut self) {
+        self.review_step = 0;
+        self.review_done = [false; 4];
+        self.review_editing_due = false;
+        self.review_state.select(Some(0));
+        self.current_screen = CurrentScreen::WeeklyReview;
+    }
+
+    pub fn review_move(&mut self, forward: bool) {
+        self.review_step = if forward {
+            (self.review_step + 1).min(ReviewStep::ALL.len() - 1)
+        } else {
+            self.review_step.saturating_sub(1)
+        };
+        self.review_state.select(Some(0));
+    }
+
+    // Mark the step done and go on, the last one finishes the review
+    pub fn finish_review_step(&mut self) {
+        self.review_done[self.review_step] = true;
+        if self.review_done.iter().all(|&done| done) {
+            self.config.last_weekly_review = Some(Local::now().date_naive());
+            if let Err(e) = self.save_config() {
+                self.flash(fill(&self.strings.config_save_failed, &[("error", &e)]));
+            } else {
+                self.flash(self.strings.great_work.clone());
+            }
+            self.current_screen = CurrentScreen::Main;
+        } else {
+            self.review_move(true);
+        }
+    }
+
+    pub fn review_selected(&self) -> Option<(usize, usize)> {
+        let row = self.review_state.selected()?;
+        self.review_items().get(row).copied()
+    }
+
+    pub fn reschedule(&mut self, list: usize, index: usize, due: Option<NaiveDate>) {
+        self.checkpoint("Reschedule");
+        if let Some(item) = self.lists[list].get_mut(index) {
+            item.due_date = due;
+        }
+    }
+}
+
+impl AppState {
+    // (list, storage index) of the incomplete items older than stale_item_days, oldest first
+    pub fn stale_items(&self) -> Vec<(usize, usize)> {
+        let cutoff = Local::now() - Duration::days(self.config.stale_item_days as i64);
+        let mut rows = vec![];
+        for (l, list) in self.lists.iter().enumerate() {
+            for i in 0..list.len() {
+                let Some(item) = list.get(i) else { continue };
+                if !item.done && item.created_at < cutoff && !self.stale_dismissed.contains(&item.id) {
+                    rows.push((l, i));
+                }
+            }
+        }
+        rows.sort_by_key(|&(l, i)| self.lists[l].get(i).map(|item| item.created_at));
+        rows
+    }
+
+    pub fn start_stale_review(&mut self) {
+        self.stale_dismissed.clear();
+        self.stale_state.select(Some(0));
+        self.current_screen = CurrentScreen::StaleItemsReview;
+    }
+
+    pub fn stale_selected(&self) -> Option<(usize, usize)> {
+        let row = self.stale_state.selected()?;
+        self.stale_items().get(row).copied()
+    }
+
+    // Start the item's aging over as if it was added today
+    pub fn snooze_stale(&mut self, list: usize, index: usize) {
+        self.checkpoint("Snooze");
+        if let Some(item) = self.lists[list].get_mut(index) {
+            item.created_at = Local::now();
+        }
+    }
+
+    pub fn delete_stale(&mut self, list: usize, index: usize) {
+        self.checkpoint("Delete");
+        let item = self.lists[list].remove(index);
+        self.notify(WebhookEvent::ItemDeleted, &item);
+    }
+
+    pub fn dismiss_stale(&mut self, list: usize, index: usize) {
+        if let Some(item) = self.lists[list].get(index) {
+            self.stale_dismissed.insert(item.id);
+        }
+    }
+
+    // Keep the selection on a row after one was taken out, the review ends with the last one
+    pub fn after_stale_action(&mut self) {
+        let len = self.stale_items().len();
+        if len == 0 {
+            self.current_screen = CurrentScreen::Main;
+        } else if self.stale_state.selected().is_some_and(|row| row >= len) {
+            self.stale_state.select(Some(len - 1));
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+
+// Weak fuzzy matches, e.g. a few scattered letters, are left out
+pub const MIN_FUZZY_SCORE: i64 = 10;
+
+pub struct Matcher {
+    fuzzy: Option<SkimMatcherV2>,
+}
+
+impl Matcher {
+    pub fn new(fuzzy: bool) -> Self {
+        Self {
+            fuzzy: fuzzy.then(|| SkimMatcherV2::default().ignore_case()),
+        }
+    }
+
+    // Score and char positions of the query in the text, None when it doesn't match
+    pub fn find(&self, query: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+        match &self.fuzzy {
+            Some(matcher) => matcher
+                .fuzzy_indices(text, query)
+                .filter(|(score, _)| *score >= MIN_FUZZY_SCORE),
+            None => {
+                let start = text.to_lowercase().find(&query.to_lowercase())?;
+                let first = text.to_lowercase()[..start].chars().count();
+                Some((0, (first..first + query.chars().count()).collect()))
+            }
+        }
+    }
+}
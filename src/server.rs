@@ -0,0 +1,85 @@
+use crate::app::AppState;
+use crate::cli::Mutation;
+use crate::config::Config;
+use crate::error::AppError;
+use crate::item::{TodoItem, TodoItemBuilder};
+use crate::storage::{self, FileBackend, StorageBackend};
+use serde_json::{json, Value};
+use std::io;
+use std::path::Path;
+use tiny_http::{Header, Method, Request, Response, Server};
+use uuid::Uuid;
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+// Answer requests until killed. Every request reloads the save file under the lock, and is
+// refused with 409 while a TUI has the file open, so neither overwrites the other's changes
+pub fn serve(port: u16, path: &Path, password: Option<&str>, config: &Config) -> io::Result<()> {
+    let server = Server::http(("127.0.0.1", port)).map_err(io::Error::other)?;
+    eprintln!("Listening on http://127.0.0.1:{}", port);
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let response = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => handle(&request, &body, path, password, config),
+            Err(e) => Err(e.into()),
+        };
+        let (status, value) = match response {
+            Ok(response) => response,
+            Err(e) => (error_status(&e), json!({ "error": e.to_string() })),
+        };
+        let body = if value.is_null() { String::new() } else { value.to_string() };
+        let header = Header::from_bytes("Content-Type", "application/json").expect("valid header");
+        let response = Response::from_string(body).with_status_code(status).with_header(header);
+        let _ = request.respond(response);
+    }
+    Ok(())
+}
+
+fn handle(
+    request: &Request,
+    body: &str,
+    path: &Path,
+    password: Option<&str>,
+    config: &Config,
+) -> Result<(u16, Value), AppError> {
+    let _lock = storage::lock(path)?;
+    let mut app = AppState::with_config(config.clone());
+    let mut backend = FileBackend::new(path.to_path_buf(), password.map(str::to_owned));
+    app.restore(backend.load()?);
+
+    let url = request.url().split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = url.split('/').filter(|s| !s.is_empty()).collect();
+    let response = match (request.method(), segments.as_slice()) {
+        (Method::Get, ["items"]) => return Ok((200, json!(app.all_items().collect::<Vec<_>>()))),
+        (Method::Get, ["items", id]) => return Ok((200, json!(app.item_by_id(id)?))),
+        (Method::Post, ["items"]) => {
+            let item = TodoItemBuilder::from(serde_json::from_str::<TodoItem>(body)?).id(Uuid::new_v4()).build()?;
+            let value = json!(item);
+            app.add_item(item);
+            (201, value)
+        }
+        (Method::Put, ["items", id]) => {
+            (200, json!(app.update_item(id, serde_json::from_str(body)?)?))
+        }
+        (Method::Delete, ["items", id]) => {
+            app.apply_mutation(&Mutation::Delete(id.to_string()))?;
+            (204, Value::Null)
+        }
+        (Method::Post, ["items", id, "done"]) => {
+            app.apply_mutation(&Mutation::MarkDone(id.to_string()))?;
+            (200, json!(app.item_by_id(id)?))
+        }
+        _ => return Err(AppError::NotFound(format!("no route for {} {}", request.method(), url))),
+    };
+    backend.save(&app.save_file())?;
+    Ok(response)
+}
+
+fn error_status(error: &AppError) -> u16 {
+    match error {
+        AppError::NotFound(_) => 404,
+        AppError::InvalidInput(_) | AppError::Parse(_) => 400,
+        AppError::Io(e) if e.kind() == io::ErrorKind::WouldBlock => 409,
+        AppError::Io(_) | AppError::Crypto(_) => 500,
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(feature = "spell-check")]
+use hunspell_rs::{CheckResult, Hunspell};
+
+#[cfg(feature = "spell-check")]
+const DICTIONARY_DIRS: &[&str] = &["/usr/share/hunspell", "/usr/share/myspell", "/usr/share/myspell/dicts"];
+
+// Checks nothing unless built with the spell-check feature and a dictionary is installed
+pub struct SpellChecker {
+    #[cfg(feature = "spell-check")]
+    hunspell: Option<Hunspell>,
+}
+
+impl SpellChecker {
+    #[cfg(feature = "spell-check")]
+    pub fn new() -> Self {
+        // LANG=en_GB.UTF-8 picks en_GB.aff/en_GB.dic
+        let language = std::env::var("LANG")
+            .ok()
+            .and_then(|lang| lang.split('.').next().map(str::to_owned))
+            .filter(|lang| lang.contains('_'))
+            .unwrap_or_else(|| "en_US".to_owned());
+        let hunspell = DICTIONARY_DIRS.iter().find_map(|dir| {
+            let aff = format!("{}/{}.aff", dir, language);
+            let dic = format!("{}/{}.dic", dir, language);
+            let found = std::path::Path::new(&aff).exists() && std::path::Path::new(&dic).exists();
+            found.then(|| Hunspell::new(&aff, &dic))
+        });
+        Self { hunspell }
+    }
+
+    #[cfg(not(feature = "spell-check"))]
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    #[cfg(feature = "spell-check")]
+    pub fn is_available(&self) -> bool {
+        self.hunspell.is_some()
+    }
+
+    #[cfg(not(feature = "spell-check"))]
+    pub fn is_available(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "spell-check")]
+    pub fn is_correct(&self, word: &str) -> bool {
+        self.hunspell
+            .as_ref()
+            .is_none_or(|hunspell| hunspell.check(word) == CheckResult::FoundInDictionary)
+    }
+
+    #[cfg(not(feature = "spell-check"))]
+    pub fn is_correct(&self, _word: &str) -> bool {
+        true
+    }
+
+    #[cfg(feature = "spell-check")]
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        self.hunspell.as_ref().map_or(vec![], |hunspell| hunspell.suggest(word))
+    }
+
+    #[cfg(not(feature = "spell-check"))]
+    pub fn suggest(&self, _word: &str) -> Vec<String> {
+        vec![]
+    }
+
+    // Misspelled words in the text, each once
+    pub fn misspelled(&self, text: &str) -> Vec<String> {
+        let mut misspelled: Vec<String> = vec![];
+        for (_, word) in words(text) {
+            if !self.is_correct(word) && !misspelled.iter().any(|w| w == word) {
+                misspelled.push(word.to_owned());
+            }
+        }
+        misspelled
+    }
+}
+
+impl Default for SpellChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Words with the char index they start at, URLs and numbers are left out
+pub fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut words = vec![];
+    let mut start = None;
+    for (chars, (offset, c)) in text.char_indices().chain([(text.len(), ' ')]).enumerate() {
+        let in_word = c.is_alphabetic() || (c == '\'' && start.is_some());
+        match (start, in_word) {
+            (None, true) => start = Some((offset, chars)),
+            (Some((begin, first)), false) => {
+                let word = text[begin..offset].trim_end_matches('\'');
+                // Part of a URL, path or identifier rather than prose
+                let glued = matches!(c, '/' | '.' | ':' | '_' | '@') && offset + 1 < text.len()
+                    && !text[offset + 1..].starts_with(' ');
+                if !glued && !text[..begin].ends_with(['/', '.', '_', '@', ':']) {
+                    words.push((first, word));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words.into_iter()
+}
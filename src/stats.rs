@@ -0,0 +1,31 @@
+use crate::item::TodoItem;
+use chrono::{Duration, NaiveDate, Weekday};
+use std::collections::HashSet;
+
+// Consecutive days ending today with at least one item completed
+pub fn current_streak<'a>(items: impl Iterator<Item = &'a TodoItem>, today: NaiveDate) -> u32 {
+    let days: HashSet<NaiveDate> = items
+        .filter_map(|item| item.completed_at)
+        .map(|at| at.date_naive())
+        .collect();
+    let mut streak = 0;
+    let mut day = today;
+    while days.contains(&day) {
+        streak += 1;
+        day -= Duration::days(1);
+    }
+    streak
+}
+
+// Items completed in the week containing `today`, weeks starting on `first_day`
+pub fn completed_this_week<'a>(
+    items: impl Iterator<Item = &'a TodoItem>,
+    today: NaiveDate,
+    first_day: Weekday,
+) -> usize {
+    let week = today.week(first_day);
+    items
+        .filter_map(|item| item.completed_at)
+        .filter(|at| week.days().contains(&at.date_naive()))
+        .count()
+}
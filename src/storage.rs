@@ -0,0 +1,254 @@
+use crate::app::TodoList;
+use crate::crypto;
+use crate::error::AppError;
+use crate::item::TodoItem;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveFile {
+    pub lists: Vec<TodoList>,
+    // Items taken out of the lists with the archive, restorable from Ctrl+A
+    pub archived: Vec<TodoItem>,
+}
+
+// Where a save file is read from and written to
+pub trait StorageBackend {
+    fn load(&self) -> Result<SaveFile, AppError>;
+    fn save(&mut self, save_file: &SaveFile) -> Result<(), AppError>;
+}
+
+// The data file, encrypted when there is a password. Callers hold the lock
+pub struct FileBackend {
+    path: PathBuf,
+    password: Option<String>,
+    backup_count: usize,
+}
+
+impl FileBackend {
+    pub fn new(path: PathBuf, password: Option<String>) -> Self {
+        Self { path, password, backup_count: 0 }
+    }
+
+    // Keep this many copies of the previous saves next to the file
+    pub fn backups(mut self, count: usize) -> Self {
+        self.backup_count = count;
+        self
+    }
+
+    // Whether the file already holds these lists. The encrypted one is compared decrypted, its
+    // bytes differ on every save
+    fn unchanged(&self, save_file: &SaveFile) -> Result<bool, AppError> {
+        let Ok(data) = fs::read(&self.path) else {
+            return Ok(false);
+        };
+        Ok(match &self.password {
+            Some(password) => crypto::decrypt(&data, password).ok() == Some(serde_json::to_vec(save_file)?),
+            None => data == serde_json::to_string_pretty(save_file)?.as_bytes(),
+        })
+    }
+}
+
+impl StorageBackend for FileBackend {
+    fn load(&self) -> Result<SaveFile, AppError> {
+        match &self.password {
+            Some(password) => load_encrypted(&self.path, password),
+            None => load(&self.path),
+        }
+    }
+
+    fn save(&mut self, save_file: &SaveFile) -> Result<(), AppError> {
+        // Saving the same lists again would only push an older backup out
+        if self.unchanged(save_file)? {
+            return Ok(());
+        }
+        rotate_backups(&self.path, self.backup_count)?;
+        match &self.password {
+            Some(password) => save_encrypted(&self.path, save_file, password),
+            None => save(&self.path, save_file),
+        }
+    }
+}
+
+// Keeps the save file in memory, for tests
+#[derive(Default)]
+pub struct InMemoryBackend {
+    lists: Vec<TodoList>,
+    archived: Vec<TodoItem>,
+}
+
+impl InMemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StorageBackend for InMemoryBackend {
+    fn load(&self) -> Result<SaveFile, AppError> {
+        Ok(SaveFile { lists: self.lists.clone(), archived: self.archived.clone() })
+    }
+
+    fn save(&mut self, save_file: &SaveFile) -> Result<(), AppError> {
+        self.lists = save_file.lists.clone();
+        self.archived = save_file.archived.clone();
+        Ok(())
+    }
+}
+
+// A leading ~/ is the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, home)) => home.join(rest),
+        None => Path::new(path).to_path_buf(),
+    }
+}
+
+pub fn data_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("todo-tui").join("todos.json"))
+}
+
+// A missing file is a fresh start, not an error
+pub fn load(path: &Path) -> Result<SaveFile, AppError> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(serde_json::from_str(&text)?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(SaveFile::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn save(path: &Path, save_file: &SaveFile) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(save_file)?;
+    save_atomic(path, json.as_bytes())?;
+    Ok(())
+}
+
+// Long enough for another load or save to finish
+const LOCK_WAIT: Duration = Duration::from_secs(2);
+
+// Held while reading or writing the save file, and by a TUI for as long as it runs, so
+// --serve and the command line options can't change the file under it. Released when
+// dropped. Waits a little for other holders, then fails with WouldBlock
+pub fn lock(path: &Path) -> io::Result<fs::File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let file = fs::File::create(lock_path)?;
+    let deadline = Instant::now() + LOCK_WAIT;
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(file),
+            Err(fs::TryLockError::WouldBlock) if Instant::now() < deadline => thread::sleep(Duration::from_millis(50)),
+            Err(fs::TryLockError::WouldBlock) => {
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, "the lists are open in a running todo-tui"));
+            }
+            Err(fs::TryLockError::Error(e)) => return Err(e),
+        }
+    }
+}
+
+// Encrypted saves live next to the plain file with an .enc extension
+pub fn encrypted_path(path: &Path) -> PathBuf {
+    path.with_extension("enc")
+}
+
+pub fn load_encrypted(path: &Path, password: &str) -> Result<SaveFile, AppError> {
+    match fs::read(path) {
+        Ok(data) => Ok(serde_json::from_slice(&crypto::decrypt(&data, password)?)?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(SaveFile::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn save_encrypted(path: &Path, save_file: &SaveFile, password: &str) -> Result<(), AppError> {
+    let json = serde_json::to_vec(save_file)?;
+    save_atomic(path, &crypto::encrypt(&json, password)?)?;
+    Ok(())
+}
+
+// Copy the file to .bak1, shifting older copies up to .bak{count} and dropping the oldest
+pub fn rotate_backups(path: &Path, count: usize) -> io::Result<()> {
+    if count == 0 || !path.exists() {
+        return Ok(());
+    }
+    let backup = |n: usize| {
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(format!(".bak{}", n));
+        PathBuf::from(backup)
+    };
+    match fs::remove_file(backup(count)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    for n in (1..count).rev() {
+        match fs::rename(backup(n), backup(n + 1)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    fs::copy(path, backup(1))?;
+    Ok(())
+}
+
+// Write to a sibling temp file and rename it over the target so a crash never leaves a
+// half-written file behind
+pub fn save_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backups_rotate_up_to_the_count() {
+        for count in [0, 1, 5] {
+            let dir = std::env::temp_dir().join(format!("todo-tui-backups-{}", uuid::Uuid::new_v4()));
+            fs::create_dir_all(&dir).unwrap();
+            let path = dir.join("todos.json");
+            for save in 0..7 {
+                rotate_backups(&path, count).unwrap();
+                fs::write(&path, save.to_string()).unwrap();
+            }
+            let backups: Vec<String> = (1..=9)
+                .map_while(|n| fs::read_to_string(dir.join(format!("todos.json.bak{}", n))).ok())
+                .collect();
+            // The newest backup holds the save before the current one
+            let expected: Vec<String> = (0..6).rev().take(count).map(|save| save.to_string()).collect();
+            assert_eq!(backups, expected);
+            fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn saving_the_same_lists_keeps_the_backups() {
+        let dir = std::env::temp_dir().join(format!("todo-tui-unchanged-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("todos.json");
+        for password in [None, Some("secret".to_owned())] {
+            fs::create_dir_all(&dir).unwrap();
+            let mut backend = FileBackend::new(path.clone(), password).backups(3);
+            let mut save_file = SaveFile::default();
+            backend.save(&save_file).unwrap();
+            backend.save(&save_file).unwrap();
+            assert!(!dir.join("todos.json.bak1").exists());
+            save_file.archived.push(TodoItem::from("A"));
+            backend.save(&save_file).unwrap();
+            backend.save(&save_file).unwrap();
+            assert!(dir.join("todos.json.bak1").exists());
+            assert!(!dir.join("todos.json.bak2").exists());
+            fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+const EN: &str = include_str!("../locales/en.toml");
+
+// Every user-visible text of the TUI, loaded from the locale named by Config::language
+#[derive(Clone, Debug, Deserialize)]
+pub struct Strings {
+    pub new_item: String,
+    pub edit_item: String,
+    pub remind_me_at: String,
+    pub new_comment: String,
+    pub notes_title: String,
+    pub password: String,
+    pub statistics: String,
+    pub commands: String,
+    pub search_and_replace: String,
+    pub find: String,
+    pub replace: String,
+    pub color_label: String,
+    pub templates: String,
+    pub search_all_lists: String,
+    pub global_search_results: String,
+    pub new_list_name: String,
+    pub merge_into: String,
+    pub comments: String,
+    pub lists: String,
+    pub github_repo: String,
+    pub import_file: String,
+    pub filter_name: String,
+    pub due_field: String,
+    pub priority_field: String,
+    pub tags_field: String,
+    pub move_to_position: String,
+    pub add_tag: String,
+    pub saved_filters: String,
+    pub undo_history: String,
+    pub sort_by: String,
+    pub filter_by_tag: String,
+    pub no_tags: String,
+    pub help: String,
+    pub marked_status: String,
+    pub confirm_delete_marked: String,
+    pub deleted_marked: String,
+    pub tag_visible: String,
+    pub theme_preview: String,
+    pub related: String,
+    pub link_item: String,
+    pub move_to_parent: String,
+    pub no_parent: String,
+    pub recently_done: String,
+    pub archive: String,
+    pub daily_prompt: String,
+    pub weekly_review: String,
+    pub stale_items: String,
+    pub timeline: String,
+    pub item_detail: String,
+    pub field_description: String,
+    pub field_status: String,
+    pub field_priority: String,
+    pub field_due: String,
+    pub field_tags: String,
+    pub field_created: String,
+    pub field_completed: String,
+    pub field_reminder: String,
+    pub field_color: String,
+    pub field_url: String,
+    pub field_parent: String,
+    pub field_id: String,
+    pub status_done: String,
+    pub status_pending: String,
+    pub no_due_items: String,
+    pub too_long_for_qr: String,
+    pub copied: String,
+    pub copied_share: String,
+    pub pomodoro_started: String,
+    pub pomodoro_stopped: String,
+    pub pomodoro_done: String,
+    pub pomodoro_status: String,
+    pub stale_age: String,
+    pub no_stale_items: String,
+    pub review_completed: String,
+    pub review_overdue: String,
+    pub review_inbox: String,
+    pub review_empty_lists: String,
+    pub nothing_to_review: String,
+    pub new_due_date: String,
+    pub nothing_done_recently: String,
+    pub archive_empty: String,
+    pub no_suggestions: String,
+    pub more_above: String,
+    pub more_below: String,
+    pub hidden: String,
+    pub inbox_prompt: String,
+    pub inbox_count: String,
+    pub duplicates: String,
+    pub confirm_dedup: String,
+    pub confirm_clear_color: String,
+    pub mark_all: String,
+    pub confirm_mark_all_done: String,
+    pub confirm_mark_all_undone: String,
+    pub clear_completed: String,
+    pub confirm_clear_completed: String,
+    pub notes: String,
+    pub confirm_discard_notes: String,
+    pub confirm_discard: String,
+    pub duplicate: String,
+    pub confirm_duplicate: String,
+    pub paste: String,
+    pub confirm_paste_multiple: String,
+    pub split_list: String,
+    pub confirm_split: String,
+    pub github_import: String,
+    pub confirm_github_import: String,
+    pub merge_lists: String,
+    pub confirm_merge: String,
+    pub delete_item: String,
+    pub confirm_delete: String,
+    pub priority: String,
+    pub due: String,
+    pub due_cleared: String,
+    pub tags: String,
+    pub created: String,
+    pub word_count: String,
+    pub note_length: String,
+    pub items_done: String,
+    pub completed_this_week: String,
+    pub current_streak: String,
+    pub longest_streak: String,
+    pub progress: String,
+    pub focus_keys: String,
+    pub note_editor_keys: String,
+    pub spell_check_keys: String,
+    pub recently_done_keys: String,
+    pub archive_keys: String,
+    pub theme_preview_keys: String,
+    pub undo_history_keys: String,
+    pub review_keys: String,
+    pub review_overdue_keys: String,
+    pub review_inbox_keys: String,
+    pub stale_items_keys: String,
+    pub timeline_keys: String,
+    pub item_detail_keys: String,
+    pub use_template: String,
+    pub main_keys: String,
+    pub edit_keys: String,
+    pub search_status: String,
+    pub due_today_status: String,
+    pub filter_status: String,
+    pub filtered_status: String,
+    pub pending_filter: String,
+    pub priority_filter: String,
+    pub due_today_filter: String,
+    pub overdue_filter: String,
+    pub fuzzy: String,
+    pub exact: String,
+    pub case_sensitive: String,
+    pub ignore_case: String,
+    pub export_keys: String,
+    pub undid: String,
+    pub nothing_to_undo: String,
+    pub redid: String,
+    pub nothing_to_redo: String,
+    pub reminder: String,
+    pub spell_check_unavailable: String,
+    pub no_misspelled_words: String,
+    pub no_completed_items: String,
+    pub exported_to: String,
+    pub export_failed: String,
+    pub cleared_items: String,
+    pub archived_items: String,
+    pub restored_item: String,
+    pub open_urls_disabled: String,
+    pub opening_in_browser: String,
+    pub open_failed: String,
+    pub replaced_occurrences: String,
+    pub split_needs_filter: String,
+    pub no_list_to_merge_into: String,
+    pub merged_lists: String,
+    pub removed_duplicates: String,
+    pub wrong_password: String,
+    pub sending_webhooks: String,
+    pub update_available: String,
+    pub recording_macro: String,
+    pub no_macro: String,
+    pub cannot_link_to_itself: String,
+    pub parent_cycle: String,
+    pub no_open_issues: String,
+    pub github_import_failed: String,
+    pub import_failed: String,
+    pub imported_items: String,
+    pub imported_issues: String,
+    pub tag_needs_filter: String,
+    pub tagged_items: String,
+    pub pasted_items: String,
+    pub config_reloaded: String,
+    pub config_not_reloaded: String,
+    pub config_save_failed: String,
+    pub editor_failed: String,
+    pub theme_saved: String,
+    pub autosave_failed: String,
+    pub exit_save_failed: String,
+    pub load_failed: String,
+    pub filter_saved: String,
+    pub no_saved_filters: String,
+    pub invalid_position: String,
+    pub invalid_date: String,
+    pub great_work: String,
+}
+
+impl Strings {
+    pub fn english() -> Strings {
+        toml::from_str(EN).expect("built-in en.toml is valid")
+    }
+
+    // Keys missing from the locale file fall back to English, a broken file is ignored
+    pub fn load(language: &str) -> Strings {
+        if language == "en" {
+            return Strings::english();
+        }
+        let Some(path) = locale_path(language) else {
+            return Strings::english();
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("Warning: could not read locale {}: {}", path.display(), e);
+                return Strings::english();
+            }
+        };
+        let mut table: toml::Table = toml::from_str(EN).expect("built-in en.toml is valid");
+        match toml::from_str::<toml::Table>(&text) {
+            Ok(overrides) => table.extend(overrides),
+            Err(e) => {
+                eprintln!("Warning: ignoring invalid locale {}: {}", path.display(), e);
+                return Strings::english();
+            }
+        }
+        table.try_into().unwrap_or_else(|e| {
+            eprintln!("Warning: ignoring invalid locale {}: {}", path.display(), e);
+            Strings::english()
+        })
+    }
+}
+
+impl Default for Strings {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+pub fn locale_path(language: &str) -> Option<PathBuf> {
+    let file = format!("{}.toml", language);
+    dirs::config_dir().map(|dir| dir.join("todo-tui").join("locales").join(file))
+}
+
+// Replace each {name} in the template with its value
+pub fn fill(template: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let mut text = template.to_owned();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
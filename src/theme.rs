@@ -0,0 +1,255 @@
+use crate::error::ConfigError;
+use ratatui::style::palette::material::{GRAY, WHITE};
+use ratatui::style::palette::tailwind::SLATE;
+use ratatui::style::{Color, Style};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+// Colors every screen is drawn with, [theme] in the config picks a built-in one by name and
+// changes single colors on top
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    // The built-in theme the colors started from
+    pub name: &'static str,
+    // Reset keeps the terminal's own
+    pub background: Color,
+    pub foreground: Color,
+    pub title: Color,
+    pub item_pending_fg: Color,
+    pub item_done_fg: Color,
+    // Items left untouched for twice aging_critical_days
+    pub item_aged_fg: Color,
+    pub item_selected_bg: Color,
+    pub marked_bg: Color,
+    pub input_border: Color,
+    pub list_border: Color,
+    pub inactive_border: Color,
+    pub secondary_fg: Color,
+    // Hints, counts and dates next to the text they belong to
+    pub muted_fg: Color,
+    pub faint_fg: Color,
+    pub separator_fg: Color,
+    pub status_bar_bg: Color,
+}
+
+pub const THEMES: &[Theme] = &[
+    Theme {
+        name: "slate",
+        background: Color::Reset,
+        foreground: Color::Reset,
+        title: Color::White,
+        item_pending_fg: WHITE,
+        item_done_fg: GRAY.c500,
+        item_aged_fg: Color::DarkGray,
+        item_selected_bg: SLATE.c800,
+        marked_bg: SLATE.c700,
+        input_border: SLATE.c300,
+        list_border: SLATE.c500,
+        inactive_border: SLATE.c700,
+        secondary_fg: SLATE.c400,
+        muted_fg: SLATE.c500,
+        faint_fg: SLATE.c600,
+        separator_fg: SLATE.c700,
+        status_bar_bg: Color::Reset,
+    },
+    Theme {
+        name: "nord",
+        background: Color::Rgb(46, 52, 64),
+        foreground: Color::Rgb(216, 222, 233),
+        title: Color::Rgb(236, 239, 244),
+        item_pending_fg: Color::Rgb(216, 222, 233),
+        item_done_fg: Color::Rgb(76, 86, 106),
+        item_aged_fg: Color::Rgb(94, 129, 172),
+        item_selected_bg: Color::Rgb(59, 66, 82),
+        marked_bg: Color::Rgb(76, 86, 106),
+        input_border: Color::Rgb(143, 188, 187),
+        list_border: Color::Rgb(129, 161, 193),
+        inactive_border: Color::Rgb(76, 86, 106),
+        secondary_fg: Color::Rgb(136, 192, 208),
+        muted_fg: Color::Rgb(129, 161, 193),
+        faint_fg: Color::Rgb(94, 129, 172),
+        separator_fg: Color::Rgb(76, 86, 106),
+        status_bar_bg: Color::Rgb(46, 52, 64),
+    },
+    Theme {
+        name: "gruvbox",
+        background: Color::Rgb(40, 40, 40),
+        foreground: Color::Rgb(235, 219, 178),
+        title: Color::Rgb(251, 241, 199),
+        item_pending_fg: Color::Rgb(235, 219, 178),
+        item_done_fg: Color::Rgb(124, 111, 100),
+        item_aged_fg: Color::Rgb(146, 131, 116),
+        item_selected_bg: Color::Rgb(60, 56, 54),
+        marked_bg: Color::Rgb(102, 92, 84),
+        input_border: Color::Rgb(213, 196, 161),
+        list_border: Color::Rgb(168, 153, 132),
+        inactive_border: Color::Rgb(102, 92, 84),
+        secondary_fg: Color::Rgb(189, 174, 147),
+        muted_fg: Color::Rgb(168, 153, 132),
+        faint_fg: Color::Rgb(146, 131, 116),
+        separator_fg: Color::Rgb(102, 92, 84),
+        status_bar_bg: Color::Rgb(40, 40, 40),
+    },
+    Theme {
+        name: "solarized",
+        background: Color::Rgb(0, 43, 54),
+        foreground: Color::Rgb(147, 161, 161),
+        title: Color::Rgb(238, 232, 213),
+        item_pending_fg: Color::Rgb(147, 161, 161),
+        item_done_fg: Color::Rgb(88, 110, 117),
+        item_aged_fg: Color::Rgb(101, 123, 131),
+        item_selected_bg: Color::Rgb(7, 54, 66),
+        marked_bg: Color::Rgb(88, 110, 117),
+        input_border: Color::Rgb(131, 148, 150),
+        list_border: Color::Rgb(38, 139, 210),
+        inactive_border: Color::Rgb(88, 110, 117),
+        secondary_fg: Color::Rgb(42, 161, 152),
+        muted_fg: Color::Rgb(38, 139, 210),
+        faint_fg: Color::Rgb(101, 123, 131),
+        separator_fg: Color::Rgb(88, 110, 117),
+        status_bar_bg: Color::Rgb(0, 43, 54),
+    },
+    Theme {
+        name: "light",
+        background: Color::Rgb(248, 250, 252),
+        foreground: Color::Rgb(30, 41, 59),
+        title: Color::Rgb(15, 23, 42),
+        item_pending_fg: Color::Rgb(30, 41, 59),
+        item_done_fg: Color::Rgb(148, 163, 184),
+        item_aged_fg: Color::Rgb(148, 163, 184),
+        item_selected_bg: Color::Rgb(226, 232, 240),
+        marked_bg: Color::Rgb(203, 213, 225),
+        input_border: Color::Rgb(51, 65, 85),
+        list_border: Color::Rgb(100, 116, 139),
+        inactive_border: Color::Rgb(203, 213, 225),
+        secondary_fg: Color::Rgb(71, 85, 105),
+        muted_fg: Color::Rgb(100, 116, 139),
+        faint_fg: Color::Rgb(148, 163, 184),
+        separator_fg: Color::Rgb(203, 213, 225),
+        status_bar_bg: Color::Rgb(248, 250, 252),
+    },
+];
+
+impl Default for Theme {
+    fn default() -> Self {
+        THEMES[0]
+    }
+}
+
+pub fn find(name: &str) -> Option<Theme> {
+    THEMES.iter().copied().find(|theme| theme.name == name)
+}
+
+impl Theme {
+    // Every color with the key it has under [theme]
+    fn colors_mut(&mut self) -> [(&'static str, &mut Color); 16] {
+        [
+            ("background", &mut self.background),
+            ("foreground", &mut self.foreground),
+            ("title", &mut self.title),
+            ("item_pending_fg", &mut self.item_pending_fg),
+            ("item_done_fg", &mut self.item_done_fg),
+            ("item_aged_fg", &mut self.item_aged_fg),
+            ("item_selected_bg", &mut self.item_selected_bg),
+            ("marked_bg", &mut self.marked_bg),
+            ("input_border", &mut self.input_border),
+            ("list_border", &mut self.list_border),
+            ("inactive_border", &mut self.inactive_border),
+            ("secondary_fg", &mut self.secondary_fg),
+            ("muted_fg", &mut self.muted_fg),
+            ("faint_fg", &mut self.faint_fg),
+            ("separator_fg", &mut self.separator_fg),
+            ("status_bar_bg", &mut self.status_bar_bg),
+        ]
+    }
+
+    pub fn colors(&self) -> [(&'static str, Color); 16] {
+        let mut theme = *self;
+        theme.colors_mut().map(|(name, color)| (name, *color))
+    }
+
+    // Cells nothing else is drawn in
+    pub fn base(&self) -> Style {
+        Style::new().fg(self.foreground).bg(self.background)
+    }
+
+    // Another built-in theme with the colors changed in this one kept, for switching themes
+    pub fn with_base(&self, base: &Theme) -> Theme {
+        let own = find(self.name).unwrap_or_default();
+        let mut theme = *base;
+        let changed = self.colors().into_iter().zip(own.colors()).map(|((_, color), (_, original))| (color != original).then_some(color));
+        for ((_, slot), changed) in theme.colors_mut().into_iter().zip(changed) {
+            if let Some(color) = changed {
+                *slot = color;
+            }
+        }
+        theme
+    }
+}
+
+impl TryFrom<BTreeMap<String, String>> for Theme {
+    type Error = ConfigError;
+
+    fn try_from(mut table: BTreeMap<String, String>) -> Result<Theme, ConfigError> {
+        let name = table.remove("name").unwrap_or_else(|| THEMES[0].name.to_owned());
+        let mut theme = find(&name).ok_or(ConfigError::InvalidTheme(name))?;
+        for (key, value) in table {
+            let color = Color::from_str(&value).map_err(|_| ConfigError::InvalidColor(key.clone(), value.clone()))?;
+            let Some((_, slot)) = theme.colors_mut().into_iter().find(|(name, _)| *name == key) else {
+                return Err(ConfigError::UnknownColor(key));
+            };
+            *slot = color;
+        }
+        Ok(theme)
+    }
+}
+
+// The name and the colors that differ from it, written back to config.toml
+impl From<Theme> for BTreeMap<String, String> {
+    fn from(theme: Theme) -> BTreeMap<String, String> {
+        let base = find(theme.name).unwrap_or_default();
+        let mut table: BTreeMap<String, String> = theme
+            .colors()
+            .into_iter()
+            .zip(base.colors())
+            .filter(|((_, color), (_, original))| color != original)
+            .map(|((name, color), _)| (name.to_owned(), color.to_string()))
+            .collect();
+        table.insert("name".to_owned(), theme.name.to_owned());
+        table
+    }
+}
+
+impl Serialize for Theme {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BTreeMap::from(*self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Theme {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Theme, D::Error> {
+        Theme::try_from(BTreeMap::<String, String>::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn changed_colors_survive_saving_and_switching_themes() {
+        let config: Config = toml::from_str("[theme]\nname = \"nord\"\nlist_border = \"#88c0d0\"\ntitle = \"blue\"\n").unwrap();
+        assert_eq!(config.theme.list_border, Color::Rgb(136, 192, 208));
+        assert_eq!(config.theme.title, Color::Blue);
+        assert_eq!(config.theme.background, THEMES[1].background);
+        let saved: Config = toml::from_str(&config.render().unwrap()).unwrap();
+        assert_eq!(saved.theme, config.theme);
+
+        let gruvbox = config.theme.with_base(&THEMES[2]);
+        assert_eq!(gruvbox.name, "gruvbox");
+        assert_eq!(gruvbox.list_border, Color::Rgb(136, 192, 208));
+        assert_eq!(gruvbox.background, THEMES[2].background);
+    }
+}
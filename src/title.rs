@@ -0,0 +1,61 @@
+// Placeholders for Config::list_title_format
+pub const PLACEHOLDERS: &[&str] = &["list_name", "total", "pending", "done", "overdue"];
+
+pub struct TitleContext<'a> {
+    pub list_name: &'a str,
+    pub total: usize,
+    pub pending: usize,
+    pub done: usize,
+    pub overdue: usize,
+}
+
+impl TitleContext<'_> {
+    fn value(&self, placeholder: &str) -> Option<String> {
+        match placeholder {
+            "list_name" => Some(self.list_name.to_owned()),
+            "total" => Some(self.total.to_string()),
+            "pending" => Some(self.pending.to_string()),
+            "done" => Some(self.done.to_string()),
+            "overdue" => Some(self.overdue.to_string()),
+            _ => None,
+        }
+    }
+}
+
+// Replace {placeholder} tokens, anything unknown is kept as written
+pub fn format_title(template: &str, ctx: &TitleContext) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        match after.find('}').and_then(|close| Some((close, ctx.value(&after[..close])?))) {
+            Some((close, value)) => {
+                out.push_str(&value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// The first placeholder that is unknown or left open, if any
+pub fn invalid_placeholder(template: &str) -> Option<String> {
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let after = &rest[open + 1..];
+        let Some(close) = after.find('}') else {
+            return Some(after.to_owned());
+        };
+        if !PLACEHOLDERS.contains(&&after[..close]) {
+            return Some(after[..close].to_owned());
+        }
+        rest = &after[close + 1..];
+    }
+    None
+}
@@ -0,0 +1,2055 @@
+use crate::app::{AddField, AppState, CurrentScreen, SortOrder, INBOX_LIST};
+use crate::config::{Config, SeparatorStyle};
+use crate::date_utils::format_ago;
+use crate::help;
+use crate::context_menu::{MenuAction, PRIORITIES};
+use crate::item::{subtask_bar, wrap_line, Priority, TodoItem, LABEL_COLORS};
+use crate::palette;
+use crate::pomodoro::Pomodoro;
+use crate::review::ReviewStep;
+use crate::strings::fill;
+use crate::stats;
+use crate::theme::{Theme, THEMES};
+use crate::title::{format_title, TitleContext};
+use chrono::{DateTime, Datelike, Duration, Local};
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::symbols::border;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, BorderType, Borders, Clear, LineGauge, List, ListItem, ListState, Padding, Paragraph, StatefulWidget,
+    Wrap,
+};
+use ratatui::Frame;
+use std::fmt::Display;
+use std::io;
+use unicode_width::UnicodeWidthStr;
+
+pub fn draw(frame: &mut Frame, app_state: &mut AppState) -> io::Result<()> {
+    // The theme preview shows the picked theme with the colors changed in the config
+    let theme = &match app_state.current_screen {
+        CurrentScreen::ThemePreview => app_state.config.theme.with_base(&THEMES[app_state.theme_preview]),
+        _ => app_state.config.theme,
+    };
+    frame.render_widget(Block::new().style(theme.base()), frame.area());
+    let result = draw_screen(frame, theme, app_state);
+    if let Some((message, _)) = &app_state.startup_message {
+        startup_message_ui(frame, theme, &app_state.config, message);
+    }
+    if app_state.config.screen_reader_mode {
+        // No colors or text effects, highlighted rows stay told apart by reversing them
+        let buffer = frame.buffer_mut();
+        for cell in buffer.content.iter_mut() {
+            let highlighted = cell.bg != theme.background;
+            cell.set_style(Style::reset());
+            if highlighted {
+                cell.modifier = Modifier::REVERSED;
+            }
+        }
+    }
+    if app_state.config.high_contrast {
+        // Applied last so it overrides every other color: white on black, with bold and
+        // underline as the only emphasis
+        let buffer = frame.buffer_mut();
+        for cell in buffer.content.iter_mut() {
+            let mut modifier = cell.modifier & (Modifier::BOLD | Modifier::UNDERLINED);
+            if cell.bg != theme.background || cell.modifier.contains(Modifier::REVERSED) {
+                modifier = Modifier::BOLD | Modifier::UNDERLINED;
+            }
+            cell.set_style(Style::reset().fg(Color::White).bg(Color::Black));
+            cell.modifier = modifier;
+        }
+    }
+    result
+}
+
+fn draw_screen(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    match app_state.current_screen {
+        CurrentScreen::Main => main_ui(frame, theme, app_state),
+        CurrentScreen::Add => add_ui(frame, theme, app_state),
+        CurrentScreen::Edit => edit_ui(frame, theme, app_state),
+        CurrentScreen::Inbox => inbox_ui(frame, theme, app_state),
+        CurrentScreen::TemplatePicker => template_picker_ui(frame, theme, app_state),
+        CurrentScreen::CommandPalette => {
+            main_ui(frame, theme, app_state)?;
+            command_palette_ui(frame, theme, app_state)
+        }
+        CurrentScreen::ConfirmDedup => {
+            main_ui(frame, theme, app_state)?;
+            let s = &app_state.strings;
+            let text = fill(&s.confirm_dedup, &[("count", &app_state.duplicate_count())]);
+            confirm_ui(frame, theme, &app_state.config, &s.duplicates, &text);
+            Ok(())
+        }
+        CurrentScreen::SearchReplace => {
+            main_ui(frame, theme, app_state)?;
+            search_replace_ui(frame, theme, app_state)
+        }
+        CurrentScreen::ColorPicker => {
+            edit_ui(frame, theme, app_state)?;
+            color_picker_ui(frame, theme, app_state);
+            Ok(())
+        }
+        CurrentScreen::ConfirmClearColor => {
+            edit_ui(frame, theme, app_state)?;
+            let s = &app_state.strings;
+            confirm_ui(frame, theme, &app_state.config, &s.color_label, &s.confirm_clear_color);
+            Ok(())
+        }
+        CurrentScreen::Focus => focus_ui(frame, theme, app_state),
+        CurrentScreen::ParentPicker => {
+            main_ui(frame, theme, app_state)?;
+            parent_picker_ui(frame, theme, app_state)
+        }
+        CurrentScreen::LinkSearch => {
+            focus_ui(frame, theme, app_state)?;
+            link_search_ui(frame, theme, app_state)
+        }
+        CurrentScreen::PasswordPrompt => password_prompt_ui(frame, theme, app_state),
+        CurrentScreen::Stats => stats_ui(frame, theme, app_state),
+        CurrentScreen::Search => {
+            main_ui(frame, theme, app_state)?;
+            search_ui(frame, theme, app_state)
+        }
+        CurrentScreen::ConfirmBulk => {
+            main_ui(frame, theme, app_state)?;
+            let s = &app_state.strings;
+            let done = app_state.bulk_done;
+            let template = if done { &s.confirm_mark_all_done } else { &s.confirm_mark_all_undone };
+            let text = fill(template, &[("count", &app_state.bulk_affected(done))]);
+            confirm_ui(frame, theme, &app_state.config, &s.mark_all, &text);
+            Ok(())
+        }
+        CurrentScreen::ConfirmDelete => {
+            main_ui(frame, theme, app_state)?;
+            let s = &app_state.strings;
+            let item = app_state.selected_item_index().and_then(|i| app_state.get(i));
+            let description = item.map_or("", |item| item.description.as_str());
+            let text = match app_state.marked_indices().len() {
+                0 => fill(&s.confirm_delete, &[("description", &description)]),
+                count => fill(&s.confirm_delete_marked, &[("count", &count)]),
+            };
+            confirm_ui(frame, theme, &app_state.config, &s.delete_item, &text);
+            Ok(())
+        }
+        CurrentScreen::ConfirmClearDone => {
+            main_ui(frame, theme, app_state)?;
+            let s = &app_state.strings;
+            let text = fill(&s.confirm_clear_completed, &[("count", &app_state.done_count())]);
+            confirm_ui(frame, theme, &app_state.config, &s.clear_completed, &text);
+            Ok(())
+        }
+        CurrentScreen::NoteEditor => note_editor_ui(frame, theme, app_state),
+        CurrentScreen::AddComment => {
+            focus_ui(frame, theme, app_state)?;
+            add_comment_ui(frame, theme, app_state)
+        }
+        CurrentScreen::ConfirmDiscardNote => {
+            note_editor_ui(frame, theme, app_state)?;
+            let s = &app_state.strings;
+            confirm_ui(frame, theme, &app_state.config, &s.notes, &s.confirm_discard_notes);
+            Ok(())
+        }
+        CurrentScreen::ConfirmDiscard => {
+            let editing = app_state.discard_return_screen == CurrentScreen::Edit;
+            if editing {
+                edit_ui(frame, theme, app_state)?;
+            } else {
+                add_ui(frame, theme, app_state)?;
+            }
+            let s = &app_state.strings;
+            let title = if editing { &s.edit_item } else { &s.new_item };
+            confirm_ui(frame, theme, &app_state.config, title, &s.confirm_discard);
+            Ok(())
+        }
+        CurrentScreen::PasteMultiple => {
+            add_ui(frame, theme, app_state)?;
+            let s = &app_state.strings;
+            let text = fill(&s.confirm_paste_multiple, &[("count", &app_state.pasted_lines.len())]);
+            confirm_ui(frame, theme, &app_state.config, &s.paste, &text);
+            Ok(())
+        }
+        CurrentScreen::DuplicateWarning => {
+            add_ui(frame, theme, app_state)?;
+            let s = &app_state.strings;
+            let description = app_state.duplicate_of.as_deref().unwrap_or_default();
+            let text = fill(&s.confirm_duplicate, &[("description", &description)]);
+            confirm_ui(frame, theme, &app_state.config, &s.duplicate, &text);
+            Ok(())
+        }
+        CurrentScreen::GlobalSearch => global_search_ui(frame, theme, app_state),
+        CurrentScreen::RecentlyDone => recently_done_ui(frame, theme, app_state),
+        CurrentScreen::Archive => archive_ui(frame, theme, app_state),
+        CurrentScreen::WeeklyReview => weekly_review_ui(frame, theme, app_state),
+        CurrentScreen::StaleItemsReview => stale_review_ui(frame, theme, app_state),
+        CurrentScreen::Timeline => timeline_ui(frame, theme, app_state),
+        CurrentScreen::ItemDetail => item_detail_ui(frame, theme, app_state),
+        CurrentScreen::DailyPrompt => {
+            main_ui(frame, theme, app_state)?;
+            daily_prompt_ui(frame, theme, app_state)
+        }
+        CurrentScreen::SplitListName => {
+            main_ui(frame, theme, app_state)?;
+            split_list_name_ui(frame, theme, app_state)
+        }
+        CurrentScreen::SaveFilterName => {
+            main_ui(frame, theme, app_state)?;
+            filter_name_ui(frame, theme, app_state)
+        }
+        CurrentScreen::MoveToPosition => {
+            main_ui(frame, theme, app_state)?;
+            move_to_position_ui(frame, theme, app_state)
+        }
+        CurrentScreen::LoadFilter => {
+            main_ui(frame, theme, app_state)?;
+            load_filter_ui(frame, theme, app_state)
+        }
+        CurrentScreen::ThemePreview => theme_preview_ui(frame, theme, app_state),
+        CurrentScreen::UndoHistory => {
+            main_ui(frame, theme, app_state)?;
+            undo_history_ui(frame, theme, app_state)
+        }
+        CurrentScreen::ContextMenu | CurrentScreen::PriorityMenu => {
+            main_ui(frame, theme, app_state)?;
+            context_menu_ui(frame, theme, app_state)
+        }
+        CurrentScreen::SortMenu => {
+            main_ui(frame, theme, app_state)?;
+            sort_menu_ui(frame, theme, app_state)
+        }
+        CurrentScreen::TagFilter => {
+            main_ui(frame, theme, app_state)?;
+            tag_filter_ui(frame, theme, app_state)
+        }
+        CurrentScreen::Help => {
+            // Over the screen it was opened from
+            app_state.current_screen = app_state.help_return_screen;
+            let drawn = draw_screen(frame, theme, app_state);
+            app_state.current_screen = CurrentScreen::Help;
+            drawn?;
+            help_ui(frame, theme, app_state)
+        }
+        CurrentScreen::BatchTag => {
+            main_ui(frame, theme, app_state)?;
+            batch_tag_ui(frame, theme, app_state)
+        }
+        CurrentScreen::GithubImport => {
+            main_ui(frame, theme, app_state)?;
+            github_import_ui(frame, theme, app_state)
+        }
+        CurrentScreen::Import => {
+            main_ui(frame, theme, app_state)?;
+            import_ui(frame, theme, app_state)
+        }
+        CurrentScreen::ConfirmGithubImport => {
+            main_ui(frame, theme, app_state)?;
+            let s = &app_state.strings;
+            let args: [(&str, &dyn Display); 3] = [
+                ("count", &app_state.github_issues.len()),
+                ("repo", &app_state.github_input.value().trim()),
+                ("list", &app_state.lists[app_state.active_list].name),
+            ];
+            confirm_ui(frame, theme, &app_state.config, &s.github_import, &fill(&s.confirm_github_import, &args));
+            Ok(())
+        }
+        CurrentScreen::ConfirmSplit => {
+            main_ui(frame, theme, app_state)?;
+            let s = &app_state.strings;
+            let args: [(&str, &dyn Display); 2] = [
+                ("count", &app_state.visible_indices().len()),
+                ("name", &app_state.split_name_input.value().trim()),
+            ];
+            confirm_ui(frame, theme, &app_state.config, &s.split_list, &fill(&s.confirm_split, &args));
+            Ok(())
+        }
+        CurrentScreen::MergeListPicker => {
+            main_ui(frame, theme, app_state)?;
+            merge_list_picker_ui(frame, theme, app_state)
+        }
+        CurrentScreen::ConfirmMerge => {
+            main_ui(frame, theme, app_state)?;
+            if let Some(target) = app_state.merge_target() {
+                let s = &app_state.strings;
+                let (source, target) = (&app_state.lists[app_state.active_list], &app_state.lists[target]);
+                let args: [(&str, &dyn Display); 4] = [
+                    ("source", &source.name),
+                    ("source_count", &source.len()),
+                    ("target", &target.name),
+                    ("target_count", &target.len()),
+                ];
+                confirm_ui(frame, theme, &app_state.config, &s.merge_lists, &fill(&s.confirm_merge, &args));
+            }
+            Ok(())
+        }
+        CurrentScreen::SpellCheck => {
+            main_ui(frame, theme, app_state)?;
+            spell_check_ui(frame, theme, app_state)
+        }
+        CurrentScreen::Exit => Ok(()),
+    }
+}
+
+pub fn main_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let chrome = !app_state.distraction_free;
+    let progress_height = if app_state.effective_config().show_progress_bar && chrome { 1 } else { 0 };
+    let [area, progress_area, status_area] = Layout::vertical([
+        Constraint::Min(0),
+        Constraint::Length(progress_height),
+        Constraint::Length(if chrome { 1 } else { 0 }),
+    ])
+    .areas(frame.area());
+    let area = if app_state.config.show_sidebar && chrome {
+        let [sidebar_area, area] =
+            Layout::horizontal([Constraint::Length(app_state.config.sidebar_width), Constraint::Min(0)]).areas(area);
+        sidebar_ui(frame, theme, app_state, sidebar_area);
+        area
+    } else {
+        area
+    };
+    let area = match app_state.config.max_list_width {
+        Some(width) => {
+            let [area] = Layout::horizontal([Constraint::Max(width)]).flex(Flex::Center).areas(area);
+            area
+        }
+        None => area,
+    };
+    // The pane without focus has the dimmer border
+    let border = if app_state.sidebar_focused && app_state.config.show_sidebar { theme.inactive_border } else { theme.list_border };
+    let block = if chrome {
+        bordered(&app_state.config)
+            .border_style(Style::new().fg(border))
+            .title(Line::from(main_title(app_state)).centered().fg(theme.title))
+    } else {
+        Block::default()
+    };
+    let block = block.padding(app_state.config.list_padding());
+    let block_inner = block.inner(area);
+    let indices = app_state.visible_indices();
+    let height = block_inner.height as usize;
+    // Expanded mode draws a spacer row under every item
+    let rows_per_item = if app_state.compact_mode { 1 } else { 2 };
+    let group_starts: Vec<bool> = match app_state.config.group_separator_style {
+        SeparatorStyle::None => vec![],
+        _ => app_state.items_grouped(&indices).map(|(starts, _)| starts).collect(),
+    };
+    // Separators between groups take a row each, above the first item of a group
+    let separator_before = |row: usize, start: usize| row > start && group_starts.get(row) == Some(&true);
+    let number_width = indices.len().to_string().len();
+    let subtasks = app_state.subtask_counts();
+    let outline = app_state.outline_mode.then(|| app_state.compute_outline_numbers());
+    // The line of an item with how many of its spans come before the description
+    let item_line = |app_state: &AppState, number: usize, index: usize, todo_item: &TodoItem| {
+        let matches = app_state.search_matches(todo_item);
+        let misspelled = app_state.misspelled_chars(todo_item);
+        let mut line = todo_item.to_line_with_matches(&app_state.config, theme, &matches, &misspelled);
+        let mut prefix = 2;
+        if let Some(number) = outline.as_ref().and_then(|numbers| numbers.get(&todo_item.id)) {
+            line.spans.insert(1, Span::styled(format!("{} ", number), theme.muted_fg).add_modifier(Modifier::DIM));
+            prefix += 1;
+        }
+        let depth = app_state.subtask_depth(index);
+        if depth > 0 {
+            line.spans.insert(1, Span::raw("  ".repeat(depth)));
+            prefix += 1;
+        }
+        if app_state.config.show_item_numbers && outline.is_none() {
+            // After the color label, which stays on the leftmost column
+            let number = format!("{:>width$}.", number, width = number_width);
+            line.spans.insert(1, Span::styled(number, theme.muted_fg));
+            prefix += 1;
+        }
+        let (done, total) = subtasks.get(&todo_item.id).copied().unwrap_or_default();
+        if total > 0 {
+            let bar = subtask_bar(done, total, app_state.config.ascii());
+            line.spans.push(Span::styled(format!(" {}", bar), theme.secondary_fg));
+            let (collapsed, expanded) = if app_state.config.ascii() { (">", "v") } else { ("▶", "▼") };
+            let marker = if todo_item.collapsed {
+                let hidden = app_state.descendant_count(index);
+                format!(" {} {}", collapsed, fill(&app_state.strings.hidden, &[("count", &hidden)]))
+            } else {
+                format!(" {}", expanded)
+            };
+            line.spans.push(Span::styled(marker, theme.muted_fg));
+        }
+        // Screen reader lines are all description
+        (line, if app_state.config.screen_reader_mode { 0 } else { prefix })
+    };
+    let symbol_width = if app_state.config.screen_reader_mode { 2 } else { app_state.config.highlight_symbol.width() };
+    let wrap_width = (block_inner.width as usize).saturating_sub(symbol_width);
+    let item_lines = |app_state: &AppState, row: usize| -> Vec<Line<'static>> {
+        let Some(todo_item) = app_state.get(indices[row]) else {
+            return vec![];
+        };
+        let (line, prefix) = item_line(app_state, row + 1, indices[row], todo_item);
+        if app_state.wrap {
+            wrap_line(line, prefix, wrap_width)
+        } else {
+            vec![line]
+        }
+    };
+    // Rows an item takes with its spacer, only wrapped ones can take more than one
+    let item_height = |app_state: &AppState, row: usize| {
+        let lines = if app_state.wrap { item_lines(app_state, row).len().max(1) } else { 1 };
+        lines + rows_per_item - 1
+    };
+    // A clipped list gives up a row above and/or below for the "N more" markers, narrowing
+    // the window can scroll it, so repeat until the reserved rows are enough
+    let mut reserved = 0;
+    let mut separator_rows = 0;
+    loop {
+        let fitting = ((height - reserved).saturating_sub(separator_rows) / rows_per_item).max(1);
+        app_state.update_render_window(fitting, indices.len());
+        if app_state.wrap {
+            // Wrapped items take more rows, drop the ones that don't fit from the end, or from
+            // the start when that would drop the selected one
+            let (mut start, mut end) = app_state.render_window;
+            let selected = app_state.todo_list_state.selected();
+            let rows = |start: usize, end: usize| -> usize {
+                (start..end).map(|row| usize::from(separator_before(row, start)) + item_height(app_state, row)).sum()
+            };
+            while end - start > 1 && rows(start, end) > height.saturating_sub(reserved) {
+                if selected.is_some_and(|s| s + 1 >= end) {
+                    start += 1;
+                } else {
+                    end -= 1;
+                }
+            }
+            app_state.render_window = (start, end);
+        }
+        let (start, end) = app_state.render_window;
+        let needed = usize::from(start > 0) + usize::from(end < indices.len());
+        let separators = (start..end).filter(|&row| separator_before(row, start)).count();
+        if needed > reserved && height >= 3 {
+            reserved = needed;
+        } else if separators > separator_rows && fitting > 1 && !app_state.wrap {
+            separator_rows += 1;
+        } else {
+            break;
+        }
+    }
+
+    // Only the rows that fit on screen are built, the window is scrolled by hand instead of
+    // through ListState::offset so large lists stay cheap to draw
+    let (start, end) = app_state.render_window;
+    let above = usize::from(start > 0 && height >= 3);
+    // Top row of each item in the window, counted from the first one
+    let mut item_rows = vec![];
+    let mut rows = 0;
+    for row in start..end {
+        rows += usize::from(separator_before(row, start));
+        item_rows.push(rows as u16);
+        rows += item_height(app_state, row);
+    }
+    app_state.item_area = Rect::new(block_inner.x, block_inner.y + above as u16, block_inner.width, rows as u16);
+    app_state.item_rows = item_rows;
+    let ascii = app_state.config.ascii();
+    let more = |template: &str, count: usize| {
+        let text = format!("… {}", fill(template, &[("count", &count)]));
+        let text = if ascii { text.replace('…', "...").replace('↑', "^").replace('↓', "v") } else { text };
+        ListItem::new(text).fg(theme.muted_fg)
+    };
+    let separator = {
+        let (line, dashed) = if ascii { ("-", "- ") } else { ("─", "╌") };
+        let width = block_inner.width as usize;
+        let text = match app_state.config.group_separator_style {
+            SeparatorStyle::Line => line.repeat(width),
+            SeparatorStyle::DashedLine => dashed.repeat(width / dashed.chars().count()),
+            SeparatorStyle::None | SeparatorStyle::BlankLine => String::new(),
+        };
+        ListItem::new(text).fg(theme.separator_fg)
+    };
+    let mut items: Vec<ListItem> = vec![];
+    if above == 1 {
+        items.push(more(&app_state.strings.more_above, start));
+    }
+    items.extend(indices[start..end]
+        .iter()
+        .enumerate()
+        .filter(|&(_, &i)| app_state.get(i).is_some())
+        .map(|(row, &index)| {
+            let item = ListItem::new(item_lines(app_state, start + row));
+            (start + row, if app_state.is_marked(index) { item.bg(theme.marked_bg) } else { item })
+        })
+        .flat_map(|(row, item)| {
+            let separator = separator_before(row, start).then(|| separator.clone());
+            let spacer = (rows_per_item == 2).then(|| ListItem::new(""));
+            separator.into_iter().chain(std::iter::once(item)).chain(spacer)
+        }));
+    if end < indices.len() && height >= 3 {
+        items.push(more(&app_state.strings.more_below, indices.len() - end));
+    }
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        // Without a background color the symbol is all that marks the selection
+        .highlight_symbol(if app_state.config.screen_reader_mode {
+            "> "
+        } else {
+            app_state.config.highlight_symbol.as_str()
+        })
+        .block(block);
+    let mut window_state = ListState::default().with_selected(app_state.todo_list_state.selected().map(|s| {
+        (s - start) * rows_per_item + above + (start..=s).filter(|&row| separator_before(row, start)).count()
+    }));
+    StatefulWidget::render(lis, area, frame.buffer_mut(), &mut window_state);
+    if app_state.items().is_empty() {
+        empty_list_ui(frame, theme, &app_state.config.empty_list_message, block_inner);
+    }
+    let today = Local::now().date_naive();
+    // Screen reader lines already carry the details, nothing is drawn off to the side
+    let metadata_rows = if app_state.config.screen_reader_mode { &[][..] } else { &indices[start..end] };
+    for (row, &i) in metadata_rows.iter().enumerate() {
+        let item = app_state.get(i);
+        let mut spans: Vec<Span> = item
+            .and_then(|item| item.metadata(&app_state.config, today))
+            .map(|metadata| {
+                let color = item.and_then(|item| item.due_color(today)).unwrap_or(theme.muted_fg);
+                Span::styled(metadata, color)
+            })
+            .into_iter()
+            .collect();
+        let pomodoro = app_state.pomodoro.as_ref().filter(|p| item.is_some_and(|item| item.id == p.item_id));
+        if let Some(pomodoro) = pomodoro {
+            if !spans.is_empty() {
+                spans.push(Span::raw("  "));
+            }
+            spans.push(pomodoro_span(pomodoro, ascii, theme));
+        }
+        if !spans.is_empty() {
+            let y = app_state.item_area.y + app_state.item_rows[row];
+            let row_area = Rect::new(block_inner.x, y, block_inner.width.saturating_sub(1), 1);
+            frame.render_widget(Line::from(spans).right_aligned(), row_area);
+        }
+    }
+    if let Some(item) = app_state.drag_source.and_then(|row| indices.get(row)).and_then(|&i| app_state.get(i)) {
+        // The dragged item follows the mouse
+        let area = app_state.item_area;
+        let y = app_state.drag_position.1.clamp(area.y, area.bottom().saturating_sub(1));
+        let row_area = Rect::new(area.x, y, area.width, 1);
+        clear(frame, theme, row_area);
+        frame.render_widget(Paragraph::new(item.to_line(&app_state.config, theme)).bg(Color::Yellow).fg(Color::Black), row_area);
+    }
+    if !chrome {
+        return Ok(());
+    }
+    if app_state.effective_config().show_progress_bar {
+        progress_bar_ui(frame, theme, app_state, progress_area);
+    }
+    status_bar_ui(frame, theme, app_state, status_area);
+    Ok(())
+}
+
+// Every list with its number of items, the active one in bold
+fn sidebar_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState, area: Rect) {
+    let active = app_state.active_list;
+    let items: Vec<ListItem> = app_state
+        .lists
+        .iter()
+        .enumerate()
+        .map(|(l, list)| {
+            let line = Line::from(vec![
+                Span::raw(list.name.clone()),
+                Span::styled(format!(" {}", list.len()), theme.muted_fg),
+            ]);
+            if l == active {
+                ListItem::new(line.fg(theme.title).bold())
+            } else {
+                ListItem::new(line.fg(theme.secondary_fg))
+            }
+        })
+        .collect();
+    let focused = app_state.sidebar_focused;
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg))
+        .block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(if focused { theme.list_border } else { theme.inactive_border }))
+                .title(Line::from(app_state.strings.lists.as_str()).fg(theme.title)),
+        );
+    let mut state = if focused { app_state.sidebar_state.clone() } else { ListState::default() };
+    StatefulWidget::render(lis, area, frame.buffer_mut(), &mut state);
+    if focused {
+        app_state.sidebar_state = state;
+    }
+}
+
+fn global_search_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let [input_area, list_area] =
+        Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(frame.area());
+    let scroll = app_state.global_search_input.visual_scroll(input_area.width as usize - 2);
+    let s = &app_state.strings;
+    let mode = if app_state.search_fuzzy { &s.fuzzy } else { &s.exact };
+    let input = Paragraph::new(app_state.global_search_input.value())
+        .scroll((0, scroll as u16))
+        .block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(s.search_all_lists.as_str()).fg(theme.title))
+                .title_bottom(Line::from(format!(" {} ", mode)).right_aligned()),
+        );
+    frame.render_widget(input, input_area);
+    let x = app_state.global_search_input.visual_cursor().max(scroll) - scroll + 1;
+    frame.set_cursor_position((input_area.x + x as u16, input_area.y + 1));
+
+    let results = app_state.global_search_results();
+    let title = fill(&app_state.strings.global_search_results, &[("count", &results.len())]);
+    let items: Vec<ListItem> = results
+        .iter()
+        .filter_map(|&(list, index)| {
+            let name = &app_state.lists[list].name;
+            let item = app_state.lists[list].get(index)?;
+            let mut line = item.to_line(&app_state.config, theme);
+            line.spans.insert(0, Span::styled(format!("[{}]", name), theme.muted_fg));
+            Some(ListItem::new(line))
+        })
+        .collect();
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(title).centered().fg(theme.title)),
+        );
+    StatefulWidget::render(lis, list_area, frame.buffer_mut(), &mut app_state.global_search_state);
+    Ok(())
+}
+
+fn progress_bar_ui(frame: &mut Frame, theme: &Theme, app_state: &AppState, area: Rect) {
+    let total = app_state.iter_unfiltered().count();
+    let done = app_state.iter_unfiltered().filter(|item| item.done).count();
+    let ratio = if total == 0 { 0.0 } else { done as f64 / total as f64 };
+    if app_state.config.ascii() {
+        let text = fill(&app_state.strings.progress, &[("done", &done), ("total", &total)]);
+        frame.render_widget(Paragraph::new(format!(" {}", text)), area);
+        return;
+    }
+    let gauge = LineGauge::default()
+        .filled_style(Style::new().fg(Color::Green))
+        .unfilled_style(Style::new().fg(theme.separator_fg))
+        .label(format!(" {} ", fill(&app_state.strings.progress, &[("done", &done), ("total", &total)])))
+        .ratio(ratio);
+    frame.render_widget(gauge, area);
+}
+
+pub fn password_prompt_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let width = 50.min(frame.area().width);
+    let area = popup_area(frame.area(), width, 3);
+    let masked = "*".repeat(app_state.password_input.value().chars().count());
+    let scroll = app_state.password_input.visual_scroll(width.saturating_sub(2) as usize);
+    let block = match &app_state.password_error {
+        Some(error) => bordered(&app_state.config)
+            .title(Line::from(error.clone()).centered())
+            .border_style(Style::new().red()),
+        None => bordered(&app_state.config)
+            .title(Line::from(app_state.strings.password.clone()).centered().fg(theme.title))
+            .border_style(Style::new().fg(theme.list_border)),
+    };
+    frame.render_widget(
+        Paragraph::new(masked)
+            .scroll((0, scroll as u16))
+            .block(block),
+        area,
+    );
+    let x = app_state.password_input.visual_cursor().max(scroll) - scroll + 1;
+    frame.set_cursor_position((area.x + x as u16, area.y + 1));
+    Ok(())
+}
+
+pub fn stats_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let total = app_state.all_items().count();
+    let done = app_state.all_items().filter(|item| item.done).count();
+    let percent = (done * 100).checked_div(total).unwrap_or(0);
+    let current = app_state.current_streak();
+    let today = Local::now().date_naive();
+    let this_week =
+        stats::completed_this_week(app_state.all_items(), today, app_state.config.first_day_of_week);
+    let flame = match (current > 0, app_state.config.ascii()) {
+        (true, false) => "🔥",
+        (true, true) => " *",
+        (false, _) => "  ",
+    };
+    let s = &app_state.strings;
+    let totals: [(&str, &dyn Display); 3] = [("total", &total), ("done", &done), ("percent", &percent)];
+    let longest = app_state.config.longest_streak;
+    let lines = vec![
+        Line::from(fill(&s.items_done, &totals)),
+        Line::from(fill(&s.completed_this_week, &[("count", &this_week)])),
+        Line::default(),
+        Line::from(format!("{} {}", flame, fill(&s.current_streak, &[("days", &current)]))).yellow(),
+        Line::from(format!("   {}", fill(&s.longest_streak, &[("days", &longest)]))),
+    ];
+    let area = popup_area(frame.area(), 40.min(frame.area().width), lines.len() as u16 + 2);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(s.statistics.as_str()).centered().fg(theme.title)),
+        ),
+        area,
+    );
+    Ok(())
+}
+
+// The selected item alone, centered, with all of its details
+pub fn focus_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let Some(item) = app_state.selected_item_index().and_then(|i| app_state.get(i)) else {
+        app_state.current_screen = CurrentScreen::Main;
+        return Ok(());
+    };
+    let title = if item.done {
+        let mark = if app_state.config.screen_reader_mode {
+            "[DONE]"
+        } else {
+            app_state.config.done_symbols().0
+        };
+        Line::from(format!("{} {}", mark, item.description))
+            .style((theme.muted_fg, Modifier::BOLD | Modifier::CROSSED_OUT))
+    } else {
+        Line::from(item.description.clone()).style(Style::new().fg(theme.title).bold())
+    };
+    let s = &app_state.strings;
+    let mut lines = vec![title, Line::default()];
+    if let Some(notes) = &item.notes {
+        lines.extend(notes.lines().map(|line| Line::from(line.to_owned())));
+        lines.push(Line::default());
+    }
+    if item.priority != Default::default() {
+        let priority = fill(&s.priority, &[("priority", &item.priority.label())]);
+        lines.push(Line::from(priority).fg(theme.secondary_fg));
+    }
+    if let Some(due) = item.due_date {
+        let due = app_state.config.format_date(due, Local::now().date_naive());
+        lines.push(Line::from(fill(&s.due, &[("due", &due)])).fg(theme.secondary_fg));
+    }
+    if !item.tags.is_empty() {
+        lines.push(Line::from(fill(&s.tags, &[("tags", &item.tags.join(" "))])).fg(theme.secondary_fg));
+    }
+    let created = item.created_at.format(&app_state.config.datetime_format);
+    lines.push(Line::from(fill(&s.created, &[("created", &created)])).fg(theme.faint_fg));
+    if !item.comments.is_empty() {
+        lines.push(Line::default());
+        lines.push(Line::from(s.comments.as_str()).fg(theme.title).bold());
+        for (i, (at, text)) in item.comments.iter().enumerate() {
+            let line = Line::from(vec![
+                Span::styled(format!("{}  ", at.format(&app_state.config.datetime_format)), theme.muted_fg),
+                Span::raw(text.clone()),
+            ]);
+            if app_state.comment_state.selected() == Some(i) {
+                lines.push(line.bg(theme.item_selected_bg));
+            } else {
+                lines.push(line);
+            }
+        }
+    }
+    let related = app_state.resolve_links(item.id);
+    if !related.is_empty() {
+        let arrow = if app_state.config.ascii() { "->" } else { "→" };
+        lines.push(Line::default());
+        lines.push(Line::from(s.related.as_str()).fg(theme.title).bold());
+        for (i, link) in related.iter().enumerate() {
+            let line = Line::from(format!("{} {}", arrow, link.description)).fg(theme.secondary_fg);
+            if app_state.comment_state.selected() == Some(item.comments.len() + i) {
+                lines.push(line.bg(theme.item_selected_bg));
+            } else {
+                lines.push(line);
+            }
+        }
+    }
+
+    let width = frame.area().width.saturating_sub(4).clamp(1, 70);
+    // Long lines wrap, leave room for the extra rows
+    let height: u16 = lines.iter().map(|line| line.width() as u16 / width + 1).sum();
+    let height = height.min(frame.area().height);
+    let text = Paragraph::new(lines).centered().wrap(Wrap { trim: true });
+    frame.render_widget(text, popup_area(frame.area(), width, height));
+    let [_, hint_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    frame.render_widget(
+        Paragraph::new(s.focus_keys.as_str()).centered().fg(theme.faint_fg),
+        hint_area,
+    );
+    if let Some(qr) = &app_state.qr_code {
+        // Dark modules on white whatever the theme, phones expect that way round
+        let lines = qr.to_half_blocks(2);
+        let width = lines.first().map_or(0, |line| line.chars().count()) as u16;
+        let area = popup_area(frame.area(), width, lines.len() as u16);
+        clear(frame, theme, area);
+        frame.render_widget(Paragraph::new(lines.join("\n")).fg(Color::Black).bg(Color::White), area);
+    }
+    Ok(())
+}
+
+fn link_search_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let area = popup_area(frame.area(), 70, 15);
+    let [input_area, list_area] = Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(area);
+    let scroll = app_state.link_input.visual_scroll(input_area.width as usize - 2);
+    clear(frame, theme, area);
+    frame.render_widget(
+        Paragraph::new(app_state.link_input.value())
+            .scroll((0, scroll as u16))
+            .block(bordered(&app_state.config).title(app_state.strings.link_item.as_str())),
+        input_area,
+    );
+    let x = app_state.link_input.visual_cursor().max(scroll) - scroll + 1;
+    frame.set_cursor_position((input_area.x + x as u16, input_area.y + 1));
+
+    let focused = app_state.selected_item_index().and_then(|i| app_state.get(i));
+    let links = focused.map(|item| item.links.clone()).unwrap_or_default();
+    let items: Vec<ListItem> = app_state
+        .link_candidates()
+        .into_iter()
+        .filter_map(|(list, index)| {
+            let item = app_state.lists[list].get(index)?;
+            // Already linked items are unlinked when picked
+            let mark = if links.contains(&item.id) { "* " } else { "  " };
+            let mut line = item.to_line(&app_state.config, theme);
+            line.spans.insert(0, Span::styled(format!("{}[{}]", mark, app_state.lists[list].name), theme.muted_fg));
+            Some(ListItem::new(line))
+        })
+        .collect();
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .block(bordered(&app_state.config).border_style(Style::new().fg(theme.list_border)));
+    StatefulWidget::render(lis, list_area, frame.buffer_mut(), &mut app_state.link_state);
+    Ok(())
+}
+
+fn parent_picker_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let area = popup_area(frame.area(), 70, 15);
+    let [input_area, list_area] = Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(area);
+    let scroll = app_state.parent_input.visual_scroll(input_area.width as usize - 2);
+    let s = &app_state.strings;
+    clear(frame, theme, area);
+    frame.render_widget(
+        Paragraph::new(app_state.parent_input.value())
+            .scroll((0, scroll as u16))
+            .block(bordered(&app_state.config).title(s.move_to_parent.as_str())),
+        input_area,
+    );
+    let x = app_state.parent_input.visual_cursor().max(scroll) - scroll + 1;
+    frame.set_cursor_position((input_area.x + x as u16, input_area.y + 1));
+
+    let mut items = vec![ListItem::new(format!(" {}", s.no_parent)).fg(theme.secondary_fg)];
+    items.extend(
+        app_state
+            .parent_candidates()
+            .into_iter()
+            .filter_map(|i| app_state.get(i))
+            .map(|item| ListItem::new(item.to_line(&app_state.config, theme))),
+    );
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .block(bordered(&app_state.config).border_style(Style::new().fg(theme.list_border)));
+    StatefulWidget::render(lis, list_area, frame.buffer_mut(), &mut app_state.parent_state);
+    Ok(())
+}
+
+fn add_comment_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let area = Rect::new(0, 0, frame.area().width.max(3) - 3, 3);
+    let scroll = app_state.comment_input.visual_scroll(area.width as usize);
+    clear(frame, theme, area);
+    frame.render_widget(
+        Paragraph::new(app_state.comment_input.value())
+            .scroll((0, scroll as u16))
+            .block(bordered(&app_state.config).title(app_state.strings.new_comment.as_str())),
+        area,
+    );
+    let x = app_state.comment_input.visual_cursor().max(scroll) - scroll + 1;
+    frame.set_cursor_position((area.x + x as u16, area.y + 1));
+    Ok(())
+}
+
+pub fn note_editor_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let [area, status_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    let title = app_state
+        .get(app_state.note_index)
+        .map(|item| fill(&app_state.strings.notes_title, &[("description", &item.description)]))
+        .unwrap_or_default();
+    app_state.note_editor.set_block(
+        bordered(&app_state.config)
+            .border_style(Style::new().fg(theme.list_border))
+            .title(Line::from(title).centered().fg(theme.title))
+            .title_bottom(Line::from(format!(" {} ", app_state.strings.note_editor_keys)).centered()),
+    );
+    app_state.note_editor.set_cursor_line_style(Style::default());
+    frame.render_widget(&app_state.note_editor, area);
+    let s = &app_state.strings;
+    let words = app_state.note_word_count();
+    let chars = app_state.note_text().chars().count();
+    let args: [(&str, &dyn Display); 3] = [("words", &words), ("chars", &chars), ("minutes", &words.div_ceil(200))];
+    let mut spans = vec![Span::raw(format!(" {}", fill(&s.word_count, &args)))];
+    if let Some(max) = app_state.config.max_note_len {
+        let limit = fill(&s.note_length, &[("chars", &chars), ("max", &max)]);
+        let color = if chars > max { Color::Red } else { theme.secondary_fg };
+        spans.push(Span::styled(format!(" • {}", limit), color));
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)).fg(theme.secondary_fg), status_area);
+    Ok(())
+}
+
+fn status_bar_ui(frame: &mut Frame, theme: &Theme, app_state: &AppState, area: Rect) {
+    let mut text = String::new();
+    if let Some(op) = &app_state.background_op {
+        let frames = if app_state.config.ascii() { r"|/-\" } else { SPINNER };
+        let frames: Vec<char> = frames.chars().collect();
+        let spinner = frames[app_state.spinner_frame as usize % frames.len()];
+        text = format!(" {} {} ", spinner, op);
+    }
+    if app_state.macro_recording {
+        text = format!("{} {}", text, app_state.strings.recording_macro);
+    }
+    if !app_state.marked.is_empty() {
+        text = format!("{} {}", text, fill(&app_state.strings.marked_status, &[("count", &app_state.marked.len())]));
+    }
+    if let Some(pomodoro) = &app_state.pomodoro {
+        let args: [(&str, &dyn Display); 2] = [("remaining", &pomodoro.countdown()), ("percent", &pomodoro.percent_done())];
+        text = format!("{} {}", text, fill(&app_state.strings.pomodoro_status, &args));
+    }
+    if let Some(message) = &app_state.status_message {
+        text = format!("{} {}", text, message);
+    } else if let Some(name) = &app_state.active_filter {
+        text = format!("{} {}", text, fill(&app_state.strings.filter_status, &[("name", name)]));
+    } else {
+        let s = &app_state.strings;
+        let query = app_state.search_input.value();
+        let filters = active_filters(app_state);
+        match filters.as_slice() {
+            [] => {}
+            // On their own, a search and due today keep their own hints
+            [_] if !query.is_empty() => {
+                let args: [(&str, &dyn Display); 2] = [("query", &query), ("count", &app_state.visible_indices().len())];
+                text = format!("{} {}", text, fill(&s.search_status, &args));
+            }
+            [_] if app_state.filter_due_today => text = format!("{} {}", text, s.due_today_status),
+            _ => {
+                let filters = filters.join(" + ");
+                text = format!("{} {}", text, fill(&s.filtered_status, &[("filters", &filters)]));
+            }
+        }
+    }
+    // With nothing else to say the main list gets its most used keys and how much is done
+    let hints = text.is_empty() && app_state.current_screen == CurrentScreen::Main;
+    if hints {
+        let config = &app_state.config;
+        let args: [(&str, &dyn Display); 4] = [
+            ("add", &config.add_key),
+            ("delete", &config.delete_key),
+            ("search", &config.search_key),
+            ("quit", &config.quit_key),
+        ];
+        text = format!(" {}", fill(&app_state.strings.main_keys, &args));
+    }
+    frame.render_widget(Paragraph::new(text).fg(theme.secondary_fg).bg(theme.status_bar_bg), area);
+    if !app_state.numeric_prefix.is_empty() {
+        let prefix = format!("{} ", app_state.numeric_prefix);
+        frame.render_widget(Line::from(prefix).right_aligned().fg(theme.secondary_fg), area);
+    } else if hints {
+        let total = app_state.iter_unfiltered().count();
+        let done = app_state.iter_unfiltered().filter(|item| item.done).count();
+        let mark = if app_state.config.ascii() { "" } else { "✓ " };
+        let args: [(&str, &dyn Display); 2] = [("done", &done), ("total", &total)];
+        let progress = format!("{}{} ", mark, fill(&app_state.strings.progress, &args));
+        frame.render_widget(Line::from(progress).right_aligned().fg(Color::Green), area);
+    }
+}
+
+// Countdown next to the item the pomodoro runs on, flashing red once the time is up
+fn pomodoro_span(pomodoro: &Pomodoro, ascii: bool, theme: &Theme) -> Span<'static> {
+    let text = format!("{} {}", if ascii { "T" } else { "⏱" }, pomodoro.countdown());
+    match (pomodoro.expired(), pomodoro.flash_on()) {
+        (false, _) => Span::styled(text, Color::Yellow),
+        (true, true) => Span::styled(text, Style::new().fg(Color::Red).add_modifier(Modifier::BOLD)),
+        (true, false) => Span::styled(text, theme.faint_fg),
+    }
+}
+
+// What narrows the list besides the config, in the order the status bar lists them
+fn active_filters(app_state: &AppState) -> Vec<String> {
+    let s = &app_state.strings;
+    let mut filters = vec![];
+    if app_state.filter_hide_done {
+        filters.push(s.pending_filter.clone());
+    }
+    for tag in &app_state.filter_tags {
+        filters.push(format!("#{}", tag));
+    }
+    if let Some(priority) = app_state.filter_priority {
+        filters.push(fill(&s.priority_filter, &[("priority", &priority.label())]));
+    }
+    if app_state.filter_due_today {
+        filters.push(s.due_today_filter.clone());
+    }
+    if app_state.filter_overdue {
+        filters.push(s.overdue_filter.clone());
+    }
+    let query = app_state.search_input.value();
+    if !query.is_empty() {
+        filters.push(format!("\"{}\"", query));
+    }
+    filters
+}
+
+// Search input on the bottom row, over the status bar
+fn search_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let [_, area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    let s = &app_state.strings;
+    let hint = format!(" {} ", if app_state.search_fuzzy { &s.fuzzy } else { &s.exact });
+    let prompt = "/";
+    let width = (area.width as usize).saturating_sub(prompt.len() + hint.len());
+    let scroll = app_state.search_input.visual_scroll(width);
+    clear(frame, theme, area);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            prompt.fg(theme.muted_fg),
+            app_state.search_input.value().chars().skip(scroll).collect::<String>().into(),
+        ])),
+        area,
+    );
+    frame.render_widget(Line::from(hint).right_aligned().fg(theme.muted_fg), area);
+    let x = app_state.search_input.visual_cursor().max(scroll) - scroll + prompt.len();
+    frame.set_cursor_position((area.x + x as u16, area.y));
+    Ok(())
+}
+
+fn command_palette_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let commands = palette::matching(app_state.palette_input.value());
+    let height = (commands.len() as u16 + 4).min(frame.area().height);
+    let area = popup_area(frame.area(), 50, height);
+    let block = bordered(&app_state.config)
+        .border_style(Style::new().fg(theme.list_border))
+        .title(Line::from(app_state.strings.commands.as_str()).centered().fg(theme.title));
+    let inner = block.inner(area);
+    clear(frame, theme, area);
+    frame.render_widget(block, area);
+
+    let [input_area, list_area] =
+        Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).areas(inner);
+    let scroll = app_state.palette_input.visual_scroll(input_area.width as usize - 2);
+    frame.render_widget(
+        Paragraph::new(format!("> {}", app_state.palette_input.value()))
+            .scroll((0, scroll as u16))
+            .block(bordered(&app_state.config).borders(Borders::BOTTOM).border_style(theme.separator_fg)),
+        input_area,
+    );
+    let x = app_state.palette_input.visual_cursor().max(scroll) - scroll + 2;
+    frame.set_cursor_position((input_area.x + x as u16, input_area.y));
+
+    let items: Vec<ListItem> = commands
+        .iter()
+        .map(|command| ListItem::new(format!(" {}", command.label())))
+        .collect();
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD));
+    StatefulWidget::render(lis, list_area, frame.buffer_mut(), &mut app_state.palette_state);
+    Ok(())
+}
+
+fn search_replace_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let area = popup_area(frame.area(), 60.min(frame.area().width), 8);
+    let s = &app_state.strings;
+    let case = if app_state.search_case_sensitive {
+        &s.case_sensitive
+    } else {
+        &s.ignore_case
+    };
+    let block = bordered(&app_state.config)
+        .border_style(Style::new().fg(theme.list_border))
+        .title(Line::from(s.search_and_replace.as_str()).centered().fg(theme.title))
+        .title_bottom(Line::from(format!(" {} ", case)).centered());
+    let inner = block.inner(area);
+    clear(frame, theme, area);
+    frame.render_widget(block, area);
+
+    let [find_area, replace_area] =
+        Layout::vertical([Constraint::Length(3), Constraint::Length(3)]).areas(inner);
+    let fields = [
+        (&app_state.find_input, &s.find, find_area, !app_state.replace_field_active),
+        (&app_state.replace_input, &s.replace, replace_area, app_state.replace_field_active),
+    ];
+    for (input, title, area, active) in fields {
+        let scroll = input.visual_scroll(area.width.saturating_sub(2) as usize);
+        let border = if active { theme.input_border } else { theme.inactive_border };
+        frame.render_widget(
+            Paragraph::new(input.value())
+                .scroll((0, scroll as u16))
+                .block(bordered(&app_state.config).border_style(border).title(title.as_str())),
+            area,
+        );
+        if active {
+            let x = input.visual_cursor().max(scroll) - scroll + 1;
+            frame.set_cursor_position((area.x + x as u16, area.y + 1));
+        }
+    }
+    Ok(())
+}
+
+fn color_picker_ui(frame: &mut Frame, theme: &Theme, app_state: &AppState) {
+    let swatches: Vec<Span> = LABEL_COLORS
+        .iter()
+        .enumerate()
+        .map(|(i, &(r, g, b))| {
+            let swatch = Span::styled(if app_state.config.ascii() { " ## " } else { " ██ " }, Color::Rgb(r, g, b));
+            if i == app_state.color_picker_index {
+                swatch.bg(theme.item_selected_bg)
+            } else {
+                swatch
+            }
+        })
+        .collect();
+    let area = popup_area(frame.area(), LABEL_COLORS.len() as u16 * 4 + 2, 3);
+    clear(frame, theme, area);
+    frame.render_widget(
+        Paragraph::new(Line::from(swatches)).block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(app_state.strings.color_label.as_str()).centered().fg(theme.title)),
+        ),
+        area,
+    );
+}
+
+// Small centered question box over the current screen
+pub fn confirm_ui(frame: &mut Frame, theme: &Theme, config: &Config, title: &str, text: &str) {
+    let width = (text.chars().count() as u16 + 4).min(frame.area().width);
+    let area = popup_area(frame.area(), width, 3);
+    clear(frame, theme, area);
+    frame.render_widget(
+        Paragraph::new(text).centered().block(
+            bordered(config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(title).centered().fg(theme.title)),
+        ),
+        area,
+    );
+}
+
+// Wrapped to at most 60 columns, centered over whatever screen is open
+// The message centered in the list, a literal \n in it breaks the line
+fn empty_list_ui(frame: &mut Frame, theme: &Theme, message: &str, area: Rect) {
+    let message = message.replace("\\n", "\n");
+    let height = message.lines().count() as u16;
+    let [area] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(area);
+    frame.render_widget(Paragraph::new(message).centered().wrap(Wrap { trim: true }).fg(theme.muted_fg), area);
+}
+
+fn startup_message_ui(frame: &mut Frame, theme: &Theme, config: &Config, message: &str) {
+    let text_width = Line::from(message).width() as u16;
+    let width = (text_width + 4).clamp(10, 64).min(frame.area().width);
+    // Long messages wrap, leave room for the extra rows
+    let height = (text_width.saturating_sub(1) / width.saturating_sub(2).max(1) + 3).min(frame.area().height);
+    let area = popup_area(frame.area(), width, height);
+    clear(frame, theme, area);
+    frame.render_widget(
+        Paragraph::new(message)
+            .centered()
+            .wrap(Wrap { trim: true })
+            .block(bordered(config).border_style(Style::new().fg(theme.list_border))),
+        area,
+    );
+}
+
+fn main_title(app_state: &AppState) -> String {
+    let name = &app_state.lists[app_state.active_list].name;
+    let today = Local::now().date_naive();
+    let (mut total, mut done, mut overdue) = (0, 0, 0);
+    for item in app_state.iter_unfiltered() {
+        total += 1;
+        if item.done {
+            done += 1;
+        } else if item.due_date.is_some_and(|due| due < today) {
+            overdue += 1;
+        }
+    }
+    let ctx = TitleContext {
+        list_name: name,
+        total,
+        pending: total - done,
+        done,
+        overdue,
+    };
+    let title = format_title(&app_state.config.list_title_format, &ctx);
+    let inbox_len = app_state.inbox_len();
+    let mut title = if inbox_len > 0 && name != INBOX_LIST {
+        let args: [(&str, &dyn Display); 2] = [("title", &title), ("count", &inbox_len)];
+        fill(&app_state.strings.inbox_count, &args)
+    } else {
+        title
+    };
+    let sort_order = app_state.effective_config().sort_order;
+    if sort_order != SortOrder::Manual {
+        title = format!("{} - {}", title, sort_order.label());
+    }
+    // Whether Ctrl+Z and Ctrl+Y have anything to do
+    let ascii = app_state.config.ascii();
+    if app_state.undo_stack.can_undo() {
+        title.push_str(if ascii { " [undo]" } else { " ↶" });
+    }
+    if app_state.undo_stack.can_redo() {
+        title.push_str(if ascii { " [redo]" } else { " ↷" });
+    }
+    title
+}
+
+pub fn edit_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    if let Some(_edit_item) = &app_state.currently_editing {
+        let area = Rect::new(0, 0, frame.area().width.max(3) - 3, 3);
+        let scroll = app_state.input.visual_scroll(area.width as usize);
+        let input = Paragraph::new(app_state.input.value())
+            .style(Style::default())
+            .scroll((0, scroll as u16))
+            .block(input_block(&app_state.strings.edit_item, app_state));
+        frame.render_widget(input, area);
+        // Ratatui hides the cursor unless it's explicitly set. Position the  cursor past the
+        // end of the input text and one line down from the border to the input line
+        if app_state.add_active_field == AddField::Description {
+            let x = app_state.input.visual_cursor().max(scroll) - scroll + 1;
+            frame.set_cursor_position((area.x + x as u16, area.y + 1));
+        }
+        let due = Rect::new(0, area.bottom(), area.width, 3).intersection(frame.area());
+        let input = &app_state.edit_due_input;
+        let active = app_state.add_active_field == AddField::Due;
+        let border = if active { theme.input_border } else { theme.inactive_border };
+        let scroll = input.visual_scroll(due.width.saturating_sub(2) as usize);
+        frame.render_widget(
+            Paragraph::new(input.value())
+                .scroll((0, scroll as u16))
+                .block(bordered(&app_state.config).border_style(border).title(app_state.strings.due_field.as_str())),
+            due,
+        );
+        if active {
+            let x = input.visual_cursor().max(scroll) - scroll + 1;
+            frame.set_cursor_position((due.x + x as u16, due.y + 1));
+        }
+        let area = reminder_ui(frame, theme, app_state, due.bottom());
+        frame.render_widget(
+            Paragraph::new(app_state.strings.edit_keys.as_str()).fg(theme.muted_fg),
+            Rect::new(1, area.bottom(), area.width.saturating_sub(1), 1).intersection(frame.area()),
+        );
+    } else {
+        app_state.current_screen = CurrentScreen::Main;
+    }
+    Ok(())
+}
+
+pub fn add_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let area = Rect::new(0, 0, frame.area().width.max(3) - 3, 3);
+    let scroll = app_state.input.visual_scroll(area.width as usize);
+    let input = Paragraph::new(app_state.input.value())
+        .style(Style::default())
+        .scroll((0, scroll as u16))
+        .block(input_block(&app_state.strings.new_item, app_state));
+    frame.render_widget(input, area);
+    // Ratatui hides the cursor unless it's explicitly set. Position the  cursor past the
+    // end of the input text and one line down from the border to the input line
+    if app_state.add_active_field == AddField::Description {
+        let x = app_state.input.visual_cursor().max(scroll) - scroll + 1;
+        frame.set_cursor_position((area.x + x as u16, area.y + 1));
+    }
+    let row = Rect::new(0, area.bottom(), area.width, 3).intersection(frame.area());
+    let areas: [Rect; 3] = Layout::horizontal([Constraint::Ratio(1, 3); 3]).areas(row);
+    let s = &app_state.strings;
+    let titles = [s.due_field.as_str(), s.priority_field.as_str(), s.tags_field.as_str()];
+    let fields = [AddField::Due, AddField::Priority, AddField::Tags];
+    for (((area, input), title), field) in areas.into_iter().zip(&app_state.add_fields).zip(titles).zip(fields) {
+        let active = app_state.add_active_field == field;
+        let border = if active { theme.input_border } else { theme.inactive_border };
+        let scroll = input.visual_scroll(area.width.saturating_sub(2) as usize);
+        frame.render_widget(
+            Paragraph::new(input.value())
+                .scroll((0, scroll as u16))
+                .block(bordered(&app_state.config).border_style(border).title(title)),
+            area,
+        );
+        if active {
+            let x = input.visual_cursor().max(scroll) - scroll + 1;
+            frame.set_cursor_position((area.x + x as u16, area.y + 1));
+        }
+    }
+    let area = reminder_ui(frame, theme, app_state, row.bottom());
+
+    frame.render_widget(
+        Paragraph::new(app_state.strings.use_template.as_str()).fg(theme.muted_fg),
+        Rect::new(1, area.bottom(), area.width.saturating_sub(1), 1).intersection(frame.area()),
+    );
+
+    Ok(())
+}
+
+// Last input of the Add/Edit screens, reached with Tab
+fn reminder_ui(frame: &mut Frame, theme: &Theme, app_state: &AppState, y: u16) -> Rect {
+    let area = Rect::new(0, y, frame.area().width.max(3) - 3, 3).intersection(frame.area());
+    let scroll = app_state.reminder_input.visual_scroll(area.width.saturating_sub(2) as usize);
+    let border = if app_state.reminder_field_active {
+        theme.input_border
+    } else {
+        theme.inactive_border
+    };
+    frame.render_widget(
+        Paragraph::new(app_state.reminder_input.value())
+            .scroll((0, scroll as u16))
+            .block(
+                bordered(&app_state.config)
+                    .border_style(border)
+                    .title(app_state.strings.remind_me_at.as_str()),
+            ),
+        area,
+    );
+    if app_state.reminder_field_active {
+        let x = app_state.reminder_input.visual_cursor().max(scroll) - scroll + 1;
+        frame.set_cursor_position((area.x + x as u16, area.y + 1));
+    }
+    area
+}
+
+// Validation errors replace the title of the input box
+fn input_block<'a>(title: &'a str, app_state: &AppState) -> Block<'a> {
+    match &app_state.input_error {
+        Some(error) => bordered(&app_state.config)
+            .title(format!("{} - {}", title, error))
+            .border_style(Style::new().red()),
+        None => bordered(&app_state.config).title(title),
+    }
+}
+
+pub fn inbox_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let prompt = app_state.strings.inbox_prompt.clone();
+    let prompt = prompt.as_str();
+    let area = Rect::new(0, 0, frame.area().width, 1);
+    let width = (area.width as usize).saturating_sub(prompt.len() + 1);
+    let scroll = app_state.inbox_input.visual_scroll(width);
+    let input = Paragraph::new(Line::from(vec![
+        prompt.fg(theme.muted_fg),
+        app_state.inbox_input.value().chars().skip(scroll).collect::<String>().into(),
+    ]));
+    frame.render_widget(input, area);
+    let x = app_state.inbox_input.visual_cursor().max(scroll) - scroll + prompt.len();
+    frame.set_cursor_position((area.x + x as u16, area.y));
+    Ok(())
+}
+
+pub fn template_picker_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    add_ui(frame, theme, app_state)?;
+    let items: Vec<ListItem> = app_state
+        .config
+        .templates
+        .iter()
+        .map(|template| ListItem::new(format!(" {}", template.name)))
+        .collect();
+    let height = (items.len() as u16 + 2).min(frame.area().height);
+    let area = popup_area(frame.area(), 40, height);
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(app_state.strings.templates.clone()).centered().fg(theme.title)),
+        );
+    clear(frame, theme, area);
+    StatefulWidget::render(lis, area, frame.buffer_mut(), &mut app_state.template_state);
+    Ok(())
+}
+
+fn spell_check_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let word = app_state.spell_queue.first().map_or("", |(_, word)| word.as_str());
+    let mut items: Vec<ListItem> = app_state
+        .spell_suggestions
+        .iter()
+        .map(|suggestion| ListItem::new(format!(" {}", suggestion)))
+        .collect();
+    if items.is_empty() {
+        items.push(ListItem::new(format!(" {}", app_state.strings.no_suggestions)).fg(theme.muted_fg));
+    }
+    let hint = app_state.strings.spell_check_keys.clone();
+    let height = (items.len() as u16 + 3).min(frame.area().height);
+    let area = popup_area(frame.area(), 40, height);
+    let [list_area, hint_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area);
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(format!("'{}'", word)).centered().fg(theme.title)),
+        );
+    clear(frame, theme, area);
+    StatefulWidget::render(lis, list_area, frame.buffer_mut(), &mut app_state.spell_state);
+    frame.render_widget(
+        Line::from(hint).centered().bg(theme.item_selected_bg),
+        hint_area,
+    );
+    Ok(())
+}
+
+fn split_list_name_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let area = popup_area(frame.area(), 40, 3);
+    let scroll = app_state.split_name_input.visual_scroll(area.width as usize - 2);
+    clear(frame, theme, area);
+    frame.render_widget(
+        Paragraph::new(app_state.split_name_input.value())
+            .scroll((0, scroll as u16))
+            .block(bordered(&app_state.config).title(app_state.strings.new_list_name.as_str())),
+        area,
+    );
+    let x = app_state.split_name_input.visual_cursor().max(scroll) - scroll + 1;
+    frame.set_cursor_position((area.x + x as u16, area.y + 1));
+    Ok(())
+}
+
+fn undo_history_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let now = Local::now();
+    let items: Vec<ListItem> = app_state
+        .undo_stack
+        .entries()
+        .iter()
+        .rev()
+        .map(|snapshot| {
+            ListItem::new(Line::from(vec![
+                Span::raw(format!(" {}", snapshot.label)),
+                Span::styled(format!(", {}", format_ago(snapshot.at, now)), theme.muted_fg),
+            ]))
+        })
+        .collect();
+    let height = (items.len() as u16 + 2).min(frame.area().height);
+    let area = popup_area(frame.area(), 50, height);
+    let s = &app_state.strings;
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(s.undo_history.as_str()).centered().fg(theme.title))
+                .title_bottom(Line::from(s.undo_history_keys.as_str()).centered()),
+        );
+    clear(frame, theme, area);
+    StatefulWidget::render(lis, area, frame.buffer_mut(), &mut app_state.undo_history_state);
+    Ok(())
+}
+
+fn sort_menu_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let items: Vec<ListItem> = SortOrder::ALL.iter().map(|order| ListItem::new(format!(" {}", order.label()))).collect();
+    let height = (items.len() as u16 + 2).min(frame.area().height);
+    let area = popup_area(frame.area(), 30, height);
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(app_state.strings.sort_by.as_str()).centered().fg(theme.title)),
+        );
+    clear(frame, theme, area);
+    StatefulWidget::render(lis, area, frame.buffer_mut(), &mut app_state.sort_menu_state);
+    Ok(())
+}
+
+// Every key from help::KEYS, with the configurable ones as they are set
+fn help_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let area = popup_area(frame.area(), frame.area().width * 4 / 5, frame.area().height * 4 / 5);
+    let config = &app_state.config;
+    let keys = help::KEYS.iter().flat_map(|(_, keys)| keys.iter());
+    let width = keys.map(|(key, _)| key.text(config).width()).max().unwrap_or(0);
+    let mut lines = vec![];
+    for (context, keys) in help::KEYS {
+        if !lines.is_empty() {
+            lines.push(Line::default());
+        }
+        lines.push(Line::from(*context).fg(theme.title).bold());
+        for (key, action) in keys.iter() {
+            let key = Span::styled(format!("  {:<width$}  ", key.text(config), width = width), theme.secondary_fg);
+            lines.push(Line::from(vec![key, Span::raw(*action)]));
+        }
+    }
+    let rows = area.height.saturating_sub(2);
+    app_state.help_scroll = app_state.help_scroll.min((lines.len() as u16).saturating_sub(rows));
+    let text = Paragraph::new(lines).scroll((app_state.help_scroll, 0)).block(
+        bordered(&app_state.config)
+            .border_style(Style::new().fg(theme.list_border))
+            .title(Line::from(app_state.strings.help.as_str()).centered().fg(theme.title)),
+    );
+    clear(frame, theme, area);
+    frame.render_widget(text, area);
+    Ok(())
+}
+
+fn tag_filter_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let items: Vec<ListItem> =
+        app_state.unfiltered_tags().into_iter().map(|tag| ListItem::new(format!(" #{}", tag))).collect();
+    let height = (items.len() as u16 + 2).min(frame.area().height);
+    let area = popup_area(frame.area(), 30, height);
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(app_state.strings.filter_by_tag.as_str()).centered().fg(theme.title)),
+        );
+    clear(frame, theme, area);
+    StatefulWidget::render(lis, area, frame.buffer_mut(), &mut app_state.tag_filter_state);
+    Ok(())
+}
+
+// Floats below and right of the click, moved left or up where it would leave the screen
+fn context_menu_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let labels: Vec<&str> = if app_state.current_screen == CurrentScreen::PriorityMenu {
+        PRIORITIES.iter().map(|priority| priority.label()).collect()
+    } else {
+        MenuAction::ALL.iter().map(|action| action.label()).collect()
+    };
+    let width = labels.iter().map(|label| label.chars().count()).max().unwrap_or(0) as u16 + 4;
+    let screen = frame.area();
+    let (width, height) = (width.min(screen.width), (labels.len() as u16 + 2).min(screen.height));
+    let (column, row) = app_state.context_menu_position;
+    let x = column.min(screen.right().saturating_sub(width));
+    let y = if row + 1 + height <= screen.bottom() { row + 1 } else { row.saturating_sub(height) };
+    let area = Rect::new(x, y, width, height);
+    app_state.context_menu_area = area;
+    let items: Vec<ListItem> = labels.into_iter().map(|label| ListItem::new(format!(" {}", label))).collect();
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .block(bordered(&app_state.config).border_style(Style::new().fg(theme.list_border)));
+    clear(frame, theme, area);
+    StatefulWidget::render(lis, area, frame.buffer_mut(), &mut app_state.context_menu_state);
+    Ok(())
+}
+
+fn filter_name_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let area = popup_area(frame.area(), 40, 3);
+    let scroll = app_state.filter_name_input.visual_scroll(area.width as usize - 2);
+    clear(frame, theme, area);
+    frame.render_widget(
+        Paragraph::new(app_state.filter_name_input.value())
+            .scroll((0, scroll as u16))
+            .block(bordered(&app_state.config).title(app_state.strings.filter_name.as_str())),
+        area,
+    );
+    let x = app_state.filter_name_input.visual_cursor().max(scroll) - scroll + 1;
+    frame.set_cursor_position((area.x + x as u16, area.y + 1));
+    Ok(())
+}
+
+fn move_to_position_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let area = Rect::new(0, 0, frame.area().width.max(3) - 3, 3);
+    clear(frame, theme, area);
+    frame.render_widget(
+        Paragraph::new(app_state.position_input.value())
+            .block(input_block(&app_state.strings.move_to_position, app_state)),
+        area,
+    );
+    let x = app_state.position_input.visual_cursor() + 1;
+    frame.set_cursor_position((area.x + x as u16, area.y + 1));
+    Ok(())
+}
+
+fn load_filter_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let items: Vec<ListItem> =
+        app_state.config.saved_filters.keys().map(|name| ListItem::new(format!(" {}", name))).collect();
+    let height = (items.len() as u16 + 2).min(frame.area().height);
+    let area = popup_area(frame.area(), 40, height);
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(app_state.strings.saved_filters.as_str()).centered().fg(theme.title)),
+        );
+    clear(frame, theme, area);
+    StatefulWidget::render(lis, area, frame.buffer_mut(), &mut app_state.saved_filter_state);
+    Ok(())
+}
+
+// Made-up items showing each kind of row, so every theme can be judged on the same list
+fn theme_preview_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let today = Local::now().date_naive();
+    let samples = [
+        TodoItem::builder().description("Renew passport").priority(Priority::High).due(today - chrono::Duration::days(2)),
+        TodoItem::builder().description("Plan the team offsite").priority(Priority::Medium).due(today),
+        TodoItem::builder().description("Read https://ratatui.rs").tags(vec!["reading".to_owned()]),
+        TodoItem::builder().description("Water the plants").done(true),
+        TodoItem::builder().description("Call the dentist").priority(Priority::Low).due(today + chrono::Duration::days(3)),
+    ];
+    let samples: Vec<TodoItem> = samples.into_iter().filter_map(|builder| builder.build().ok()).collect();
+    let s = &app_state.strings;
+    let name = THEMES[app_state.theme_preview].name;
+    let [area, hint_area] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    let items: Vec<ListItem> = samples.iter().map(|item| item.to_list_item(&app_state.config, theme)).collect();
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .highlight_symbol(app_state.config.highlight_symbol.as_str())
+        .block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(fill(&s.theme_preview, &[("name", &name)])).centered().fg(theme.title)),
+        );
+    let inner = bordered(&app_state.config).inner(area);
+    StatefulWidget::render(lis, area, frame.buffer_mut(), &mut ListState::default().with_selected(Some(1)));
+    for (row, item) in samples.iter().enumerate() {
+        if let Some(metadata) = item.metadata(&app_state.config, today) {
+            let row_area = Rect::new(inner.x, inner.y + row as u16, inner.width.saturating_sub(1), 1);
+            frame.render_widget(Line::from(metadata).right_aligned().fg(theme.muted_fg), row_area);
+        }
+    }
+    frame.render_widget(Paragraph::new(s.theme_preview_keys.as_str()).fg(theme.secondary_fg), hint_area);
+    Ok(())
+}
+
+fn batch_tag_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let area = popup_area(frame.area(), 50, 3);
+    let scroll = app_state.tag_input.visual_scroll(area.width as usize - 2);
+    let title = match app_state.tag_item {
+        Some(_) => app_state.strings.add_tag.clone(),
+        None => fill(&app_state.strings.tag_visible, &[("count", &app_state.visible_indices().len())]),
+    };
+    clear(frame, theme, area);
+    frame.render_widget(
+        Paragraph::new(app_state.tag_input.value())
+            .scroll((0, scroll as u16))
+            .block(bordered(&app_state.config).title(title)),
+        area,
+    );
+    let x = app_state.tag_input.visual_cursor().max(scroll) - scroll + 1;
+    frame.set_cursor_position((area.x + x as u16, area.y + 1));
+    Ok(())
+}
+
+fn github_import_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let area = popup_area(frame.area(), 50, 3);
+    let scroll = app_state.github_input.visual_scroll(area.width as usize - 2);
+    clear(frame, theme, area);
+    frame.render_widget(
+        Paragraph::new(app_state.github_input.value())
+            .scroll((0, scroll as u16))
+            .block(bordered(&app_state.config).title(app_state.strings.github_repo.as_str())),
+        area,
+    );
+    let x = app_state.github_input.visual_cursor().max(scroll) - scroll + 1;
+    frame.set_cursor_position((area.x + x as u16, area.y + 1));
+    Ok(())
+}
+
+fn import_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let area = popup_area(frame.area(), 60, 3);
+    let scroll = app_state.import_input.visual_scroll(area.width as usize - 2);
+    clear(frame, theme, area);
+    frame.render_widget(
+        Paragraph::new(app_state.import_input.value())
+            .scroll((0, scroll as u16))
+            .block(bordered(&app_state.config).title(app_state.strings.import_file.as_str())),
+        area,
+    );
+    let x = app_state.import_input.visual_cursor().max(scroll) - scroll + 1;
+    frame.set_cursor_position((area.x + x as u16, area.y + 1));
+    Ok(())
+}
+
+fn merge_list_picker_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let items: Vec<ListItem> = app_state
+        .merge_targets()
+        .into_iter()
+        .map(|l| ListItem::new(format!(" {}", app_state.lists[l].name)))
+        .collect();
+    let height = (items.len() as u16 + 2).min(frame.area().height);
+    let area = popup_area(frame.area(), 40, height);
+    let source = &app_state.lists[app_state.active_list].name;
+    let title = fill(&app_state.strings.merge_into, &[("source", source)]);
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(title).centered().fg(theme.title)),
+        );
+    clear(frame, theme, area);
+    StatefulWidget::render(lis, area, frame.buffer_mut(), &mut app_state.merge_state);
+    Ok(())
+}
+
+fn daily_prompt_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let area = popup_area(frame.area(), 60, 3);
+    let scroll = app_state.daily_input.visual_scroll(area.width as usize - 2);
+    let counter = format!(" {}/3 ", app_state.daily_count + 1);
+    clear(frame, theme, area);
+    frame.render_widget(
+        Paragraph::new(app_state.daily_input.value()).scroll((0, scroll as u16)).block(
+            bordered(&app_state.config)
+                .title(app_state.strings.daily_prompt.as_str())
+                .title_bottom(Line::from(counter).right_aligned()),
+        ),
+        area,
+    );
+    let x = app_state.daily_input.visual_cursor().max(scroll) - scroll + 1;
+    frame.set_cursor_position((area.x + x as u16, area.y + 1));
+    Ok(())
+}
+
+fn recently_done_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let now = Local::now();
+    let s = &app_state.strings;
+    let mut items: Vec<ListItem> = app_state
+        .recently_done()
+        .into_iter()
+        .filter_map(|i| app_state.get(i))
+        .map(|item| {
+            let mut line = item.to_line(&app_state.config, theme);
+            let ago = item.completed_at.map(|at| format_ago(at, now)).unwrap_or_default();
+            line.spans.push(Span::styled(format!("  {}", ago), theme.muted_fg));
+            ListItem::new(line)
+        })
+        .collect();
+    if items.is_empty() {
+        items.push(ListItem::new(format!(" {}", s.nothing_done_recently)).fg(theme.muted_fg));
+    }
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(s.recently_done.as_str()).centered().fg(theme.title))
+                .title_bottom(Line::from(format!(" {} ", s.recently_done_keys)).centered()),
+        );
+    StatefulWidget::render(lis, frame.area(), frame.buffer_mut(), &mut app_state.recent_state);
+    Ok(())
+}
+
+fn archive_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let s = &app_state.strings;
+    let mut items: Vec<ListItem> = app_state.archived.iter().map(|item| item.to_list_item(&app_state.config, theme)).collect();
+    if items.is_empty() {
+        items.push(ListItem::new(format!(" {}", s.archive_empty)).fg(theme.muted_fg));
+    }
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(s.archive.as_str()).centered().fg(theme.title))
+                .title_bottom(Line::from(format!(" {} ", s.archive_keys)).centered()),
+        );
+    StatefulWidget::render(lis, frame.area(), frame.buffer_mut(), &mut app_state.archive_state);
+    Ok(())
+}
+
+// One column per day, each item a bar from the day it was added to its due date
+// Every field of the selected item, unset ones as a dash
+fn item_detail_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let Some(item) = app_state.selected_item_index().and_then(|i| app_state.get(i)) else {
+        app_state.current_screen = CurrentScreen::Main;
+        return Ok(());
+    };
+    let s = &app_state.strings;
+    let config = &app_state.config;
+    let datetime = |at: &DateTime<Local>| at.format(&config.datetime_format).to_string();
+    let none = || "-".to_owned();
+    let parent = item
+        .parent_id
+        .and_then(|id| app_state.find_item(id))
+        .and_then(|(list, index)| app_state.lists[list].get(index))
+        .map(|parent| parent.description.clone());
+    let fields = [
+        (&s.field_description, item.description.clone()),
+        (&s.field_status, if item.done { s.status_done.clone() } else { s.status_pending.clone() }),
+        (&s.field_priority, item.priority.label().to_owned()),
+        (&s.field_due, item.due_date.map_or_else(none, |due| config.format_date(due, Local::now().date_naive()))),
+        (&s.field_tags, if item.tags.is_empty() { none() } else { item.tags.join(" ") }),
+        (&s.field_created, datetime(&item.created_at)),
+        (&s.field_completed, item.completed_at.as_ref().map_or_else(none, datetime)),
+        (&s.field_reminder, item.reminder_at.as_ref().map_or_else(none, datetime)),
+        (&s.field_color, item.color_label.map_or_else(none, |(r, g, b)| format!("#{:02x}{:02x}{:02x}", r, g, b))),
+        (&s.field_url, item.url.clone().unwrap_or_else(none)),
+        (&s.field_parent, parent.unwrap_or_else(none)),
+        (&s.field_id, item.id.to_string()),
+    ];
+    let width = fields.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0) + 2;
+    let mut lines: Vec<Line> = fields
+        .into_iter()
+        .map(|(label, value)| {
+            Line::from(vec![Span::styled(format!("{:width$}", label, width = width), theme.muted_fg), Span::raw(value)])
+        })
+        .collect();
+    let mut section = |title: &str, rows: Vec<Line<'static>>| {
+        if !rows.is_empty() {
+            lines.push(Line::default());
+            lines.push(Line::from(title.to_owned()).fg(theme.title).bold());
+            lines.extend(rows);
+        }
+    };
+    let notes = item.notes.as_deref().unwrap_or_default().lines().map(|line| Line::from(line.to_owned())).collect();
+    section(&s.notes, notes);
+    let comments = item
+        .comments
+        .iter()
+        .map(|(at, text)| Line::from(vec![Span::styled(format!("{}  ", datetime(at)), theme.muted_fg), Span::raw(text.clone())]))
+        .collect();
+    section(&s.comments, comments);
+    let arrow = if config.ascii() { "->" } else { "→" };
+    let related = app_state.resolve_links(item.id).iter().map(|link| Line::from(format!("{} {}", arrow, link.description))).collect();
+    section(&s.related, related);
+
+    let block = bordered(config)
+        .border_style(Style::new().fg(theme.list_border))
+        .title(Line::from(s.item_detail.as_str()).centered().fg(theme.title))
+        .title_bottom(Line::from(format!(" {} ", s.item_detail_keys)).centered())
+        .padding(Padding::horizontal(1));
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).block(block), frame.area());
+    Ok(())
+}
+
+fn timeline_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let today = Local::now().date_naive();
+    let s = &app_state.strings;
+    let config = &app_state.config;
+    let (bar, due_mark, today_mark) = if config.ascii() { ('=', '*', '|') } else { ('━', '◆', '│') };
+    let block = bordered(config)
+        .border_style(Style::new().fg(theme.list_border))
+        .title(Line::from(s.timeline.as_str()).centered().fg(theme.title))
+        .title_bottom(Line::from(format!(" {} ", s.timeline_keys)).centered());
+    let inner = block.inner(frame.area());
+    frame.render_widget(block, frame.area());
+    let [header_area, list_area] = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(inner);
+    let label_width = (inner.width / 3).min(24) as usize;
+    let days = (inner.width as usize).saturating_sub(label_width + 3);
+    let date = |column: usize| app_state.timeline_start + Duration::days(column as i64);
+
+    // Dates at the start of each week, the first one also when it's mid-week and there's room
+    let mut header = vec![' '; days];
+    let mut column = 0;
+    let mut free = 0;
+    while column < days {
+        let label: Vec<char> = date(column).format("%m-%d").to_string().chars().collect();
+        if column >= free && column + label.len() <= days {
+            header[column..column + label.len()].copy_from_slice(&label);
+            free = column + label.len() + 1;
+        }
+        let weekday = date(column).weekday().days_since(config.first_day_of_week) as usize;
+        column += if weekday == 0 { 7 } else { 7 - weekday };
+    }
+    if let Some(column) = (0..days).find(|&column| date(column) == today && header[column] == ' ') {
+        header[column] = today_mark;
+    }
+    let header: String = header.into_iter().collect();
+    let header = format!("{:width$}   {}", "", header, width = label_width);
+    frame.render_widget(Paragraph::new(header).fg(theme.secondary_fg), header_area);
+
+    let mut items: Vec<ListItem> = app_state
+        .timeline_items()
+        .into_iter()
+        .filter_map(|i| app_state.get(i))
+        .filter_map(|item| Some((item, item.due_date?)))
+        .map(|(item, due)| {
+            let color = if item.done {
+                theme.item_done_fg
+            } else if due < today {
+                Color::Red
+            } else {
+                theme.item_pending_fg
+            };
+            let label: String = item.description.chars().take(label_width).collect();
+            let mut spans = vec![Span::styled(format!("{:width$}   ", label, width = label_width), color)];
+            let added = item.created_at.date_naive();
+            for column in 0..days {
+                let day = date(column);
+                let span = if day == due {
+                    Span::styled(due_mark.to_string(), color)
+                } else if added <= day && day < due {
+                    Span::styled(bar.to_string(), color)
+                } else if day == today {
+                    Span::styled(today_mark.to_string(), theme.muted_fg)
+                } else {
+                    Span::raw(" ")
+                };
+                spans.push(span);
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    if items.is_empty() {
+        items.push(ListItem::new(format!(" {}", s.no_due_items)).fg(theme.muted_fg));
+    }
+    let lis = List::new(items).highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD));
+    StatefulWidget::render(lis, list_area, frame.buffer_mut(), &mut app_state.timeline_state);
+    Ok(())
+}
+
+fn stale_review_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let now = Local::now();
+    let s = &app_state.strings;
+    let items: Vec<ListItem> = app_state
+        .stale_items()
+        .into_iter()
+        .filter_map(|(l, i)| app_state.lists[l].get(i))
+        .map(|item| {
+            let mut line = item.to_line(&app_state.config, theme);
+            let age = fill(&s.stale_age, &[("days", &(now - item.created_at).num_days())]);
+            line.spans.push(Span::styled(format!("  {}", age), theme.muted_fg));
+            ListItem::new(line)
+        })
+        .collect();
+    let title = fill(&s.stale_items, &[("days", &app_state.config.stale_item_days)]);
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(title).centered().fg(theme.title))
+                .title_bottom(Line::from(format!(" {} ", s.stale_items_keys)).centered()),
+        );
+    StatefulWidget::render(lis, frame.area(), frame.buffer_mut(), &mut app_state.stale_state);
+    Ok(())
+}
+
+fn weekly_review_ui(frame: &mut Frame, theme: &Theme, app_state: &mut AppState) -> io::Result<()> {
+    let s = &app_state.strings;
+    let step = app_state.review_step();
+    let (name, step_keys) = match step {
+        ReviewStep::Completed => (&s.review_completed, None),
+        ReviewStep::Overdue => (&s.review_overdue, Some(&s.review_overdue_keys)),
+        ReviewStep::Inbox => (&s.review_inbox, Some(&s.review_inbox_keys)),
+        ReviewStep::EmptyLists => (&s.review_empty_lists, None),
+    };
+    let args: [(&str, &dyn Display); 3] = [
+        ("step", &(app_state.review_step + 1)),
+        ("steps", &ReviewStep::ALL.len()),
+        ("name", name),
+    ];
+    let marks: String = app_state.review_done.iter().map(|&done| if done { 'x' } else { '.' }).collect();
+    let title = format!(" {} [{}] ", fill(&s.weekly_review, &args), marks);
+    let keys = match step_keys {
+        Some(step_keys) => format!(" {}  {} ", step_keys, s.review_keys),
+        None => format!(" {} ", s.review_keys),
+    };
+
+    let mut items: Vec<ListItem> = if step == ReviewStep::EmptyLists {
+        app_state
+            .empty_lists()
+            .into_iter()
+            .map(|l| ListItem::new(format!(" {}", app_state.lists[l].name)))
+            .collect()
+    } else {
+        app_state
+            .review_items()
+            .into_iter()
+            .filter_map(|(l, i)| {
+                let list = &app_state.lists[l];
+                let mut line = list.get(i)?.to_line(&app_state.config, theme);
+                line.spans.insert(0, Span::styled(format!("[{}] ", list.name), theme.muted_fg));
+                Some(ListItem::new(line))
+            })
+            .collect()
+    };
+    if items.is_empty() {
+        items.push(ListItem::new(format!(" {}", s.nothing_to_review)).fg(theme.muted_fg));
+    }
+
+    let area = frame.area();
+    let [list_area, input_area] = if app_state.review_editing_due {
+        Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).areas(area)
+    } else {
+        [area, Rect::default()]
+    };
+    let lis = List::new(items)
+        .highlight_style(Style::new().bg(theme.item_selected_bg).add_modifier(Modifier::BOLD))
+        .block(
+            bordered(&app_state.config)
+                .border_style(Style::new().fg(theme.list_border))
+                .title(Line::from(title).centered().fg(theme.title))
+                .title_bottom(Line::from(keys).centered()),
+        );
+    StatefulWidget::render(lis, list_area, frame.buffer_mut(), &mut app_state.review_state);
+
+    if app_state.review_editing_due {
+        let input = &app_state.review_due_input;
+        let scroll = input.visual_scroll(input_area.width as usize - 2);
+        frame.render_widget(
+            Paragraph::new(input.value())
+                .scroll((0, scroll as u16))
+                .block(bordered(&app_state.config).title(app_state.strings.new_due_date.as_str())),
+            input_area,
+        );
+        let x = input.visual_cursor().max(scroll) - scroll + 1;
+        frame.set_cursor_position((input_area.x + x as u16, input_area.y + 1));
+    }
+    Ok(())
+}
+
+pub fn bordered(config: &Config) -> Block<'static> {
+    if config.ascii() {
+        return Block::bordered().border_set(ASCII_BORDER);
+    }
+    if config.high_contrast {
+        return Block::bordered().border_type(BorderType::Double);
+    }
+    Block::bordered().border_type(config.borders)
+}
+
+const SPINNER: &str = "⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏";
+
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+// Clear leaves the terminal's background, popups keep the theme's
+fn clear(frame: &mut Frame, theme: &Theme, area: Rect) {
+    frame.render_widget(Clear, area);
+    frame.render_widget(Block::new().style(theme.base()), area);
+}
+
+// Centered rect of the given size, clamped to the available area
+pub fn popup_area(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::TodoItem;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    // How long it takes is measured by benches/render.rs
+    #[test]
+    fn large_list_draws_only_the_visible_window() {
+        let mut app_state = AppState::default();
+        app_state.extend_items((0..10_000).map(|i| TodoItem::from(format!("Item {}", i))));
+        app_state.todo_list_state.select(Some(9_000));
+        let mut terminal = Terminal::new(TestBackend::new(80, 40)).unwrap();
+        terminal
+            .draw(|frame| main_ui(frame, &Theme::default(), &mut app_state).unwrap())
+            .unwrap();
+
+        let (start, end) = app_state.render_window;
+        assert!(start <= 9_000 && 9_000 < end);
+        // Two of the 37 rows show how many items are above and below
+        assert_eq!(end - start, 35);
+    }
+
+    #[test]
+    fn wrapped_items_keep_the_selection_on_screen() {
+        let mut app_state = AppState::default();
+        let long = "Repaint the fence along the garden";
+        app_state.extend_items((0..20).map(|i| TodoItem::from(format!("{} {}", long, i))));
+        app_state.wrap = true;
+        app_state.todo_list_state.select(Some(19));
+        let mut terminal = Terminal::new(TestBackend::new(40, 20)).unwrap();
+        terminal
+            .draw(|frame| main_ui(frame, &Theme::default(), &mut app_state).unwrap())
+            .unwrap();
+
+        let (start, end) = app_state.render_window;
+        assert_eq!(end, 20);
+        assert_eq!(end - start, 8);
+        // Every item takes two rows of description
+        assert!(app_state.item_rows.windows(2).all(|rows| rows[1] - rows[0] == 2));
+    }
+}
@@ -0,0 +1,68 @@
+use crate::app::TodoList;
+use crate::item::TodoItem;
+use chrono::{DateTime, Local};
+
+#[derive(Clone)]
+pub struct Snapshot {
+    pub label: String,
+    pub at: DateTime<Local>,
+    pub lists: Vec<TodoList>,
+    pub archived: Vec<TodoItem>,
+    pub active_list: usize,
+}
+
+// The lists before each change, and after each undone one for redo
+#[derive(Default)]
+pub struct UndoStack {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl UndoStack {
+    // A new change, which can't be followed by a redo; the oldest entries beyond
+    // max_depth are dropped
+    pub fn push(&mut self, snapshot: Snapshot, max_depth: usize) {
+        self.redo.clear();
+        self.undo.push(snapshot);
+        if self.undo.len() > max_depth {
+            self.undo.drain(..self.undo.len() - max_depth);
+        }
+    }
+
+    // The state to go back to, keeping `current` to redo the step
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let snapshot = self.undo.pop()?;
+        self.redo.push(Snapshot { label: snapshot.label.clone(), ..current });
+        Some(snapshot)
+    }
+
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let snapshot = self.redo.pop()?;
+        self.undo.push(Snapshot { label: snapshot.label.clone(), ..current });
+        Some(snapshot)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    // Oldest first
+    pub fn entries(&self) -> &[Snapshot] {
+        &self.undo
+    }
+
+    // The snapshot at `index`, taking it and every newer one as if undone one by one
+    pub fn pop_to(&mut self, index: usize, mut current: Snapshot) -> Option<Snapshot> {
+        if index >= self.undo.len() {
+            return None;
+        }
+        while self.undo.len() > index {
+            current = self.undo(current)?;
+        }
+        Some(current)
+    }
+}
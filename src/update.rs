@@ -0,0 +1,63 @@
+use crate::error::AppError;
+use serde::Deserialize;
+use std::sync::Mutex;
+use std::thread;
+
+const URL: &str = "https://crates.io/api/v1/crates/todo-tui";
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(rename = "crate")]
+    krate: Crate,
+}
+
+#[derive(Deserialize)]
+struct Crate {
+    newest_version: String,
+}
+
+// Set by the background check when crates.io has a newer release
+static NEWER: Mutex<Option<String>> = Mutex::new(None);
+
+// Ask crates.io for the newest release without holding up the start, failures are silent
+pub fn check_in_background() {
+    thread::spawn(|| {
+        if let Ok(newest) = newest_version() {
+            if is_newer(&newest, env!("CARGO_PKG_VERSION")) {
+                *NEWER.lock().unwrap_or_else(|e| e.into_inner()) = Some(newest);
+            }
+        }
+    });
+}
+
+// The newer version found, once
+pub fn take_newer_version() -> Option<String> {
+    NEWER.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+fn newest_version() -> Result<String, AppError> {
+    let body = reqwest::blocking::Client::new()
+        .get(URL)
+        // crates.io refuses requests without one
+        .header("User-Agent", concat!("todo-tui/", env!("CARGO_PKG_VERSION")))
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .map_err(|e| AppError::Io(std::io::Error::other(e)))?;
+    let response: Response = serde_json::from_str(&body)?;
+    Ok(response.krate.newest_version)
+}
+
+// Compares the numbers of major.minor.patch, a pre-release suffix is ignored
+fn is_newer(version: &str, current: &str) -> bool {
+    let parts = |version: &str| -> Vec<u64> {
+        version
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    parts(version) > parts(current)
+}
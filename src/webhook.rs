@@ -0,0 +1,84 @@
+use crate::config::Config;
+use crate::item::TodoItem;
+use hmac::{Hmac, KeyInit, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Sha256;
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    ItemAdded,
+    ItemCompleted,
+    ItemDeleted,
+    ItemEdited,
+}
+
+impl WebhookEvent {
+    pub const ALL: &'static [WebhookEvent] = &[
+        WebhookEvent::ItemAdded,
+        WebhookEvent::ItemCompleted,
+        WebhookEvent::ItemDeleted,
+        WebhookEvent::ItemEdited,
+    ];
+}
+
+// Deliveries still in flight, waited for before the process exits
+static PENDING: Mutex<Vec<JoinHandle<()>>> = Mutex::new(vec![]);
+
+// POST the event to the configured URL in the background
+pub fn notify(config: &Config, event: WebhookEvent, item: &TodoItem) {
+    let Some(url) = config.webhook_url.clone() else {
+        return;
+    };
+    if !config.webhook_events.contains(&event) {
+        return;
+    }
+    let body = json!({ "event": event, "item": item }).to_string();
+    let signature = config.webhook_secret.as_deref().map(|secret| sign(secret, &body));
+    let handle = thread::spawn(move || {
+        if post(&url, &body, signature.as_deref()).is_err() {
+            thread::sleep(Duration::from_secs(2));
+            let _ = post(&url, &body, signature.as_deref());
+        }
+    });
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    pending.retain(|handle| !handle.is_finished());
+    pending.push(handle);
+}
+
+// Deliveries still being sent or retried
+pub fn pending() -> usize {
+    let pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    pending.iter().filter(|handle| !handle.is_finished()).count()
+}
+
+pub fn wait_for_pending() {
+    let pending = std::mem::take(&mut *PENDING.lock().unwrap_or_else(|e| e.into_inner()));
+    for handle in pending {
+        let _ = handle.join();
+    }
+}
+
+fn post(url: &str, body: &str, signature: Option<&str>) -> reqwest::Result<()> {
+    let mut request = reqwest::blocking::Client::new()
+        .post(url)
+        .header("Content-Type", "application/json")
+        .body(body.to_owned());
+    if let Some(signature) = signature {
+        request = request.header("X-Signature", signature);
+    }
+    request.send()?.error_for_status()?;
+    Ok(())
+}
+
+// "sha256=<hex HMAC of the body>", the receiver recomputes it with the shared secret
+fn sign(secret: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("any key length");
+    mac.update(body.as_bytes());
+    let hex: String = mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", hex)
+}
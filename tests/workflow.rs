@@ -0,0 +1,176 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use std::fs;
+use todo_list::app::AppState;
+use todo_list::config::Config;
+use todo_list::item::TodoItem;
+use todo_list::storage::{FileBackend, InMemoryBackend, StorageBackend};
+
+fn press(app: &mut AppState, key: KeyCode) {
+    app.handle_key_event(KeyEvent::from(key));
+}
+
+fn type_text(app: &mut AppState, text: &str) {
+    for c in text.chars() {
+        press(app, KeyCode::Char(c));
+    }
+}
+
+#[test]
+fn add_edit_delete() {
+    let mut app = AppState::new_with_backend(&InMemoryBackend::new()).unwrap();
+
+    press(&mut app, KeyCode::Char('a'));
+    type_text(&mut app, "Buy milk");
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.items().len(), 1);
+
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Char('e'));
+    for _ in 0.."milk".len() {
+        press(&mut app, KeyCode::Backspace);
+    }
+    type_text(&mut app, "oat milk");
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.items()[0].description, "Buy oat milk");
+
+    press(&mut app, KeyCode::Char('d'));
+    press(&mut app, KeyCode::Char('y'));
+    assert!(app.items().is_empty());
+}
+
+#[test]
+fn manual_order_survives_a_restart() {
+    let dir = std::env::temp_dir().join(format!("todo-tui-order-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    let mut backend = FileBackend::new(dir.join("todos.json"), None);
+    let mut app = AppState::new_with_backend(&backend).unwrap();
+    app.extend_items(["A", "B", "C"].map(TodoItem::from));
+
+    // Dragging the second row onto the first
+    app.reorder(1, 0);
+    backend.save(&app.save_file()).unwrap();
+
+    let app = AppState::new_with_backend(&backend).unwrap();
+    let order: Vec<&str> = app.items().iter().map(|item| item.description.as_str()).collect();
+    assert_eq!(order, ["B", "A", "C"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unreadable_save_file_starts_empty_and_is_reported() {
+    let dir = std::env::temp_dir().join(format!("todo-tui-load-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("todos.json");
+    let app = AppState::new(Config::default(), Some(&path), None);
+    assert!(!app.load_failed);
+    assert!(app.items().is_empty());
+
+    fs::write(&path, "not json").unwrap();
+    let app = AppState::new(Config::default(), Some(&path), None);
+    assert!(app.load_failed);
+    assert!(app.items().is_empty());
+    assert!(app.status_message.is_some_and(|message| message.contains("todos.json")));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn archived_items_are_saved_and_restored() {
+    let mut backend = InMemoryBackend::new();
+    let mut app = AppState::new_with_backend(&backend).unwrap();
+    app.extend_items(["A", "B", "C"].map(TodoItem::from));
+    app.get_mut(1).unwrap().set_done(true);
+    assert_eq!(app.archive_completed(), 1);
+    backend.save(&app.save_file()).unwrap();
+
+    let mut app = AppState::new_with_backend(&backend).unwrap();
+    assert_eq!(app.items().len(), 2);
+    assert_eq!(app.archived[0].description, "B");
+    app.restore_archived(0);
+    let order: Vec<&str> = app.items().iter().map(|item| item.description.as_str()).collect();
+    assert_eq!(order, ["A", "C", "B"]);
+    assert!(app.archived.is_empty());
+}
+
+#[test]
+fn search_replace_covers_notes_and_undoes_at_once() {
+    let mut app = AppState::new_with_backend(&InMemoryBackend::new()).unwrap();
+    app.extend_items([
+        TodoItem { description: "Call Bob about the bob sled".to_owned(), ..TodoItem::default() },
+        TodoItem { description: "Pay rent".to_owned(), notes: Some("ask BOB first".to_owned()), ..TodoItem::default() },
+    ]);
+
+    assert_eq!(app.search_replace("Bob", "Alice", true), 1);
+    assert_eq!(app.items()[0].description, "Call Alice about the bob sled");
+    assert_eq!(app.search_replace("bob", "Carol", false), 2);
+    assert_eq!(app.items()[0].description, "Call Alice about the Carol sled");
+    assert_eq!(app.items()[1].notes.as_deref(), Some("ask Carol first"));
+
+    app.undo();
+    assert_eq!(app.items()[0].description, "Call Alice about the bob sled");
+    assert_eq!(app.items()[1].notes.as_deref(), Some("ask BOB first"));
+}
+
+#[test]
+fn macro_that_replays_itself_stops() {
+    let mut app = AppState::new_with_backend(&InMemoryBackend::new()).unwrap();
+    app.extend_items([TodoItem::from("A")]);
+    app.todo_list_state.select(Some(0));
+    press(&mut app, KeyCode::Char('Q'));
+    type_text(&mut app, " @qQ");
+    assert!(app.items()[0].done);
+    type_text(&mut app, "@q");
+
+    let mut replayed = 0;
+    while let Some(key) = app.next_macro_key() {
+        app.handle_key_event(key);
+        replayed += 1;
+        assert!(replayed < 100, "the macro kept replaying itself");
+    }
+    assert!(!app.items()[0].done);
+}
+
+#[test]
+fn bad_config_values_cost_only_their_own_setting() {
+    let dir = std::env::temp_dir().join(format!("todo-tui-config-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config.toml");
+    fs::write(&path, "wrap_navigation = true\naging_warn_days = \"lots\"\nkey_add = \"x\"\n").unwrap();
+
+    let config = Config::load(Some(&path));
+    assert!(config.wrap_navigation);
+    assert_eq!(config.aging_warn_days, Config::default().aging_warn_days);
+    // The file keeps what couldn't be read
+    assert!(config.path.is_none());
+    let error = Config::read(&path).err().unwrap().to_string();
+    assert!(error.contains("aging_warn_days") && error.contains("key_add"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn iter_mut_yields_what_iter_shows() {
+    let mut app = AppState::new_with_backend(&InMemoryBackend::new()).unwrap();
+    app.extend_items(["Buy milk", "Call Bob", "Buy bread"].map(TodoItem::from));
+    press(&mut app, KeyCode::Char('/'));
+    type_text(&mut app, "buy");
+    press(&mut app, KeyCode::Enter);
+
+    let shown: Vec<String> = app.iter().map(|item| item.description.clone()).collect();
+    assert_eq!(shown.len(), 2);
+    let changed: Vec<String> = app.iter_mut().map(|item| item.description.clone()).collect();
+    assert_eq!(changed, shown);
+}
+
+#[test]
+fn editing_away_the_description_is_refused() {
+    let mut app = AppState::new_with_backend(&InMemoryBackend::new()).unwrap();
+    app.extend_items([TodoItem::from("Buy milk")]);
+    app.todo_list_state.select(Some(0));
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Char('e'));
+    for _ in 0.."Buy milk".len() {
+        press(&mut app, KeyCode::Backspace);
+    }
+    press(&mut app, KeyCode::Enter);
+    assert!(app.input_error.is_some());
+    assert_eq!(app.items()[0].description, "Buy milk");
+}